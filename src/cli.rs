@@ -4,9 +4,12 @@ use clap::Parser;
 use clap::Subcommand;
 use clap_complete::Shell;
 
+use crate::config::ConfigFormat;
 use crate::config::GitSetting;
-use crate::error::TextProviderError;
 use crate::format::Format;
+use crate::text_provider::PathTextProvider;
+use crate::text_provider::StdinTextProvider;
+use crate::text_provider::TextProvider;
 
 /// Get CLI args via `clap` while also handling when we are invoked as a cargo
 /// subcommand
@@ -28,27 +31,69 @@ pub struct Args {
 
     #[clap(flatten)]
     pub(crate) verbose: clap_verbosity_flag::Verbosity,
+
+    /// Use this configuration file instead of searching for one in the repository root
+    #[clap(long, global = true)]
+    pub(crate) config: Option<PathBuf>,
+
+    /// Parse the configuration file as this format, instead of detecting it from its extension
+    #[clap(long, global = true, value_enum)]
+    pub(crate) config_format: Option<ConfigFormat>,
+
+    /// Use this fragment directory (relative to the repository root) instead of the configured
+    /// one
+    #[clap(long, global = true)]
+    pub(crate) fragment_dir: Option<PathBuf>,
+
+    /// Write the changelog to this file (relative to the repository root) instead of the
+    /// configured one
+    ///
+    /// Takes precedence over the CARGO_CHANGELOG_OUTPUT environment variable.
+    #[clap(long, global = true)]
+    pub(crate) changelog: Option<PathBuf>,
+
+    /// Load fragments with at most this many threads, instead of the configured number
+    #[clap(long, global = true)]
+    pub(crate) threads: Option<std::num::NonZeroUsize>,
+
+    /// Never prompt or open an editor, e.g. in CI
+    ///
+    /// "add" does not ask for values and fails if a required value is missing, or if prompts or
+    /// the editor are requested explicitly with "--interactive true" or "--edit true". "edit"
+    /// fails.
+    #[clap(long, global = true)]
+    pub(crate) no_interactive: bool,
 }
 
 #[derive(Subcommand)]
 pub enum Command {
     /// Initialize the repository for cargo-changelog
-    Init,
+    Init {
+        /// Use a template that renders a table of contents of all versions
+        #[clap(long)]
+        toc: bool,
+    },
 
     /// Create a new changelog fragment
     Add {
-        #[clap(short, long, action = clap::ArgAction::Set, default_value_t = true)]
-        interactive: bool,
+        /// Ask for the values of the header fields [default: true, false with --no-interactive]
+        #[clap(short, long, action = clap::ArgAction::Set)]
+        interactive: Option<bool>,
 
-        #[clap(short, long, action = clap::ArgAction::Set, default_value_t = true)]
-        edit: bool,
+        /// Edit the text in $EDITOR [default: true, false with --no-interactive]
+        #[clap(short, long, action = clap::ArgAction::Set)]
+        edit: Option<bool>,
 
         #[clap(short, long, value_enum, value_parser, default_value_t = Format::Toml)]
         format: Format,
 
         /// Read the changelog entry text from some path or stdin (via "-")
-        #[clap(long, value_parser = text_provider_parser)]
-        read: Option<TextProvider>,
+        #[clap(long, value_parser = text_source_parser)]
+        read: Option<TextSource>,
+
+        /// Use this text as the changelog entry text
+        #[clap(long, conflicts_with = "read")]
+        text: Option<String>,
 
         /// Set a header field to a specific value, non-interactively
         ///
@@ -70,10 +115,51 @@ pub enum Command {
         /// used.
         #[clap(long, value_enum, value_parser)]
         git: Option<GitSetting>,
+
+        /// Place the new entry in the directory of the next version, computed from the version in
+        /// the Cargo.toml, instead of the "unreleased" directory
+        #[clap(long, value_enum, value_parser)]
+        bump: Option<Bump>,
+
+        /// Whether to create the directory for the new entry if it does not exist yet
+        ///
+        /// Only the directory directly inside the fragment directory is created.
+        #[clap(long, action = clap::ArgAction::Set, default_value_t = true)]
+        create_dirs: bool,
+
+        /// Name the new entry by a hash of its header and text, instead of the current time
+        ///
+        /// Identical entries get the same name, so adding an entry that already exists fails.
+        #[clap(long)]
+        hash_name: bool,
+
+        /// Print the path and the contents of the new entry, instead of creating it
+        ///
+        /// No editor is spawned. The text of the entry is a placeholder, unless it is given with
+        /// "--text".
+        #[clap(long)]
+        dry_run: bool,
+
+        /// Pre-fill the new entry from this GitHub pull request
+        ///
+        /// The title of the pull request becomes the text, its number the "issue" and a matching
+        /// label the "type". The API token is read from GITHUB_TOKEN or GH_TOKEN.
+        #[cfg(feature = "github")]
+        #[clap(long, conflicts_with = "read")]
+        from_pr: Option<u64>,
     },
 
     /// Verify the metadata in existing changelog fragments
-    VerifyMetadata,
+    #[clap(alias = "verify")]
+    VerifyMetadata {
+        /// Print the problems as JSON (with "path", "kind" and "message" of each problem) instead
+        /// of a human readable report
+        #[clap(long)]
+        format: Option<VerifyFormat>,
+
+        /// Only verify these fragment files, e.g. the file that is currently edited
+        files: Vec<PathBuf>,
+    },
 
     /// Use the current unreleased changelog fragments to generate the changelog for the next
     /// release
@@ -86,18 +172,119 @@ pub enum Command {
         #[clap(long)]
         all: bool,
 
+        /// Always write an "unreleased" section, even if there are no unreleased fragments
+        ///
+        /// Implies "--all".
+        #[clap(long)]
+        include_unreleased: bool,
+
         #[clap(long, default_value_t = false)]
         allow_dirty: bool,
+
+        /// Commit the generated changelog and the changes in the fragment directory
+        ///
+        /// The commit message can be configured with the "release_commit_message" setting.
+        /// Refuses to commit if there are other changes staged in the repository.
+        #[clap(long)]
+        commit: bool,
+
+        /// Create an annotated tag for the release commit
+        ///
+        /// The name of the tag can be configured with the "tag_format" setting.
+        #[clap(long, requires = "commit")]
+        tag: bool,
+
+        /// Do not write the changelog, but fail with a diff if the existing changelog differs from
+        /// the generated one
+        #[clap(long, conflicts_with = "commit")]
+        check: bool,
+
+        /// Use this template instead of the configured one
+        #[clap(long)]
+        template: Option<PathBuf>,
+
+        /// Use a built-in template instead of the configured one
+        ///
+        /// A template given with "--template" takes precedence.
+        #[clap(long)]
+        format: Option<TemplatePreset>,
+
+        /// Only include fragments with this value in their "author" header field
+        ///
+        /// As the changelog is incomplete then, this requires "--check", "--emit-version-only" or
+        /// an explicit changelog path (e.g. "--changelog"), and the "outputs" setting is ignored.
+        #[clap(long, conflicts_with = "commit")]
+        author: Option<String>,
+
+        /// Only include fragments for this audience, e.g. "internal"
+        ///
+        /// Fragments without an "audience" header field are for the audience in the
+        /// "default_audience" setting.
+        #[clap(long)]
+        audience: Option<String>,
+
+        /// Resolve relative links in the text of fragments against this URL
+        ///
+        /// Overrides the "base_url" setting.
+        #[clap(long)]
+        base_url: Option<String>,
+
+        /// Also render the changelog to this file, with this template
+        ///
+        /// The path is relative to the repository root, the template relative to the fragment
+        /// directory. Can be given multiple times, in addition to the "outputs" setting.
+        #[clap(long = "output", value_name = "PATH=TEMPLATE")]
+        outputs: Vec<crate::config::Output>,
+
+        /// Also write a JSON manifest of the fragment files in each version to this file
+        ///
+        /// The path is relative to the repository root.
+        #[clap(long, conflicts_with_all = ["check", "emit_version_only"])]
+        manifest: Option<PathBuf>,
+
+        /// Do not write the changelog, but print the section of this version to stdout
+        ///
+        /// The section is rendered without the title of the changelog, e.g. for release notes.
+        #[clap(long, conflicts_with_all = ["commit", "check"])]
+        emit_version_only: Option<String>,
+
+        /// Only render versions from this one on (inclusive)
+        #[clap(long)]
+        from: Option<semver::Version>,
+
+        /// Only render versions up to this one (inclusive), leaving out unreleased fragments
+        #[clap(long)]
+        to: Option<semver::Version>,
+
+        /// Open the generated changelog in $EDITOR (or $VISUAL), or the default application
+        ///
+        /// Does nothing if not run in a terminal, or with "--no-interactive".
+        #[clap(long, conflicts_with_all = ["check", "emit_version_only"])]
+        open: bool,
     },
 
     Show {
         #[clap(long)]
         format: Option<ShowFormat>,
+
+        /// Do not colorize the output, even if STDOUT is a terminal
+        ///
+        /// Color is also disabled if the NO_COLOR environment variable is set.
+        #[clap(long)]
+        no_color: bool,
+
+        /// Only show fragments with this value in their "author" header field
+        #[clap(long)]
+        author: Option<String>,
+
         #[clap(subcommand)]
         selector: Option<Selector>,
     },
     /// Generation completions for the shell of your choice, available options:
     /// [bash, elvish, fish, powershell, zsh]
+    ///
+    /// The completion script is printed to stdout.
+    #[clap(visible_alias = "completions")]
     GenerationCompletions {
         #[clap(value_parser)]
         shell: Shell,
@@ -112,11 +299,61 @@ pub enum Command {
         #[clap(subcommand)]
         selector: Selector,
     },
+
+    /// List the versions that have changelog fragments, with the number of fragments each
+    ListVersions {
+        #[clap(long)]
+        format: Option<ListVersionsFormat>,
+    },
+
+    /// Move a changelog fragment to another version
+    ///
+    /// With the "flat" layout, the "version" header field of the fragment is updated instead.
+    Move {
+        /// The fragment file to move
+        fragment: PathBuf,
+
+        /// The version to move the fragment to
+        new_version: String,
+    },
+
+    /// Print a JSON Schema describing the header of changelog fragments
+    ///
+    /// The schema is derived from the configured header fields and can be used for editor
+    /// integration.
+    Schema,
+
+    /// Print the number of changelog fragments, in total and by version, type and author
+    Stats {
+        #[clap(long)]
+        format: Option<StatsFormat>,
+    },
+
+    /// Open an existing fragment in $EDITOR (or $VISUAL)
+    ///
+    /// The fragment is only changed if it is still valid after editing.
+    Edit {
+        /// The fragment file to edit
+        fragment: PathBuf,
+    },
+
+    /// Add a type to the possible values of the "type" header field in the configuration
+    ///
+    /// The configuration file is edited in place, keeping its comments and formatting.
+    AddType {
+        /// The name of the new type, e.g. "Security"
+        name: String,
+    },
+
+    /// Move the fragments of released versions into the configured database file
+    ///
+    /// Unreleased fragments are left as they are.
+    Compact,
 }
 
-fn text_provider_parser(s: &str) -> Result<TextProvider, String> {
+fn text_source_parser(s: &str) -> Result<TextSource, String> {
     if s == "-" {
-        return Ok(TextProvider::Stdin);
+        return Ok(TextSource::Stdin);
     }
 
     let path = PathBuf::from(s);
@@ -128,7 +365,7 @@ fn text_provider_parser(s: &str) -> Result<TextProvider, String> {
         return Err(format!("Path is not a file: {}", path.display()));
     }
 
-    Ok(TextProvider::Path(path))
+    Ok(TextSource::Path(path))
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, getset::Getters)]
@@ -139,6 +376,13 @@ pub struct KV {
     value: String,
 }
 
+impl KV {
+    #[cfg(any(test, feature = "github"))]
+    pub fn new(key: String, value: String) -> Self {
+        Self { key, value }
+    }
+}
+
 fn kv_value_parser(s: &str) -> Result<KV, String> {
     if s.chars().filter(|c| *c == '=').count() != 1 {
         Err(format!("Cannot parse as key-value: '{s}'"))
@@ -151,27 +395,18 @@ fn kv_value_parser(s: &str) -> Result<KV, String> {
     }
 }
 
+/// Where to read the text of a changelog entry from
 #[derive(Clone, Debug, PartialEq, Eq)]
-pub enum TextProvider {
+pub enum TextSource {
     Stdin,
     Path(PathBuf),
 }
 
-impl TextProvider {
-    pub fn read(&self) -> Result<String, TextProviderError> {
-        use std::io::Read;
-
+impl TextSource {
+    pub fn provider(&self) -> Box<dyn TextProvider> {
         match self {
-            TextProvider::Stdin => {
-                let mut buf = Vec::new();
-
-                std::io::stdin().read_to_end(&mut buf)?;
-
-                String::from_utf8(buf).map_err(TextProviderError::from)
-            }
-            TextProvider::Path(path) => {
-                std::fs::read_to_string(path).map_err(TextProviderError::from)
-            }
+            TextSource::Stdin => Box::new(StdinTextProvider),
+            TextSource::Path(path) => Box::new(PathTextProvider(path.clone())),
         }
     }
 }
@@ -187,12 +422,67 @@ pub enum VersionSpec {
     },
 }
 
+#[derive(
+    Copy, Clone, Debug, PartialEq, Eq, clap::ValueEnum, serde::Deserialize, serde::Serialize,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum Bump {
+    Patch,
+    Minor,
+    Major,
+}
+
+/// The built-in templates `generate-changelog` can use instead of a template file
+#[derive(Copy, Clone, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum TemplatePreset {
+    /// Following <https://keepachangelog.com>
+    Keepachangelog,
+
+    /// A plain list of subjects per version
+    Simple,
+
+    /// In the style of GitHub release notes
+    Github,
+}
+
+impl TemplatePreset {
+    /// The source of the template
+    pub fn source(self) -> &'static str {
+        match self {
+            TemplatePreset::Keepachangelog => crate::consts::KEEPACHANGELOG_TEMPLATE,
+            TemplatePreset::Simple => crate::consts::SIMPLE_TEMPLATE,
+            TemplatePreset::Github => crate::consts::GITHUB_TEMPLATE,
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, clap::ValueEnum)]
 pub enum ShowFormat {
     Text,
     Json,
 }
 
+#[derive(Clone, Debug, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum VerifyFormat {
+    #[default]
+    Text,
+    Json,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum ListVersionsFormat {
+    #[default]
+    Text,
+    Json,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum StatsFormat {
+    #[default]
+    Text,
+    Json,
+}
+
 #[derive(Clone, Debug, Subcommand, serde::Serialize)]
 #[serde(rename_all = "lowercase")]
 pub enum Selector {
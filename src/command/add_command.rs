@@ -1,4 +1,3 @@
-use std::collections::HashMap;
 use std::path::Path;
 use std::path::PathBuf;
 use std::process::Command;
@@ -6,11 +5,15 @@ use std::process::Command;
 use dialoguer::Confirm;
 use dialoguer::Input;
 use dialoguer::Select;
+use indexmap::IndexMap;
 
-use crate::cli::TextProvider;
+use crate::cli::Bump;
+use crate::cli::TextSource;
 use crate::cli::KV;
 use crate::config::Configuration;
 use crate::config::GitSetting;
+use crate::config::Layout;
+use crate::config::MissingRequired;
 use crate::error::Error;
 use crate::error::FragmentError;
 use crate::error::InteractiveError;
@@ -20,15 +23,28 @@ use crate::fragment::FragmentData;
 use crate::fragment::FragmentDataDesc;
 use crate::fragment::FragmentDataType;
 use crate::fragment::FragmentDataTypeDefinite;
+use crate::text_provider::EditorTextProvider;
+use crate::text_provider::LiteralTextProvider;
+use crate::text_provider::TextProvider;
 
 #[derive(Debug, typed_builder::TypedBuilder)]
 pub struct AddCommand {
-    interactive: bool,
-    edit: bool,
+    /// Whether to prompt for header values, by default unless `no_interactive`
+    interactive: Option<bool>,
+    /// Whether to open the editor, by default unless `no_interactive`
+    edit: Option<bool>,
+    /// Fail if prompts or the editor are requested explicitly
+    no_interactive: bool,
     format: Format,
     set: Vec<KV>,
-    text: Option<TextProvider>,
+    read: Option<TextSource>,
+    text: Option<String>,
     git: Option<GitSetting>,
+    bump: Option<Bump>,
+    create_dirs: bool,
+    hash_name: bool,
+    dry_run: bool,
+    quiet: bool,
 }
 
 impl crate::command::Command for AddCommand {
@@ -37,37 +53,46 @@ impl crate::command::Command for AddCommand {
         workdir: &Path,
         config: &Configuration,
     ) -> Result<Option<std::process::ExitCode>, Error> {
-        let unreleased_dir_path = ensure_fragment_dir(workdir, config)?;
-
-        let new_file_path = {
-            let new_file_name = format!(
-                "{ts}.md",
-                ts = {
-                    // We cannot use the well-known formats here, because cargo cannot package
-                    // filenames with ":" in it, but the well-known formats contain this character.
-                    // Hence we have to use our own.
-                    let fragment_file_timestamp_format = time::macros::format_description!(
-                        "[year]-[month]-[day]T[hour]_[minute]_[second]_[subsecond]"
-                    );
-                    time::OffsetDateTime::now_utc().format(&fragment_file_timestamp_format)?
-                },
-            );
-            unreleased_dir_path.join(new_file_name)
+        let interactive = self.input_enabled(self.interactive)?;
+        let edit = self.input_enabled(self.edit)?;
+
+        let next_version = self
+            .bump
+            .map(|bump| crate::command::common::next_version(workdir, bump))
+            .transpose()?;
+        let target_dir_path = if self.dry_run {
+            let path = fragment_dir_path(workdir, config, next_version.as_ref());
+            if !path.is_dir() && !self.create_dirs {
+                return Err(Error::VersionDirDoesNotExist(path));
+            }
+            path
+        } else {
+            ensure_fragment_dir(workdir, config, next_version.as_ref(), self.create_dirs)?
         };
 
-        let mut file = std::fs::OpenOptions::new()
-            .create(true)
-            .truncate(true)
-            .write(true)
-            .append(false)
-            .open(&new_file_path)?;
+        let now = time::OffsetDateTime::now_utc();
+        let file_stem = {
+            // We cannot use the well-known formats here, because cargo cannot package
+            // filenames with ":" in it, but the well-known formats contain this character.
+            // Hence we have to use our own.
+            let fragment_file_timestamp_format = time::macros::format_description!(
+                "[year]-[month]-[day]T[hour]_[minute]_[second]_[subsecond]"
+            );
+            now.format(&fragment_file_timestamp_format)?
+        };
+        let mut new_file_path = if self.dry_run {
+            target_dir_path.join(format!("{file_stem}.md"))
+        } else {
+            create_fragment_file(&target_dir_path, &file_stem)?
+        };
 
         let mut fragment = crate::fragment::Fragment::empty();
 
-        if let Some(text_provider) = self.text.as_ref() {
-            let text = text_provider.read()?;
-            fragment.set_text(text);
-        }
+        // Keys given on the commandline can be in any case
+        let set_key = |kv: &KV| match config.key_case() {
+            Some(key_case) => key_case.convert(kv.key()),
+            None => kv.key().to_string(),
+        };
 
         // Fill the fragment header with data
         *fragment.header_mut() = config
@@ -77,14 +102,29 @@ impl crate::command::Command for AddCommand {
                 let cli_set: Option<FragmentData> = match self
                     .set
                     .iter()
-                    .find(|kv| kv.key() == key)
+                    .find(|kv| set_key(kv) == *key)
                     .map(KV::value)
-                    .map(|val| FragmentData::parse(val))
+                    .map(|val| FragmentData::parse_as(val, data_desc.fragment_type()))
                 {
                     Some(Ok(val)) => Some(val),
                     Some(Err(e)) => return Some(Err(e)),
                     None => None,
                 };
+                let cli_set = match cli_set {
+                    Some(val) if config.coerce_scalars() => {
+                        Some(data_desc.fragment_type().coerce(&val).unwrap_or(val))
+                    }
+                    other => other,
+                };
+                let cli_set = match cli_set {
+                    Some(val) if config.normalize_enum_case() => Some(
+                        data_desc
+                            .fragment_type()
+                            .normalize_case(&val)
+                            .unwrap_or(val),
+                    ),
+                    other => other,
+                };
                 let crawler = data_desc.crawler();
                 let default_value = data_desc.default_value();
 
@@ -111,8 +151,11 @@ impl crate::command::Command for AddCommand {
                 }
 
                 match (default_value, cli_set, crawler) {
+                    // computed from the other fields after they are all known
+                    (None, None, None) if data_desc.default_template().is_some() => None,
+
                     (Some(default), None, None) => {
-                        if self.interactive {
+                        if interactive {
                             interactive_edit(key, default, data_desc)
                                 .map_err(FragmentError::from)
                                 .transpose()
@@ -122,7 +165,7 @@ impl crate::command::Command for AddCommand {
                     }
 
                     (_, Some(clival), _) => {
-                        if self.interactive {
+                        if interactive {
                             interactive_edit(key, &clival, data_desc)
                                 .map_err(FragmentError::from)
                                 .transpose()
@@ -155,14 +198,14 @@ impl crate::command::Command for AddCommand {
 
                     (None, None, None) => {
                         if data_desc.required() {
-                            if self.interactive {
+                            if interactive {
                                 interactive_provide(key, data_desc)
                                     .map_err(FragmentError::from)
                                     .transpose()
                             } else {
-                                Some(Err(FragmentError::RequiredValueMissing(key.to_string())))
+                                missing_required_value(key, data_desc, config.missing_required())
                             }
-                        } else if self.interactive {
+                        } else if interactive {
                             match ask_do_provide_data_for(key, data_desc)
                                 .map_err(FragmentError::from)
                             {
@@ -178,28 +221,92 @@ impl crate::command::Command for AddCommand {
                     }
                 }
             })
-            .collect::<Result<HashMap<String, FragmentData>, _>>()
+            .collect::<Result<IndexMap<String, FragmentData>, _>>()
             .map_err(|e| Error::Fragment(e, new_file_path.to_path_buf()))?;
 
-        fragment
-            .write_to(&mut file, self.format)
+        let templated_values = config
+            .header_fields()
+            .iter()
+            .filter(|(key, _)| !fragment.header().contains_key(*key))
+            .filter_map(|(key, data_desc)| {
+                data_desc.default_template().as_ref().map(|template| {
+                    render_default_template(key, template, data_desc, fragment.header())
+                        .map(|value| (key.to_string(), value))
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()
             .map_err(|e| Error::Fragment(e, new_file_path.to_path_buf()))?;
-        file.sync_all()?;
-        drop(file);
-
-        if self.edit {
-            let mut editor_command = get_editor_command()?;
-            let std::process::Output { status, .. } = editor_command
-                .arg(&new_file_path)
-                .stderr(std::process::Stdio::inherit())
-                .stdout(std::process::Stdio::inherit())
-                .output()?;
-
-            if status.success() {
-                tracing::info!("Successfully edited");
-            } else {
-                tracing::error!("Failure editing {}", new_file_path.display());
+        fragment.header_mut().extend(templated_values);
+
+        if let (Layout::Flat, Some(version)) = (config.layout(), next_version.as_ref()) {
+            fragment.header_mut().insert(
+                crate::consts::VERSION_HEADER_FIELD.to_string(),
+                FragmentData::Str(version.to_string()),
+            );
+        }
+
+        // A creation time given on the commandline is used as is
+        let created_field = config.created_field();
+        if config.record_created() && !fragment.header().contains_key(created_field) {
+            let created = match self.set.iter().find(|kv| set_key(kv) == *created_field) {
+                Some(kv) => kv.value().to_string(),
+                None => now.format(&time::format_description::well_known::Rfc3339)?,
+            };
+            fragment
+                .header_mut()
+                .insert(created_field.to_string(), FragmentData::Str(created));
+        }
+
+        fragment.sort_header(config.header_fields());
+        if let Some(key_case) = config.key_case() {
+            fragment
+                .normalize_key_case(key_case)
+                .map_err(|e| Error::Fragment(e, new_file_path.to_path_buf()))?;
+        }
+
+        if self.dry_run {
+            let text = self.text.as_deref().unwrap_or(DRY_RUN_PLACEHOLDER_TEXT);
+            fragment.set_text(text.to_string());
+            if self.hash_name {
+                new_file_path = new_file_path.with_file_name(format!(
+                    "{}.md",
+                    fragment.content_hash(&[config.created_field()])
+                ));
+            }
+
+            print_dry_run(
+                &mut std::io::stdout().lock(),
+                workdir,
+                &new_file_path,
+                &fragment,
+                self.format,
+            )?;
+            return Ok(None);
+        }
+
+        write_fragment(&fragment, &new_file_path, self.format)?;
+        for text_provider in self.text_providers(edit) {
+            text_provider.provide(&mut fragment, &new_file_path)?;
+            // The editor can change the header as well
+            if let Some(key_case) = config.key_case() {
+                fragment
+                    .normalize_key_case(key_case)
+                    .map_err(|e| Error::Fragment(e, new_file_path.to_path_buf()))?;
             }
+            write_fragment(&fragment, &new_file_path, self.format)?;
+        }
+
+        if self.hash_name {
+            new_file_path = rename_to_content_hash(&new_file_path, &fragment, config)?;
+        }
+
+        if !self.quiet {
+            print_summary(
+                &mut std::io::stdout().lock(),
+                workdir,
+                &new_file_path,
+                &fragment,
+            )?;
         }
 
         match self.git.as_ref().or_else(|| config.git().as_ref()) {
@@ -243,30 +350,203 @@ impl crate::command::Command for AddCommand {
     }
 }
 
-fn ensure_fragment_dir(workdir: &Path, config: &Configuration) -> Result<PathBuf, Error> {
-    let unreleased_dir_path = workdir
-        .join(config.fragment_dir())
-        .join(crate::consts::UNRELEASED_DIR_NAME);
-    std::fs::create_dir_all(&unreleased_dir_path)?;
-    Ok(unreleased_dir_path)
+impl AddCommand {
+    /// Whether the input (prompts or editor) `requested` on the commandline is enabled
+    ///
+    /// Input is enabled by default, unless `no_interactive` is set. Explicitly requesting it
+    /// together with `no_interactive` fails.
+    fn input_enabled(&self, requested: Option<bool>) -> Result<bool, InteractiveError> {
+        match requested {
+            Some(true) if self.no_interactive => Err(InteractiveError::Disabled),
+            Some(requested) => Ok(requested),
+            None => Ok(!self.no_interactive),
+        }
+    }
+
+    /// Select the providers for the text of the new entry, in the order they are applied
+    fn text_providers(&self, edit: bool) -> Vec<Box<dyn TextProvider>> {
+        let mut providers = Vec::new();
+
+        if let Some(read) = self.read.as_ref() {
+            providers.push(read.provider());
+        }
+
+        if let Some(text) = self.text.as_ref() {
+            providers.push(Box::new(LiteralTextProvider(text.clone())) as Box<dyn TextProvider>);
+        }
+
+        if edit {
+            providers.push(Box::new(EditorTextProvider));
+        }
+
+        providers
+    }
+}
+
+/// The text of the entry printed with `--dry-run`, if no text is given on the commandline
+const DRY_RUN_PLACEHOLDER_TEXT: &str = "<text of the changelog entry>";
+
+/// The directory a new entry for `version` is placed in
+fn fragment_dir_path(
+    workdir: &Path,
+    config: &Configuration,
+    version: Option<&semver::Version>,
+) -> PathBuf {
+    let dir_name = match (config.layout(), version) {
+        (Layout::Flat, _) => crate::consts::FLAT_DIR_NAME.to_string(),
+        (Layout::Versioned, Some(version)) => version.to_string(),
+        (Layout::Versioned, None) => crate::consts::UNRELEASED_DIR_NAME.to_string(),
+    };
+
+    workdir.join(config.fragment_dir()).join(dir_name)
+}
+
+fn ensure_fragment_dir(
+    workdir: &Path,
+    config: &Configuration,
+    version: Option<&semver::Version>,
+    create_dirs: bool,
+) -> Result<PathBuf, Error> {
+    let root_path = workdir.join(config.fragment_dir());
+    let fragment_dir_path = fragment_dir_path(workdir, config, version);
+    if fragment_dir_path.is_dir() {
+        return Ok(fragment_dir_path);
+    }
+
+    if !create_dirs {
+        return Err(Error::VersionDirDoesNotExist(fragment_dir_path));
+    }
+
+    // Only ever create the directory directly inside the fragment directory, so a misconfigured
+    // fragment directory does not result in a deep directory tree somewhere
+    if !root_path.is_dir() {
+        return Err(Error::FragmentDirDoesNotExist(root_path));
+    }
+
+    match std::fs::create_dir(&fragment_dir_path) {
+        Ok(()) => {
+            tracing::debug!("Created directory {}", fragment_dir_path.display());
+            Ok(fragment_dir_path)
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => Ok(fragment_dir_path),
+        Err(e) => Err(Error::from(e)),
+    }
 }
 
-fn get_editor_command() -> Result<Command, Error> {
-    let editor = match std::env::var("EDITOR") {
-        Ok(editor) => editor,
-        Err(std::env::VarError::NotPresent) => match std::env::var("VISUAL") {
-            Ok(editor) => editor,
-            Err(std::env::VarError::NotPresent) => return Err(Error::EditorEnvNotSet),
-            Err(std::env::VarError::NotUnicode(_)) => {
-                return Err(Error::EnvNotUnicode("VISUAL".to_string()))
+/// Create a new, empty fragment file, without overwriting an existing file of the same name
+///
+/// The file is created atomically (`O_EXCL`), so concurrent invocations of `add` never write to
+/// the same file. If the name is already taken, a counter is appended to the name.
+fn create_fragment_file(dir: &Path, file_stem: &str) -> Result<PathBuf, Error> {
+    let mut counter = 0;
+    loop {
+        let file_name = if counter == 0 {
+            format!("{file_stem}.md")
+        } else {
+            format!("{file_stem}-{counter}.md")
+        };
+        let path = dir.join(file_name);
+
+        match std::fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&path)
+        {
+            Ok(_) => return Ok(path),
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists && counter < 100 => {
+                tracing::debug!("File exists, trying next name: {}", path.display());
+                counter += 1;
             }
-        },
-        Err(std::env::VarError::NotUnicode(_)) => {
-            return Err(Error::EnvNotUnicode("EDITOR".to_string()))
+            Err(e) => return Err(Error::from(e)),
         }
-    };
+    }
+}
+
+/// Rename the fragment file to the hash of its contents
+///
+/// If a fragment with the same name exists already, the new fragment file is removed and an error
+/// is returned, as the fragment is a duplicate. The creation time is not part of the hash, so
+/// fragments that only differ in it are duplicates as well.
+fn rename_to_content_hash(
+    path: &Path,
+    fragment: &crate::fragment::Fragment,
+    config: &Configuration,
+) -> Result<PathBuf, Error> {
+    let hash = fragment.content_hash(&[config.created_field()]);
+    let hashed_path = path.with_file_name(format!("{hash}.md"));
+
+    // Linking fails if the file exists, so an existing fragment is never replaced
+    match std::fs::hard_link(path, &hashed_path) {
+        Ok(()) => {
+            std::fs::remove_file(path)?;
+            Ok(hashed_path)
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+            std::fs::remove_file(path)?;
+            Err(Error::FragmentExists(hashed_path))
+        }
+        Err(e) => Err(Error::from(e)),
+    }
+}
+
+/// Print where the fragment was created, with its header and the first line of its text
+fn print_summary<W: std::io::Write>(
+    out: &mut W,
+    workdir: &Path,
+    path: &Path,
+    fragment: &crate::fragment::Fragment,
+) -> Result<(), Error> {
+    let path = path.strip_prefix(workdir).unwrap_or(path);
+    writeln!(out, "Created {}", path.display())?;
+
+    // In the order of the written file, i.e. as the header fields are declared
+    for (key, value) in fragment.header() {
+        writeln!(out, "  {key}: {}", value.display())?;
+    }
+
+    if let Some(first_line) = fragment.text().lines().find(|line| !line.trim().is_empty()) {
+        writeln!(out, "  {first_line}")?;
+    }
+    Ok(())
+}
+
+/// Print where the fragment would be created and what would be written to it
+fn print_dry_run<W: std::io::Write>(
+    out: &mut W,
+    workdir: &Path,
+    path: &Path,
+    fragment: &crate::fragment::Fragment,
+    format: Format,
+) -> Result<(), Error> {
+    let relative_path = path.strip_prefix(workdir).unwrap_or(path);
+    writeln!(out, "Would create {}", relative_path.display())?;
+    if let Some(dir) = path.parent().filter(|dir| !dir.is_dir()) {
+        let dir = dir.strip_prefix(workdir).unwrap_or(dir);
+        writeln!(out, "Would create directory {}", dir.display())?;
+    }
+    writeln!(out)?;
 
-    Ok(Command::new(editor))
+    fragment
+        .to_writer(out, format)
+        .map_err(|e| Error::Fragment(e, path.to_path_buf()))
+}
+
+/// Write the fragment to the file at `path`, replacing its contents
+fn write_fragment(
+    fragment: &crate::fragment::Fragment,
+    path: &Path,
+    format: Format,
+) -> Result<(), Error> {
+    let mut file = std::fs::OpenOptions::new()
+        .write(true)
+        .truncate(true)
+        .open(path)?;
+
+    fragment
+        .to_writer(&mut file, format)
+        .map_err(|e| Error::Fragment(e, path.to_path_buf()))?;
+    file.sync_all()?;
+    Ok(())
 }
 
 /// Ask interactively whether these values are okay or should be changed
@@ -310,6 +590,26 @@ fn ask_do_provide_data_for(
         .map_err(InteractiveError::from)
 }
 
+/// Handle a required header field without a value, as configured with `missing_required`
+fn missing_required_value(
+    key: &str,
+    desc: &FragmentDataDesc,
+    missing_required: MissingRequired,
+) -> Option<Result<(String, FragmentData), FragmentError>> {
+    let placeholder = FragmentData::Str(crate::consts::MISSING_REQUIRED_PLACEHOLDER.to_string());
+    match missing_required {
+        MissingRequired::Error => Some(Err(FragmentError::RequiredValueMissing(key.to_string()))),
+        MissingRequired::Placeholder if desc.fragment_type().matches(&placeholder) => {
+            tracing::warn!("Required value '{key}' is missing, using a placeholder");
+            Some(Ok((key.to_string(), placeholder)))
+        }
+        MissingRequired::Warn | MissingRequired::Placeholder => {
+            tracing::warn!("Required value '{key}' is missing, leaving it out");
+            None
+        }
+    }
+}
+
 /// Let the user provide a value matching the description interactively
 fn interactive_provide(
     key: &str,
@@ -344,7 +644,7 @@ fn interactive_provide(
             Ok(Some((key.to_string(), FragmentData::Bool(value))))
         }
         FragmentDataType::Ty(FragmentDataTypeDefinite::Int) => {
-            let dialoguer = Input::<u64>::new().with_prompt(format!("Enter a number for '{key}'"));
+            let dialoguer = Input::<i64>::new().with_prompt(format!("Enter a number for '{key}'"));
 
             let dialoguer = if let Some(data) = desc.default_value() {
                 if let FragmentData::Int(i) = data {
@@ -362,6 +662,33 @@ fn interactive_provide(
             let value = dialoguer.interact_text().map_err(InteractiveError::from)?;
             Ok(Some((key.to_string(), FragmentData::Int(value))))
         }
+        FragmentDataType::Ty(FragmentDataTypeDefinite::Float) => {
+            let dialoguer = Input::<f64>::new()
+                .with_prompt(format!("Enter a number for '{key}'"))
+                .validate_with(|f: &f64| {
+                    if f.is_finite() {
+                        Ok(())
+                    } else {
+                        Err("Value must be a finite number")
+                    }
+                });
+
+            let dialoguer = if let Some(data) = desc.default_value() {
+                if let FragmentData::Float(f) = data {
+                    dialoguer.default(*f)
+                } else {
+                    return Err(InteractiveError::TypeError(
+                        desc.fragment_type().clone(),
+                        data.clone(),
+                    ));
+                }
+            } else {
+                dialoguer
+            };
+
+            let value = dialoguer.interact_text().map_err(InteractiveError::from)?;
+            Ok(Some((key.to_string(), FragmentData::Float(value))))
+        }
         FragmentDataType::Ty(FragmentDataTypeDefinite::Str) => {
             let dialoguer = Input::<String>::new().with_prompt(format!("Enter a text for '{key}'"));
 
@@ -381,6 +708,14 @@ fn interactive_provide(
             let value = dialoguer.interact_text().map_err(InteractiveError::from)?;
             Ok(Some((key.to_string(), FragmentData::Str(value))))
         }
+        FragmentDataType::Ty(FragmentDataTypeDefinite::Map) => {
+            // Maps cannot be entered in a prompt, but only in the editor
+            tracing::info!("Skipping '{key}', maps can only be edited in the editor");
+            Ok(desc
+                .default_value()
+                .clone()
+                .map(|data| (key.to_string(), data)))
+        }
         FragmentDataType::OneOf(possible_values) => {
             let dialoguer = Select::new()
                 .items(possible_values)
@@ -423,6 +758,36 @@ fn interactive_provide(
     }
 }
 
+/// Render the default value template of a field with the already provided header fields
+fn render_default_template(
+    field_name: &str,
+    template: &str,
+    desc: &FragmentDataDesc,
+    header: &IndexMap<String, FragmentData>,
+) -> Result<FragmentData, FragmentError> {
+    let mut handlebars = handlebars::Handlebars::new();
+    handlebars.register_escape_fn(handlebars::no_escape);
+    handlebars.set_strict_mode(true);
+
+    let rendered = handlebars
+        .render_template(template, header)
+        .map_err(|source| FragmentError::DefaultTemplate {
+            field_name: field_name.to_string(),
+            source: Box::new(source),
+        })?;
+
+    let data = FragmentData::parse_as(&rendered, desc.fragment_type())?;
+    if desc.fragment_type().matches(&data) {
+        Ok(data)
+    } else {
+        Err(FragmentError::DataType {
+            exp: desc.fragment_type().type_name(),
+            recv: data.type_name().to_string(),
+            field_name: field_name.to_string(),
+        })
+    }
+}
+
 fn crawl_with_crawler(
     crawler: &Crawler,
     field_name: &str,
@@ -458,7 +823,7 @@ fn crawl_with_crawler(
             .trim()
             .to_string();
         tracing::info!("crawled = '{}'", out);
-        let data = FragmentData::parse(&out)?;
+        let data = FragmentData::parse_as(&out, expected_type)?;
         if expected_type.matches(&data) {
             Ok(data)
         } else {
@@ -472,3 +837,94 @@ fn crawl_with_crawler(
         Err(FragmentError::CommandNoSuccess(command_str))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_create_fragment_file_does_not_overwrite() {
+        let temp_dir = tempfile::tempdir().unwrap();
+
+        let first = create_fragment_file(temp_dir.path(), "2023-01-01T00_00_00_0").unwrap();
+        let second = create_fragment_file(temp_dir.path(), "2023-01-01T00_00_00_0").unwrap();
+
+        assert_eq!(first, temp_dir.path().join("2023-01-01T00_00_00_0.md"));
+        assert_eq!(second, temp_dir.path().join("2023-01-01T00_00_00_0-1.md"));
+    }
+
+    #[test]
+    fn test_add_with_literal_text() {
+        use crate::command::Command;
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(temp_dir.path().join(".changelogs")).unwrap();
+        let config: Configuration = toml::from_str(crate::config::DEFAULT_CONFIG).unwrap();
+
+        let set = ["issue=123", "subject=Subject", "type=Feature"]
+            .iter()
+            .map(|s| {
+                let (key, value) = s.split_once('=').unwrap();
+                KV::new(key.to_string(), value.to_string())
+            })
+            .collect();
+
+        let result = AddCommand::builder()
+            .interactive(Some(false))
+            .edit(Some(false))
+            .no_interactive(false)
+            .format(Format::Toml)
+            .set(set)
+            .read(None)
+            .text(Some("Some literal text".to_string()))
+            .git(None)
+            .bump(None)
+            .create_dirs(true)
+            .hash_name(false)
+            .dry_run(false)
+            .quiet(true)
+            .build()
+            .execute(temp_dir.path(), &config);
+        assert!(result.is_ok(), "Not ok: {:?}", result.unwrap_err());
+
+        let unreleased_dir = temp_dir.path().join(".changelogs").join("unreleased");
+        let files = std::fs::read_dir(unreleased_dir)
+            .unwrap()
+            .collect::<Vec<_>>();
+        assert_eq!(files.len(), 1);
+
+        let path = files[0].as_ref().unwrap().path();
+        let fragment =
+            crate::fragment::Fragment::from_reader(&mut std::fs::File::open(path).unwrap())
+                .unwrap();
+        assert_eq!(fragment.text(), "Some literal text");
+    }
+
+    #[test]
+    fn test_print_summary() {
+        let fragment = crate::fragment::Fragment::new(
+            IndexMap::from([
+                (
+                    "subject".to_string(),
+                    FragmentData::Str("Subject".to_string()),
+                ),
+                ("issue".to_string(), FragmentData::Int(123)),
+            ]),
+            "\nFirst line\nSecond line".to_string(),
+        );
+
+        let mut out = Vec::new();
+        print_summary(
+            &mut out,
+            Path::new("/repo"),
+            Path::new("/repo/.changelogs/unreleased/fragment.md"),
+            &fragment,
+        )
+        .unwrap();
+
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "Created .changelogs/unreleased/fragment.md\n  subject: Subject\n  issue: 123\n  First line\n"
+        );
+    }
+}
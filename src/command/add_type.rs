@@ -0,0 +1,82 @@
+use std::path::{Path, PathBuf};
+
+use crate::{
+    config::{ConfigFormat, Configuration},
+    error::Error,
+    fragment::FragmentDataType,
+};
+
+/// Add a possible value to the "type" header field in the configuration file
+#[derive(Debug, typed_builder::TypedBuilder)]
+pub struct AddTypeCommand {
+    name: String,
+    /// The configuration file, `None` if the configuration is not in a file of its own
+    config_file: Option<PathBuf>,
+    config_format: Option<ConfigFormat>,
+}
+
+impl crate::command::Command for AddTypeCommand {
+    fn execute(
+        self,
+        workdir: &Path,
+        config: &Configuration,
+    ) -> Result<Option<std::process::ExitCode>, Error> {
+        let types = match config
+            .header_fields()
+            .get(crate::consts::TYPE_HEADER_FIELD)
+            .map(|desc| desc.fragment_type())
+        {
+            Some(FragmentDataType::OneOf(types)) => types,
+            _ => return Err(Error::NoTypeList),
+        };
+        // Types differing only in case would be confusing, especially with `normalize_enum_case`
+        if types.iter().any(|ty| ty.eq_ignore_ascii_case(&self.name)) {
+            return Err(Error::TypeExists(self.name));
+        }
+
+        let config_file = self.config_file.ok_or(Error::ConfigNotEditable)?;
+        let format = self
+            .config_format
+            .or_else(|| ConfigFormat::from_path(&config_file))
+            .unwrap_or(ConfigFormat::Toml);
+        if format != ConfigFormat::Toml {
+            return Err(Error::ConfigNotEditable);
+        }
+
+        let mut document =
+            std::fs::read_to_string(&config_file)?.parse::<toml_edit::DocumentMut>()?;
+        let possible_values = document
+            .get_mut("header_fields")
+            .and_then(toml_edit::Item::as_table_like_mut)
+            .and_then(|fields| fields.get_mut(crate::consts::TYPE_HEADER_FIELD))
+            .and_then(toml_edit::Item::as_table_like_mut)
+            .and_then(|field| field.get_mut("type"))
+            .and_then(toml_edit::Item::as_array_mut)
+            .ok_or(Error::NoTypeList)?;
+        // Move the whitespace before the closing bracket behind the new value
+        let suffix = possible_values
+            .iter_mut()
+            .last()
+            .and_then(|last| {
+                let suffix = last.decor().suffix().cloned();
+                last.decor_mut().set_suffix("");
+                suffix
+            })
+            .unwrap_or_default();
+        possible_values.push(self.name.as_str());
+        if let Some(new) = possible_values.iter_mut().last() {
+            new.decor_mut().set_suffix(suffix);
+        }
+        std::fs::write(&config_file, document.to_string())?;
+
+        println!(
+            "Added type '{}' to {}",
+            self.name,
+            config_file
+                .strip_prefix(workdir)
+                .unwrap_or(&config_file)
+                .display()
+        );
+        Ok(None)
+    }
+}
@@ -1,10 +1,63 @@
+use std::ffi::OsStr;
 use std::path::Path;
 
 use crate::{
-    cli::VersionSpec,
+    cli::{Bump, VersionSpec},
+    config::{Configuration, Layout},
     error::{Error, VersionError},
+    fragment::{Fragment, FragmentData},
 };
 
+/// Find all fragment files in the fragment directory
+///
+/// Fragment files are markdown files in a subdirectory of the fragment directory.
+///
+/// Symlinks are only followed if configured. Symlink loops and directories deeper than the
+/// configured maximum depth are skipped with a warning.
+pub fn find_fragment_files(
+    workdir: &Path,
+    config: &Configuration,
+) -> impl Iterator<Item = Result<walkdir::DirEntry, walkdir::Error>> {
+    let root_path = workdir.join(config.fragment_dir());
+    let max_depth = config.max_walk_depth();
+    walkdir::WalkDir::new(root_path.clone())
+        .follow_links(config.follow_symlinks())
+        .max_depth(max_depth)
+        .max_open(100)
+        .same_file_system(true)
+        .into_iter()
+        .filter_map(move |rde| match rde {
+            Err(e) if e.loop_ancestor().is_some() => {
+                tracing::warn!("Skipping symlink loop: {e}");
+                None
+            }
+            Err(e) => Some(Err(e)),
+            Ok(de) if de.file_type().is_dir() && de.depth() == max_depth => {
+                tracing::warn!(
+                    "Skipping directory deeper than max_walk_depth ({max_depth}): {}",
+                    de.path().display()
+                );
+                None
+            }
+            Ok(de) => {
+                if de.file_type().is_file() {
+                    // Since the file itself comes from the workdir, this cannot fail?
+                    let clean_path = de.path().strip_prefix(root_path.as_path()).unwrap();
+                    if clean_path.components().count() <= 1
+                        || de.path().extension() != Some(OsStr::new("md"))
+                    {
+                        None
+                    } else {
+                        tracing::debug!("Considering: {:?}", de);
+                        Some(Ok(de))
+                    }
+                } else {
+                    None
+                }
+            }
+        })
+}
+
 pub fn get_version_from_path(path: &Path) -> Result<Option<semver::Version>, VersionError> {
     path.components()
         .find_map(|comp| match comp {
@@ -15,13 +68,12 @@ pub fn get_version_from_path(path: &Path) -> Result<Option<semver::Version>, Ver
 
                 match s {
                     Err(e) => Some(Err(e)),
-                    Ok(s) => {
+                    // Windows paths are split on non-Windows platforms as well, e.g. for paths
+                    // written on Windows
+                    Ok(s) => s.split('\\').find_map(|s| {
                         tracing::trace!("Parsing '{}' as semver", s);
-                        match semver::Version::parse(s) {
-                            Err(_) => None,
-                            Ok(semver) => Some(Ok(semver)),
-                        }
-                    }
+                        semver::Version::parse(s).ok().map(Ok)
+                    }),
                 }
             }
             _ => None,
@@ -29,34 +81,267 @@ pub fn get_version_from_path(path: &Path) -> Result<Option<semver::Version>, Ver
         .transpose()
 }
 
-pub fn find_version_string(workdir: &Path, version: &VersionSpec) -> Result<String, Error> {
-    use cargo_metadata::MetadataCommand;
+/// Get the version of a fragment, depending on the configured layout
+///
+/// With the versioned layout, the version directory is the version of the fragment. A `version`
+/// header field that does not match it is ignored with a warning.
+pub fn get_version_of_fragment(
+    path: &Path,
+    fragment: &Fragment,
+    layout: Layout,
+) -> Result<Option<semver::Version>, VersionError> {
+    let header_version = fragment.header().get(crate::consts::VERSION_HEADER_FIELD);
+
+    match layout {
+        Layout::Versioned => {
+            let version = get_version_from_path(path)?;
+            if let Some(header_version) = header_version {
+                let matches = match (header_version, version.as_ref()) {
+                    (FragmentData::Str(s), Some(version)) => {
+                        semver::Version::parse(s).is_ok_and(|v| v == *version)
+                    }
+                    _ => false,
+                };
+                if !matches {
+                    tracing::warn!(
+                        "Ignoring the version header field of {}, the layout is 'versioned'",
+                        path.display()
+                    );
+                }
+            }
+            Ok(version)
+        }
+        Layout::Flat => {
+            if get_version_from_path(path)?.is_some() {
+                return Err(VersionError::VersionDirInFlatLayout(path.to_path_buf()));
+            }
 
+            match header_version {
+                None => Ok(None),
+                Some(FragmentData::Str(s)) => semver::Version::parse(s)
+                    .map(Some)
+                    .map_err(|_| VersionError::InvalidVersionHeader(path.to_path_buf())),
+                Some(_) => Err(VersionError::InvalidVersionHeader(path.to_path_buf())),
+            }
+        }
+    }
+}
+
+pub fn find_version_string(workdir: &Path, version: &VersionSpec) -> Result<String, Error> {
     if let VersionSpec::Custom { custom } = version {
         Ok(custom.clone())
     } else {
-        let metadata = MetadataCommand::new()
-            .manifest_path(workdir.join("./Cargo.toml"))
-            .exec()?;
+        current_version(workdir).map(|v| v.to_string())
+    }
+}
+
+/// Get all versions a fragment is shown under
+///
+/// The `versions` header field, listing e.g. the versions a change was backported to, takes
+/// precedence over the version directory or `version` header field of the fragment. These are
+/// still checked, though.
+pub fn get_versions_of_fragment(
+    path: &Path,
+    fragment: &Fragment,
+    layout: Layout,
+) -> Result<Vec<Option<semver::Version>>, VersionError> {
+    let version = get_version_of_fragment(path, fragment, layout)?;
+
+    match fragment.header().get(crate::consts::VERSIONS_HEADER_FIELD) {
+        None => Ok(vec![version]),
+        Some(FragmentData::List(list)) if !list.is_empty() => {
+            let mut versions = Vec::with_capacity(list.len());
+            for value in list {
+                let version = match value {
+                    FragmentData::Str(s) => semver::Version::parse(s)
+                        .map_err(|_| VersionError::InvalidVersionsHeader(path.to_path_buf()))?,
+                    _ => return Err(VersionError::InvalidVersionsHeader(path.to_path_buf())),
+                };
+                if !versions.contains(&Some(version.clone())) {
+                    versions.push(Some(version));
+                }
+            }
+            Ok(versions)
+        }
+        Some(_) => Err(VersionError::InvalidVersionsHeader(path.to_path_buf())),
+    }
+}
+
+/// Get the version of the crate(s) in the workspace from the Cargo.toml
+///
+/// If members inherit the `[workspace.package]` version of the workspace root with
+/// `version.workspace = true`, that is the version. Otherwise all crates of the workspace must
+/// have the same version.
+pub fn current_version(workdir: &Path) -> Result<semver::Version, Error> {
+    use cargo_metadata::MetadataCommand;
 
-        let workspace_member_ids = &metadata.workspace_members;
+    let metadata = MetadataCommand::new()
+        .manifest_path(workdir.join("./Cargo.toml"))
+        .exec()?;
 
-        let versions = metadata
-            .packages
-            .iter()
-            .filter(|pkg| workspace_member_ids.contains(&pkg.id))
-            .map(|pkg| &pkg.version)
-            .collect::<Vec<_>>();
+    let workspace_member_ids = &metadata.workspace_members;
+    let members = metadata
+        .packages
+        .iter()
+        .filter(|pkg| workspace_member_ids.contains(&pkg.id))
+        .collect::<Vec<_>>();
 
-        if versions.is_empty() {
-            return Err(Error::NoVersionInCargoToml);
+    if let Some(version) = workspace_package_version(metadata.workspace_root.as_std_path())? {
+        // Members with a version of their own are not released with the workspace version
+        for member in members.iter() {
+            if inherits_workspace_version(member.manifest_path.as_std_path())? {
+                tracing::debug!(
+                    "Using [workspace.package] version {version}, inherited by {}",
+                    member.name
+                );
+                return Ok(version);
+            }
         }
+    }
+
+    let versions = members.iter().map(|pkg| &pkg.version).collect::<Vec<_>>();
+
+    if versions.is_empty() {
+        return Err(Error::NoVersionInCargoToml);
+    }
+
+    let first = versions[0];
+    let all_versions_same = versions.iter().all(|v| *v == first);
+    if !all_versions_same {
+        return Err(Error::WorkspaceVersionsNotEqual);
+    }
+    Ok(first.clone())
+}
+
+/// Get the `[workspace.package]` version from the Cargo.toml in the workspace root, if any
+fn workspace_package_version(workspace_root: &Path) -> Result<Option<semver::Version>, Error> {
+    let manifest = std::fs::read_to_string(workspace_root.join("Cargo.toml"))?;
+    let manifest = toml::from_str::<toml::Table>(&manifest)?;
+
+    let version = manifest
+        .get("workspace")
+        .and_then(|workspace| workspace.get("package"))
+        .and_then(|package| package.get("version"))
+        .and_then(toml::Value::as_str);
+
+    version
+        .map(semver::Version::parse)
+        .transpose()
+        .map_err(Error::from)
+}
+
+/// Whether the package in the Cargo.toml at `manifest_path` has `version.workspace = true`
+fn inherits_workspace_version(manifest_path: &Path) -> Result<bool, Error> {
+    let manifest = std::fs::read_to_string(manifest_path)?;
+    let manifest = toml::from_str::<toml::Table>(&manifest)?;
+
+    Ok(manifest
+        .get("package")
+        .and_then(|package| package.get("version"))
+        .and_then(|version| version.get("workspace"))
+        .and_then(toml::Value::as_bool)
+        .unwrap_or(false))
+}
+
+/// Compute the next version from the version in the Cargo.toml
+pub fn next_version(workdir: &Path, bump: Bump) -> Result<semver::Version, Error> {
+    current_version(workdir).map(|current| bump_version(&current, bump))
+}
 
-        let first = versions[0];
-        let all_versions_same = versions.iter().all(|v| *v == first);
-        if !all_versions_same {
-            return Err(Error::WorkspaceVersionsNotEqual);
+/// Bump `current`, dropping its prerelease and build metadata
+///
+/// A prerelease of the version the bump leads to is released instead, e.g. "1.0.0-rc.1" becomes
+/// "1.0.0" with any bump, "1.1.0-beta" with a minor (but not a major) bump.
+pub fn bump_version(current: &semver::Version, bump: Bump) -> semver::Version {
+    let is_pre = !current.pre.is_empty();
+    match bump {
+        Bump::Patch if is_pre => semver::Version::new(current.major, current.minor, current.patch),
+        Bump::Minor if is_pre && current.patch == 0 => {
+            semver::Version::new(current.major, current.minor, 0)
+        }
+        Bump::Major if is_pre && current.minor == 0 && current.patch == 0 => {
+            semver::Version::new(current.major, 0, 0)
         }
-        Ok(first.to_string())
+        Bump::Patch => semver::Version::new(current.major, current.minor, current.patch + 1),
+        Bump::Minor => semver::Version::new(current.major, current.minor + 1, 0),
+        Bump::Major => semver::Version::new(current.major + 1, 0, 0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_version_from_path() {
+        let version = |path: &str| get_version_from_path(Path::new(path)).unwrap();
+        let expected = Some(semver::Version::new(0, 1, 0));
+
+        assert_eq!(version(".changelogs/0.1.0/fix.md"), expected);
+        assert_eq!(version(r".changelogs\0.1.0\fix.md"), expected);
+        assert_eq!(version(r"C:\repo\.changelogs\0.1.0\fix.md"), expected);
+        assert_eq!(version(".changelogs/unreleased/fix.md"), None);
+        assert_eq!(version(r".changelogs\unreleased\fix.md"), None);
+    }
+
+    #[test]
+    fn test_version_header_in_versioned_layout() {
+        let fragment = |version: &str| {
+            let mut fragment = Fragment::empty();
+            fragment.header_mut().insert(
+                crate::consts::VERSION_HEADER_FIELD.to_string(),
+                FragmentData::Str(version.to_string()),
+            );
+            fragment
+        };
+        let path = Path::new("0.1.0/fragment.md");
+        let expected = Some(semver::Version::new(0, 1, 0));
+
+        assert_eq!(
+            get_version_of_fragment(path, &fragment("0.1.0"), Layout::Versioned).unwrap(),
+            expected
+        );
+        // The version directory takes precedence
+        assert_eq!(
+            get_version_of_fragment(path, &fragment("0.2.0"), Layout::Versioned).unwrap(),
+            expected
+        );
+        assert_eq!(
+            get_version_of_fragment(
+                Path::new("unreleased/fragment.md"),
+                &fragment("0.2.0"),
+                Layout::Versioned
+            )
+            .unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_bump_version() {
+        let current = semver::Version::parse("0.1.2").unwrap();
+        assert_eq!(bump_version(&current, Bump::Patch).to_string(), "0.1.3");
+        assert_eq!(bump_version(&current, Bump::Minor).to_string(), "0.2.0");
+        assert_eq!(bump_version(&current, Bump::Major).to_string(), "1.0.0");
+    }
+
+    #[test]
+    fn test_bump_version_drops_prerelease() {
+        let bump = |current: &str, bump: Bump| {
+            bump_version(&semver::Version::parse(current).unwrap(), bump).to_string()
+        };
+
+        assert_eq!(bump("1.0.0-rc.1+build.5", Bump::Patch), "1.0.0");
+        assert_eq!(bump("1.0.0-rc.1+build.5", Bump::Minor), "1.0.0");
+        assert_eq!(bump("1.0.0-rc.1+build.5", Bump::Major), "1.0.0");
+        assert_eq!(bump("2.0.0-rc.1", Bump::Major), "2.0.0");
+
+        assert_eq!(bump("1.1.0-beta", Bump::Patch), "1.1.0");
+        assert_eq!(bump("1.1.0-beta", Bump::Minor), "1.1.0");
+        assert_eq!(bump("1.1.0-beta", Bump::Major), "2.0.0");
+
+        assert_eq!(bump("1.0.1-alpha", Bump::Patch), "1.0.1");
+        assert_eq!(bump("1.0.1-alpha", Bump::Minor), "1.1.0");
+        assert_eq!(bump("1.0.1-alpha", Bump::Major), "2.0.0");
     }
 }
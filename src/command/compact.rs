@@ -0,0 +1,72 @@
+use std::path::{Path, PathBuf};
+
+use crate::{config::Configuration, error::Error, fragment::Fragment};
+
+/// Move the fragments of released versions into the database
+#[derive(Debug, typed_builder::TypedBuilder)]
+pub struct CompactCommand {}
+
+impl crate::command::Command for CompactCommand {
+    fn execute(
+        self,
+        workdir: &Path,
+        config: &Configuration,
+    ) -> Result<Option<std::process::ExitCode>, Error> {
+        let fragment_dir = workdir.join(config.fragment_dir());
+        let database_path = fragment_dir.join(config.database().as_ref().ok_or(Error::NoDatabase)?);
+        let ignore_file = crate::ignore_file::IgnoreFile::load(&fragment_dir)?;
+
+        let mut released = Vec::<(PathBuf, Vec<semver::Version>, Fragment)>::new();
+        for rde in crate::command::common::find_fragment_files(workdir, config) {
+            let path = rde?.into_path();
+            let relative_path = path.strip_prefix(&fragment_dir).unwrap_or(&path);
+            if ignore_file
+                .as_ref()
+                .is_some_and(|ignore_file| ignore_file.is_ignored(relative_path))
+            {
+                tracing::debug!("Ignoring {}", path.display());
+                continue;
+            }
+
+            let mut fragment = Fragment::from_path(&path)?;
+            let versions = crate::command::common::get_versions_of_fragment(
+                relative_path,
+                &fragment,
+                config.layout(),
+            )?
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>();
+            if versions.is_empty() {
+                tracing::debug!("Not released: {}", path.display());
+                continue;
+            }
+
+            let source_path = path.strip_prefix(workdir).unwrap_or(&path);
+            fragment.set_source_path(source_path.to_path_buf());
+            released.push((path, versions, fragment));
+        }
+
+        // Only remove the fragment files once they are safely in the database
+        crate::database::append(
+            &database_path,
+            released.iter().flat_map(|(_, versions, fragment)| {
+                versions.iter().map(move |version| (version, fragment))
+            }),
+        )?;
+        for (path, _, _) in released.iter() {
+            tracing::info!("Compacted {}", path.display());
+            std::fs::remove_file(path)?;
+        }
+
+        println!(
+            "Compacted {} fragments into {}",
+            released.len(),
+            database_path
+                .strip_prefix(workdir)
+                .unwrap_or(&database_path)
+                .display()
+        );
+        Ok(None)
+    }
+}
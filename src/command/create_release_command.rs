@@ -1,7 +1,12 @@
 use std::path::{Path, PathBuf};
 
 use crate::{
-    cli::VersionSpec, command::common::find_version_string, config::Configuration, error::Error,
+    cli::VersionSpec,
+    command::common::find_version_string,
+    config::{Configuration, Layout},
+    error::Error,
+    format::Format,
+    fragment::{Fragment, FragmentData},
 };
 
 #[derive(Debug, typed_builder::TypedBuilder)]
@@ -16,6 +21,11 @@ impl crate::command::Command for CreateReleaseCommand {
         config: &Configuration,
     ) -> Result<Option<std::process::ExitCode>, Error> {
         let version_string = find_version_string(workdir, &self.version)?;
+
+        if config.layout() == Layout::Flat {
+            return release_flat(workdir, config, &version_string).map(|_| None);
+        }
+
         tracing::debug!("Creating new directory for version '{}'", version_string);
         let release_dir = ensure_release_dir(workdir, config, &version_string)?;
         let unreleased_dir = workdir
@@ -46,6 +56,55 @@ impl crate::command::Command for CreateReleaseCommand {
     }
 }
 
+/// Set the version header field on all unreleased fragments
+fn release_flat(workdir: &Path, config: &Configuration, version_string: &str) -> Result<(), Error> {
+    // make sure we do not write an invalid version into the fragments
+    let version = semver::Version::parse(version_string)?;
+    let flat_dir = workdir
+        .join(config.fragment_dir())
+        .join(crate::consts::FLAT_DIR_NAME);
+
+    if !flat_dir.exists() {
+        return Ok(());
+    }
+
+    for rdirentry in std::fs::read_dir(&flat_dir)? {
+        let path = rdirentry?.path();
+        if !path.is_file() || path.ends_with(".gitkeep") {
+            continue;
+        }
+
+        let mut fragment = Fragment::from_path(&path)?;
+
+        if fragment
+            .header()
+            .contains_key(crate::consts::VERSION_HEADER_FIELD)
+        {
+            continue;
+        }
+
+        tracing::info!("Releasing {} as {}", path.display(), version);
+        fragment.header_mut().insert(
+            crate::consts::VERSION_HEADER_FIELD.to_string(),
+            FragmentData::Str(version.to_string()),
+        );
+
+        // Serialize first, so that the file is not truncated if that fails
+        let content = fragment
+            .to_string(Format::Toml)
+            .map_err(|e| Error::Fragment(e, path.clone()))?;
+        let mut file = std::fs::OpenOptions::new()
+            .create(false)
+            .truncate(true)
+            .write(true)
+            .open(&path)?;
+        std::io::Write::write_all(&mut file, content.as_bytes())?;
+        file.sync_all()?;
+    }
+
+    Ok(())
+}
+
 fn ensure_release_dir(
     workdir: &Path,
     config: &Configuration,
@@ -0,0 +1,80 @@
+use std::path::{Path, PathBuf};
+
+use crate::{
+    config::Configuration,
+    error::{Error, InteractiveError},
+    fragment::Fragment,
+    text_provider::{EditorTextProvider, TextProvider},
+};
+
+/// Open an existing fragment in the editor
+#[derive(Debug, typed_builder::TypedBuilder)]
+pub struct EditCommand {
+    fragment: PathBuf,
+    /// Fail instead of opening the editor
+    no_interactive: bool,
+}
+
+impl crate::command::Command for EditCommand {
+    fn execute(
+        self,
+        workdir: &Path,
+        config: &Configuration,
+    ) -> Result<Option<std::process::ExitCode>, Error> {
+        if self.no_interactive {
+            return Err(InteractiveError::Disabled.into());
+        }
+
+        if !self.fragment.is_file() {
+            return Err(Error::NotAFile(self.fragment));
+        }
+
+        let root = workdir.join(config.fragment_dir());
+        if !self
+            .fragment
+            .canonicalize()?
+            .starts_with(root.canonicalize()?)
+        {
+            return Err(Error::NotInFragmentDir(self.fragment));
+        }
+
+        if self.fragment.extension() != Some(std::ffi::OsStr::new("md")) {
+            return Err(Error::NotAFragment(self.fragment));
+        }
+
+        // Edit a copy, so that the fragment is only changed if the result is valid
+        let file_name = self
+            .fragment
+            .file_name()
+            .ok_or_else(|| Error::NotAFile(self.fragment.clone()))?;
+        let edit_path = std::env::temp_dir().join(format!(
+            "cargo-changelog-{}-{}",
+            std::process::id(),
+            file_name.to_string_lossy()
+        ));
+        std::fs::copy(&self.fragment, &edit_path)?;
+
+        let mut fragment = Fragment::empty();
+        let edited = EditorTextProvider
+            .provide(&mut fragment, &edit_path)
+            .and_then(|_| {
+                fragment
+                    .check_header_types(config.header_fields())
+                    .map_err(|e| Error::Fragment(e, edit_path.clone()))
+            });
+
+        if let Err(e) = edited {
+            tracing::warn!(
+                "Leaving {} unchanged, the edited fragment is kept at {}",
+                self.fragment.display(),
+                edit_path.display()
+            );
+            return Err(e);
+        }
+
+        tracing::info!("Saving edited fragment to {}", self.fragment.display());
+        std::fs::copy(&edit_path, &self.fragment)?;
+        std::fs::remove_file(&edit_path)?;
+        Ok(None)
+    }
+}
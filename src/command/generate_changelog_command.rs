@@ -1,14 +1,39 @@
-use std::ffi::OsStr;
-use std::io::Write;
-use std::{collections::HashMap, io::BufReader, path::Path};
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+};
 
-use crate::{config::Configuration, error::Error, fragment::Fragment};
+use indexmap::IndexMap;
+
+use crate::{
+    cli::TemplatePreset,
+    config::{
+        BuildMetadataHandling, Configuration, EntrySortBy, Layout, Output, PrereleaseHandling,
+    },
+    error::{Error, FragmentError, OutputError, VerificationError},
+    fragment::{Fragment, FragmentData, FragmentDataDesc, HeaderFilter},
+};
 
 #[derive(typed_builder::TypedBuilder)]
 pub struct GenerateChangelogCommand {
     repository: git2::Repository,
     all: bool,
+    include_unreleased: bool,
     allow_dirty: bool,
+    commit: bool,
+    tag: bool,
+    check: bool,
+    template: Option<PathBuf>,
+    preset: Option<TemplatePreset>,
+    author: Option<String>,
+    audience: Option<String>,
+    base_url: Option<String>,
+    outputs: Vec<Output>,
+    manifest: Option<PathBuf>,
+    emit_version_only: Option<String>,
+    range: VersionRange,
+    /// Open the changelog after writing it
+    open: bool,
 }
 
 impl std::fmt::Debug for GenerateChangelogCommand {
@@ -16,7 +41,21 @@ impl std::fmt::Debug for GenerateChangelogCommand {
         f.debug_struct("ReleaseCommand")
             .field("repository", &self.repository.workdir())
             .field("all", &self.all)
+            .field("include_unreleased", &self.include_unreleased)
             .field("allow_dirty", &self.allow_dirty)
+            .field("commit", &self.commit)
+            .field("tag", &self.tag)
+            .field("check", &self.check)
+            .field("template", &self.template)
+            .field("preset", &self.preset)
+            .field("author", &self.author)
+            .field("audience", &self.audience)
+            .field("base_url", &self.base_url)
+            .field("outputs", &self.outputs)
+            .field("manifest", &self.manifest)
+            .field("emit_version_only", &self.emit_version_only)
+            .field("range", &self.range)
+            .field("open", &self.open)
             .finish_non_exhaustive()
     }
 }
@@ -27,136 +66,933 @@ impl crate::command::Command for GenerateChangelogCommand {
         workdir: &Path,
         config: &Configuration,
     ) -> Result<Option<std::process::ExitCode>, Error> {
-        if crate::util::repo_is_dirty(&self.repository) && !self.allow_dirty {
+        // The configured outputs would only get the fragments of one author
+        let configured_outputs = if self.author.is_some() {
+            &[][..]
+        } else {
+            config.outputs().as_slice()
+        };
+        let outputs = configured_outputs
+            .iter()
+            .chain(self.outputs.iter())
+            .collect::<Vec<_>>();
+
+        if self.commit {
+            // The fragment directory is expected to be dirty after `create-release`, as its
+            // changes are committed together with the changelog
+            let unrelated = crate::util::staged_paths(&self.repository)?
+                .into_iter()
+                .filter(|path| {
+                    let path = Path::new(path);
+                    !path.starts_with(config.fragment_dir())
+                        && path != config.changelog()
+                        && !outputs.iter().any(|output| path == output.path())
+                })
+                .collect::<Vec<_>>();
+
+            if !unrelated.is_empty() {
+                return Err(Error::GitUnrelatedStagedChanges(unrelated));
+            }
+        } else if crate::util::repo_is_dirty(&self.repository)
+            && !self.allow_dirty
+            && !self.check
+            && self.emit_version_only.is_none()
+        {
             return Err(Error::GitRepoDirty);
         }
 
-        let template_path = workdir
-            .join(config.fragment_dir())
-            .join(config.template_path());
-        let template_source = std::fs::read_to_string(template_path)?;
-        let template = crate::template::new_handlebars(&template_source)?;
-
-        let suffix_path = workdir.join(config.fragment_dir()).join("suffix.md");
-        let suffix = match std::fs::read_to_string(&suffix_path) {
-            Ok(suffix) => Some(suffix),
-            Err(err) => {
-                match err.kind() {
-                    std::io::ErrorKind::NotFound => {
-                        // We don't want to spam the user for something they don't use
-                        tracing::trace!(
-                            "Did not find {}, not appending suffix",
-                            suffix_path.display()
-                        )
-                    }
-                    _ => {
-                        tracing::error!(
-                            "Could not read suffix file at {}: {err}",
-                            suffix_path.display()
-                        );
-                    }
-                }
-                None
+        let template = match (self.template.as_ref(), self.preset) {
+            (Some(template_path), _) => load_template(template_path, config)?,
+            (None, Some(preset)) => {
+                crate::template::new_handlebars(preset.source(), config.strict_templates())?
             }
+            (None, None) => load_template(
+                &workdir
+                    .join(config.fragment_dir())
+                    .join(config.template_path()),
+                config,
+            )?,
         };
 
-        let template_data =
-            generate_template_data(load_release_files(workdir, config, self.all), suffix)?;
+        let filters = self
+            .author
+            .as_ref()
+            .map(|author| HeaderFilter::new(crate::consts::AUTHOR_HEADER_FIELD, author))
+            .into_iter()
+            .chain(self.audience.as_ref().map(|audience| {
+                HeaderFilter::new(crate::consts::AUDIENCE_HEADER_FIELD, audience)
+                    .with_default(config.default_audience())
+            }))
+            .collect::<Vec<_>>();
+        let template_data = collect_template_data(
+            workdir,
+            config,
+            self.all || self.include_unreleased,
+            self.include_unreleased,
+            &filters,
+            self.base_url.as_deref(),
+            &self.range,
+        )?;
 
-        let changelog_contents =
-            template.render(crate::consts::INTERNAL_TEMPLATE_NAME, &template_data)?;
-        tracing::debug!("Rendered successfully");
+        if let Some(version) = self.emit_version_only.as_ref() {
+            return emit_version(config, &template_data, version).map(|_| None);
+        }
+
+        let release = if self.commit {
+            let version = latest_released_version(&template_data)?;
+            let message = render_version_template(config.release_commit_message(), &version)?;
+            let tag_name = if self.tag {
+                let tag_name = render_version_template(config.tag_format(), &version)?;
+                if self
+                    .repository
+                    .refname_to_id(&format!("refs/tags/{tag_name}"))
+                    .is_ok()
+                {
+                    return Err(Error::GitTagExists(tag_name));
+                }
+                Some(tag_name)
+            } else {
+                None
+            };
+            Some((message, tag_name))
+        } else {
+            None
+        };
 
         let changelog_file_path = workdir.join(config.changelog());
+        if self.check {
+            return check_changelog(&changelog_file_path, config, &template, &template_data);
+        }
+
         tracing::debug!(
-            "Writing changelog file now: {}",
+            "Rendering changelog file now: {}",
             changelog_file_path.display()
         );
-        let mut changelog_file = std::fs::OpenOptions::new()
-            .create(true)
-            .append(false)
-            .truncate(true)
-            .write(true)
-            .open(changelog_file_path)?;
-
-        write!(changelog_file, "{changelog_contents}")?;
-        changelog_file.sync_all()?;
+        let result = write_atomically(&changelog_file_path, config, |writer| {
+            render_changelog(writer, config, &template, &template_data)
+        });
+
+        // Every output is written, even if others fail, so all failures are reported at once
+        if outputs.is_empty() {
+            result?;
+        } else {
+            let mut errors = result
+                .err()
+                .map(|source| OutputError {
+                    path: changelog_file_path,
+                    source,
+                })
+                .into_iter()
+                .collect::<Vec<_>>();
+
+            for output in outputs.iter() {
+                let path = workdir.join(output.path());
+                tracing::debug!("Rendering output file now: {}", path.display());
+                let result = load_template(
+                    &workdir.join(config.fragment_dir()).join(output.template()),
+                    config,
+                )
+                .and_then(|template| {
+                    write_atomically(&path, config, |writer| {
+                        template
+                            .render_to_write(
+                                crate::consts::INTERNAL_TEMPLATE_NAME,
+                                &template_data,
+                                writer,
+                            )
+                            .map_err(Error::from)
+                    })
+                });
+
+                if let Err(source) = result {
+                    errors.push(OutputError { path, source });
+                }
+            }
+
+            if !errors.is_empty() {
+                return Err(Error::Outputs(errors));
+            }
+        }
+        tracing::debug!("Rendered successfully");
+
+        if let Some(manifest) = self.manifest.as_ref() {
+            let path = workdir.join(manifest);
+            tracing::debug!("Writing manifest now: {}", path.display());
+            write_atomically(&path, config, |writer| {
+                serde_json::to_writer_pretty(&mut *writer, &Manifest::new(&template_data))?;
+                std::io::Write::write_all(writer, b"\n")?;
+                Ok(())
+            })?;
+        }
+
+        if let Some(hook) = config.post_release_hook() {
+            let version = latest_released_version(&template_data)?;
+            run_post_release_hook(workdir, hook, &version)?;
+        }
+
+        if let Some((message, tag_name)) = release {
+            let commit = commit_release(&self.repository, config, &outputs, message.clone())?;
+
+            if let Some(tag_name) = tag_name {
+                let object = self.repository.find_object(commit, None)?;
+                let signature = self.repository.signature()?;
+                self.repository
+                    .tag(&tag_name, &object, &signature, &message, false)?;
+                tracing::info!("Created release tag {tag_name}");
+            }
+        }
+
+        if self.open {
+            open_changelog(&workdir.join(config.changelog()));
+        }
+
         Ok(None)
     }
 }
 
-fn load_release_files(
+/// Open the changelog in the editor, or the default application if no editor is set
+///
+/// The changelog is already written at this point, so failing to open it is only a warning.
+/// Nothing is opened if not run in a terminal, e.g. in CI.
+fn open_changelog(path: &Path) {
+    use is_terminal::IsTerminal;
+
+    if !std::io::stdin().is_terminal() || !std::io::stdout().is_terminal() {
+        tracing::debug!("Not a terminal, not opening {}", path.display());
+        return;
+    }
+
+    let command = match crate::text_provider::get_editor_command() {
+        Ok(command) => Ok(command),
+        Err(Error::EditorEnvNotSet) => Ok(default_open_command()),
+        Err(e) => Err(e),
+    };
+    let status = command.and_then(|mut command| Ok(command.arg(path).status()?));
+    match status {
+        Ok(status) if status.success() => {}
+        Ok(status) => tracing::warn!("Opening {} failed: {status}", path.display()),
+        Err(e) => tracing::warn!("Opening {} failed: {e}", path.display()),
+    }
+}
+
+/// The command that opens a file in its default application
+fn default_open_command() -> std::process::Command {
+    if cfg!(target_os = "windows") {
+        let mut command = std::process::Command::new("cmd");
+        command.args(["/C", "start", ""]);
+        command
+    } else if cfg!(target_os = "macos") {
+        std::process::Command::new("open")
+    } else {
+        std::process::Command::new("xdg-open")
+    }
+}
+
+/// An inclusive range of versions to render, given with `--from` and `--to`
+#[derive(Debug, Default, typed_builder::TypedBuilder)]
+pub struct VersionRange {
+    from: Option<semver::Version>,
+    to: Option<semver::Version>,
+}
+
+impl VersionRange {
+    /// Whether `version` is in the range
+    ///
+    /// Unreleased fragments (without a version) come after all versions, so they are only in
+    /// ranges without an upper bound.
+    fn contains(&self, version: Option<&semver::Version>) -> bool {
+        match version {
+            Some(version) => {
+                self.from.as_ref().is_none_or(|from| version >= from)
+                    && self.to.as_ref().is_none_or(|to| version <= to)
+            }
+            None => self.to.is_none(),
+        }
+    }
+}
+
+/// Collect the data the changelog templates are rendered with
+///
+/// Fragments without a version are only included with `all`, fragments without an `author`
+/// header field matching `author` are left out if it is given.
+fn collect_template_data(
     workdir: &Path,
     config: &Configuration,
     all: bool,
-) -> impl Iterator<Item = Result<(Option<semver::Version>, Fragment), Error>> {
-    let root_path = workdir.join(config.fragment_dir());
-    walkdir::WalkDir::new(root_path.clone())
-        .follow_links(false)
-        .max_open(100)
-        .same_file_system(true)
-        .into_iter()
-        .filter_map(move |rde| match rde {
-            Err(e) => Some(Err(e)),
-            Ok(de) => {
-                if de.file_type().is_file() {
-                    // Since the file itself comes from the workdir, this cannot fail?
-                    let clean_path = de.path().strip_prefix(root_path.as_path()).unwrap();
-                    if clean_path.components().count() <= 1
-                        || de.path().extension() != Some(OsStr::new("md"))
-                    {
-                        None
-                    } else {
-                        tracing::debug!("Considering: {:?}", de);
-                        Some(Ok(de))
-                    }
-                } else {
-                    None
+    include_unreleased: bool,
+    filters: &[HeaderFilter],
+    base_url: Option<&str>,
+    range: &VersionRange,
+) -> Result<TemplateData, Error> {
+    let suffix_path = workdir.join(config.fragment_dir()).join("suffix.md");
+    let suffix = match std::fs::read_to_string(&suffix_path) {
+        Ok(suffix) => Some(suffix),
+        Err(err) => {
+            match err.kind() {
+                std::io::ErrorKind::NotFound => {
+                    // We don't want to spam the user for something they don't use
+                    tracing::trace!(
+                        "Did not find {}, not appending suffix",
+                        suffix_path.display()
+                    )
+                }
+                _ => {
+                    tracing::error!(
+                        "Could not read suffix file at {}: {err}",
+                        suffix_path.display()
+                    );
                 }
             }
-        })
-        .filter_map(move |rde| {
-            let de = match rde {
-                Err(e) => return Some(Err(Error::from(e))),
-                Ok(de) => de,
-            };
+            None
+        }
+    };
+
+    let build_metadata = config.build_metadata();
+    let empty_versions = if config.skip_empty_versions() {
+        HashSet::new()
+    } else {
+        find_version_dirs(workdir, config)?
+    };
+    let empty_versions = empty_versions
+        .into_iter()
+        .map(|v| apply_build_metadata_handling(v, build_metadata))
+        .filter(|v| range.contains(Some(v)))
+        .collect();
+    let yanked = find_yanked_versions(workdir, config)?
+        .into_iter()
+        .map(|v| apply_build_metadata_handling(v, build_metadata))
+        .collect();
 
-            let version = match crate::command::common::get_version_from_path(de.path()) {
-                Err(e) => return Some(Err(Error::from(e))),
-                Ok(None) => {
-                    if all {
-                        None
-                    } else {
-                        return None;
-                    }
+    let base_url = base_url.or(config.base_url().as_deref());
+    let commits = if config.resolve_commits() {
+        let repository = git2::Repository::open(workdir)?;
+        Some(crate::util::commits_adding_files(
+            &repository,
+            config.fragment_dir(),
+        )?)
+    } else {
+        None
+    };
+    let release_files = load_release_files(workdir, config, all)?
+        .filter(|r| match r {
+            Ok((_, fragment)) => filters.iter().all(|filter| filter.matches(fragment)),
+            Err(_) => true,
+        })
+        .map(|r| {
+            r.map(|(version, mut fragment)| {
+                let version = version.map(|v| apply_build_metadata_handling(v, build_metadata));
+                if let Some(base_url) = base_url {
+                    let text = crate::links::absolutize_links(fragment.text(), base_url);
+                    fragment.set_text(text);
                 }
-                Ok(Some(version)) => Some(version),
-            };
+                if let Some(commits) = commits.as_ref() {
+                    let commit = fragment
+                        .source_path()
+                        .as_ref()
+                        .and_then(|path| commits.get(path))
+                        .map_or_else(
+                            || crate::consts::UNCOMMITTED.to_string(),
+                            |oid| oid.to_string()[..7].to_string(),
+                        );
+                    fragment.set_commit(commit);
+                }
+                (version, fragment)
+            })
+        })
+        .filter(|r| match r {
+            Ok((version, _)) => range.contains(version.as_ref()),
+            Err(_) => true,
+        });
 
-            let fragment = std::fs::OpenOptions::new()
-                .read(true)
-                .create(false)
-                .write(false)
-                .open(de.path())
-                .map_err(Error::from)
-                .map(BufReader::new)
-                .and_then(|mut reader| {
-                    Fragment::from_reader(&mut reader)
-                        .map_err(|e| Error::Fragment(e, de.path().to_path_buf()))
-                });
+    let template_data = generate_template_data(
+        release_files,
+        config.prerelease(),
+        config.order(),
+        config.entry_sort_by(),
+        Some(config.created_field()),
+        &yanked,
+        &empty_versions,
+        include_unreleased,
+        suffix,
+    )?;
+    let versions = with_tags(template_data.versions, config.tag_format())?;
+    Ok(TemplateData {
+        versions,
+        repository_url: config.repository_url().clone(),
+        vars: config.template_vars().clone(),
+        group_headings: config.group_headings().clone(),
+        version_display_map: config.version_display_map().clone(),
+        group_order: config.group_order().clone(),
+        group_fallback: config.group_fallback().clone(),
+        ..template_data
+    })
+}
+
+/// Render the configured changelog template and output templates without writing them
+///
+/// All fragments are rendered, including the unreleased ones, so that errors in the templates
+/// show up before a release.
+pub fn check_templates_render(
+    workdir: &Path,
+    config: &Configuration,
+) -> Result<(), VerificationError> {
+    let fragment_dir = workdir.join(config.fragment_dir());
+    let template_data = collect_template_data(
+        workdir,
+        config,
+        true,
+        true,
+        &[],
+        None,
+        &VersionRange::default(),
+    )
+    .map_err(|source| VerificationError::TemplateRender {
+        path: fragment_dir.join(config.template_path()),
+        source: Box::new(source),
+    })?;
+
+    std::iter::once(fragment_dir.join(config.template_path()))
+        .chain(
+            config
+                .outputs()
+                .iter()
+                .map(|output| fragment_dir.join(output.template())),
+        )
+        .try_for_each(|path| {
+            load_template(&path, config)
+                .and_then(|template| {
+                    template
+                        .render_to_write(
+                            crate::consts::INTERNAL_TEMPLATE_NAME,
+                            &template_data,
+                            std::io::sink(),
+                        )
+                        .map_err(Error::from)
+                })
+                .map_err(|source| VerificationError::TemplateRender {
+                    path,
+                    source: Box::new(source),
+                })
+        })
+}
+
+/// The fragment files that went into each version of the changelog, for auditing
+#[derive(Debug, serde::Serialize)]
+struct Manifest<'a> {
+    versions: Vec<ManifestVersion<'a>>,
+}
+
+#[derive(Debug, serde::Serialize)]
+struct ManifestVersion<'a> {
+    version: &'a str,
+    /// The paths of the fragment files, relative to the repository root
+    fragments: Vec<&'a Path>,
+}
+
+impl<'a> Manifest<'a> {
+    fn new(template_data: &'a TemplateData) -> Self {
+        let versions = template_data
+            .versions
+            .iter()
+            .map(|version| ManifestVersion {
+                version: &version.version,
+                fragments: version
+                    .entries
+                    .iter()
+                    .filter_map(|fragment| fragment.source_path().as_deref())
+                    .collect(),
+            })
+            .collect();
+        Manifest { versions }
+    }
+}
+
+/// Load the template at `template_path`
+fn load_template(
+    template_path: &Path,
+    config: &Configuration,
+) -> Result<handlebars::Handlebars<'static>, Error> {
+    if !template_path.exists() {
+        return Err(Error::TemplateDoesNotExist(template_path.to_path_buf()));
+    }
+    let template_source = std::fs::read_to_string(template_path)?;
+    crate::template::new_handlebars(&template_source, config.strict_templates())
+}
+
+/// Write a file with the configured line endings, by writing a temporary file next to it first
+///
+/// The file is replaced by renaming the temporary file, so it is never left half-written.
+fn write_atomically<F>(path: &Path, config: &Configuration, render: F) -> Result<(), Error>
+where
+    F: FnOnce(
+        &mut crate::line_ending::LineEndingWriter<std::io::BufWriter<std::fs::File>>,
+    ) -> Result<(), Error>,
+{
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy())
+        .unwrap_or_default();
+    let temp_path = path.with_file_name(format!(".{file_name}.tmp"));
+
+    let result = std::fs::File::create(&temp_path)
+        .map_err(Error::from)
+        .and_then(|file| {
+            // Render directly into the file, so we do not have to keep the whole changelog in
+            // memory
+            let mut writer = crate::line_ending::LineEndingWriter::new(
+                std::io::BufWriter::new(file),
+                config.line_ending(),
+            );
+            render(&mut writer)?;
+
+            let writer = writer.finish()?;
+            let file = writer.into_inner().map_err(|e| e.into_error())?;
+            file.sync_all()?;
+            std::fs::rename(&temp_path, path)?;
+            Ok(())
+        });
+
+    if result.is_err() {
+        let _ = std::fs::remove_file(&temp_path);
+    }
+    result
+}
+
+/// Render the changelog, including the configured front-matter
+fn render_changelog<W: std::io::Write>(
+    writer: &mut W,
+    config: &Configuration,
+    template: &handlebars::Handlebars,
+    template_data: &TemplateData,
+) -> Result<(), Error> {
+    if let Some(frontmatter) = config.changelog_frontmatter() {
+        crate::frontmatter::write_frontmatter(writer, frontmatter)?;
+    }
+
+    template.render_to_write(crate::consts::INTERNAL_TEMPLATE_NAME, template_data, writer)?;
+    Ok(())
+}
+
+/// Print the section of a single version to stdout
+fn emit_version(
+    config: &Configuration,
+    template_data: &TemplateData,
+    version: &str,
+) -> Result<(), Error> {
+    let version_data = template_data
+        .versions
+        .iter()
+        .find(|v| v.version == version)
+        .ok_or_else(|| Error::VersionNotFound(version.to_string()))?;
+
+    // The version is rendered on its own, so it needs the grouping settings next to it
+    let data = VersionTemplateData {
+        version: version_data,
+        group_headings: &template_data.group_headings,
+        version_display_map: &template_data.version_display_map,
+        group_order: &template_data.group_order,
+        group_fallback: &template_data.group_fallback,
+    };
+
+    let template = crate::template::new_handlebars(
+        crate::consts::VERSION_TEMPLATE,
+        config.strict_templates(),
+    )?;
+    let mut writer =
+        crate::line_ending::LineEndingWriter::new(std::io::stdout().lock(), config.line_ending());
+    template.render_to_write(crate::consts::INTERNAL_TEMPLATE_NAME, &data, &mut writer)?;
+    std::io::Write::flush(&mut writer.finish()?)?;
+    Ok(())
+}
+
+/// Render the changelog in memory and compare it to the existing changelog file
+fn check_changelog(
+    changelog_file_path: &Path,
+    config: &Configuration,
+    template: &handlebars::Handlebars,
+    template_data: &TemplateData,
+) -> Result<Option<std::process::ExitCode>, Error> {
+    let mut writer = crate::line_ending::LineEndingWriter::new(Vec::new(), config.line_ending());
+    render_changelog(&mut writer, config, template, template_data)?;
+    let rendered = String::from_utf8(writer.finish()?)?;
+
+    let existing = match std::fs::read_to_string(changelog_file_path) {
+        Ok(existing) => existing,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(Error::from(e)),
+    };
 
-            match fragment {
-                Err(e) => Some(Err(e)),
-                Ok(fragment) => Some(Ok((version, fragment))),
+    if existing == rendered {
+        tracing::info!("Changelog is up to date");
+        return Ok(None);
+    }
+
+    let name = config.changelog().display().to_string();
+    let diff = difflib::unified_diff(
+        &existing.split_inclusive('\n').collect::<Vec<_>>(),
+        &rendered.split_inclusive('\n').collect::<Vec<_>>(),
+        &format!("{name} (existing)"),
+        &format!("{name} (generated)"),
+        "",
+        "",
+        3,
+    );
+    for line in diff {
+        print!("{line}");
+        if !line.ends_with('\n') {
+            println!();
+        }
+    }
+
+    tracing::error!("Changelog is not up to date: {name}");
+    Ok(Some(std::process::ExitCode::FAILURE))
+}
+
+fn run_post_release_hook(
+    workdir: &Path,
+    hook: &str,
+    version: &semver::Version,
+) -> Result<(), Error> {
+    let mut args =
+        comma::parse_command(hook).ok_or_else(|| Error::NoValidHookCommand(hook.to_string()))?;
+    if args.is_empty() {
+        return Err(Error::NoValidHookCommand(hook.to_string()));
+    }
+    let binary = args.remove(0);
+
+    tracing::info!("Running post release hook for version {version}: {hook}");
+    let status = std::process::Command::new(binary)
+        .args(args)
+        .current_dir(workdir)
+        .env("CHANGELOG_VERSION", version.to_string())
+        .stdout(std::process::Stdio::inherit())
+        .stderr(std::process::Stdio::inherit())
+        .status()?;
+
+    if !status.success() {
+        return Err(Error::HookNoSuccess(hook.to_string(), status));
+    }
+    tracing::info!("Post release hook finished successfully");
+    Ok(())
+}
+
+fn latest_released_version(template_data: &TemplateData) -> Result<semver::Version, Error> {
+    template_data
+        .versions
+        .iter()
+        .filter_map(|v| semver::Version::parse(&v.version).ok())
+        .max()
+        .ok_or(Error::NoReleasedVersion)
+}
+
+fn render_version_template(template: &str, version: &semver::Version) -> Result<String, Error> {
+    handlebars::Handlebars::new()
+        .render_template(
+            template,
+            &serde_json::json!({ "version": version.to_string() }),
+        )
+        .map_err(Error::from)
+}
+
+/// Commit the changelog file and the fragment directory
+fn commit_release(
+    repository: &git2::Repository,
+    config: &Configuration,
+    outputs: &[&Output],
+    mut message: String,
+) -> Result<git2::Oid, Error> {
+    let signature = repository.signature()?;
+    if config.git_commit_signoff() {
+        message.push_str(&format!(
+            "\n\nSigned-off-by: {} <{}>",
+            signature.name().unwrap_or_default(),
+            signature.email().unwrap_or_default()
+        ));
+    }
+
+    let mut index = repository.index()?;
+    index.add_path(config.changelog())?;
+    for output in outputs {
+        index.add_path(output.path())?;
+    }
+    index.add_all([config.fragment_dir()], git2::IndexAddOption::DEFAULT, None)?;
+    // stage files that were moved away from the fragment directory
+    index.update_all([config.fragment_dir()], None)?;
+    index.write()?;
+
+    let tree = repository.find_tree(index.write_tree()?)?;
+    let parent = repository
+        .head()
+        .ok()
+        .and_then(|head| head.peel_to_commit().ok());
+    let parents = parent.iter().collect::<Vec<_>>();
+
+    let commit = repository.commit(
+        Some("HEAD"),
+        &signature,
+        &signature,
+        &message,
+        &tree,
+        &parents,
+    )?;
+    tracing::info!("Created release commit {commit}");
+    Ok(commit)
+}
+
+/// Load the fragments with their versions
+///
+/// Unreleased fragments are only loaded if `all` is set. Fragments matching the `.changelogignore`
+/// file in the fragment directory are skipped, as are fragments with `true` in the `skip_field`
+/// header field.
+///
+/// Released fragments that were compacted into the `database` are loaded from there.
+///
+/// The fragments are parsed in parallel, with at most `threads` threads from the configuration.
+pub fn load_release_files(
+    workdir: &Path,
+    config: &Configuration,
+    all: bool,
+) -> Result<impl Iterator<Item = Result<(Option<semver::Version>, Fragment), Error>>, Error> {
+    let fragment_dir = workdir.join(config.fragment_dir());
+    let loader = FragmentLoader {
+        ignore_file: crate::ignore_file::IgnoreFile::load(&fragment_dir)?,
+        fragment_dir,
+        root: workdir.to_path_buf(),
+        all,
+        layout: config.layout(),
+        expand_body_templates: config.expand_body_templates(),
+        skip_field: config.skip_field().clone(),
+        coerce_header_fields: config
+            .coerce_scalars()
+            .then(|| config.header_fields().clone()),
+        normalize_header_fields: config
+            .normalize_enum_case()
+            .then(|| config.header_fields().clone()),
+    };
+
+    let entries = crate::command::common::find_fragment_files(workdir, config).collect::<Vec<_>>();
+    let threads = config
+        .threads()
+        .or_else(|| std::thread::available_parallelism().ok())
+        .map_or(1, std::num::NonZeroUsize::get);
+
+    let mut loaded = loader.load_all(entries, threads);
+
+    if let Some(database) = config.database() {
+        let database_path = loader.fragment_dir.join(database);
+        for (version, fragment) in crate::database::load(&database_path)? {
+            loaded.push(loader.prepare(fragment, Some(version), &database_path));
+        }
+    }
+
+    Ok(loaded.into_iter().flatten())
+}
+
+/// A loaded fragment with its version, `None` if the file is not part of the changelog
+type LoadedFragment = Option<Result<(Option<semver::Version>, Fragment), Error>>;
+
+/// Everything needed to load a single fragment file, shared between the loading threads
+struct FragmentLoader {
+    fragment_dir: PathBuf,
+    ignore_file: Option<crate::ignore_file::IgnoreFile>,
+    root: PathBuf,
+    all: bool,
+    layout: Layout,
+    expand_body_templates: bool,
+    skip_field: String,
+    coerce_header_fields: Option<IndexMap<String, FragmentDataDesc>>,
+    normalize_header_fields: Option<IndexMap<String, FragmentDataDesc>>,
+}
+
+impl FragmentLoader {
+    /// Load the entries with up to `threads` threads, keeping their order
+    fn load_all(
+        &self,
+        entries: Vec<Result<walkdir::DirEntry, walkdir::Error>>,
+        threads: usize,
+    ) -> Vec<LoadedFragment> {
+        if threads <= 1 || entries.len() <= 1 {
+            return entries.into_iter().flat_map(|rde| self.load(rde)).collect();
+        }
+
+        let chunk_size = entries.len().div_ceil(threads);
+        let mut chunks = Vec::new();
+        let mut entries = entries.into_iter();
+        loop {
+            let chunk = entries.by_ref().take(chunk_size).collect::<Vec<_>>();
+            if chunk.is_empty() {
+                break;
             }
+            chunks.push(chunk);
+        }
+
+        std::thread::scope(|scope| {
+            let handles = chunks
+                .into_iter()
+                .map(|chunk| {
+                    scope.spawn(move || {
+                        chunk
+                            .into_iter()
+                            .flat_map(|rde| self.load(rde))
+                            .collect::<Vec<_>>()
+                    })
+                })
+                .collect::<Vec<_>>();
+
+            handles
+                .into_iter()
+                .flat_map(|handle| match handle.join() {
+                    Ok(loaded) => loaded,
+                    Err(panic) => std::panic::resume_unwind(panic),
+                })
+                .collect()
         })
+    }
+
+    /// Load a single fragment file, once for each of its versions
+    fn load(&self, rde: Result<walkdir::DirEntry, walkdir::Error>) -> Vec<LoadedFragment> {
+        let de = match rde {
+            Err(e) => return vec![Some(Err(Error::from(e)))],
+            Ok(de) => de,
+        };
+
+        if let Some(ignore_file) = self.ignore_file.as_ref() {
+            let relative_path = de
+                .path()
+                .strip_prefix(&self.fragment_dir)
+                .unwrap_or(de.path());
+            if ignore_file.is_ignored(relative_path) {
+                tracing::debug!("Ignoring {}", de.path().display());
+                return Vec::new();
+            }
+        }
+
+        // Even with the versioned layout, unreleased fragments have to be parsed, as they can list
+        // the versions they were backported to
+        let mut fragment = match Fragment::from_path(de.path()) {
+            Err(e) => return vec![Some(Err(e))],
+            Ok(fragment) => fragment,
+        };
+
+        // Keep the path relative, so that it can be used in the changelog, e.g. for links
+        let source_path = de.path().strip_prefix(&self.root).unwrap_or(de.path());
+        fragment.set_source_path(source_path.to_path_buf());
+
+        // Only look at the path inside the fragment directory, so that the location of the
+        // repository itself does not matter
+        let relative_path = de
+            .path()
+            .strip_prefix(&self.fragment_dir)
+            .unwrap_or(de.path());
+        match crate::command::common::get_versions_of_fragment(
+            relative_path,
+            &fragment,
+            self.layout,
+        ) {
+            Err(e) => vec![Some(Err(Error::from(e)))],
+            Ok(versions) => versions
+                .into_iter()
+                .filter(|version| self.all || version.is_some())
+                .map(|version| self.prepare(fragment.clone(), version, de.path()))
+                .collect(),
+        }
+    }
+
+    /// Prepare a fragment for the changelog, `path` is where it was loaded from
+    fn prepare(
+        &self,
+        mut fragment: Fragment,
+        version: Option<semver::Version>,
+        path: &Path,
+    ) -> LoadedFragment {
+        if let Some(header_fields) = self.coerce_header_fields.as_ref() {
+            fragment.coerce_header(header_fields);
+        }
+        if let Some(header_fields) = self.normalize_header_fields.as_ref() {
+            fragment.normalize_enum_case(header_fields);
+        }
+
+        if std::matches!(
+            fragment.header().get(&self.skip_field),
+            Some(FragmentData::Bool(true))
+        ) {
+            tracing::debug!("Skipping {}", path.display());
+            return None;
+        }
+
+        if self.expand_body_templates {
+            if let Err(e) = expand_body_template(&mut fragment, version.as_ref()) {
+                return Some(Err(Error::Fragment(e, path.to_path_buf())));
+            }
+        }
+        Some(Ok((version, fragment)))
+    }
+}
+
+/// Render the text of the fragment as template, with its header and version as data
+fn expand_body_template(
+    fragment: &mut Fragment,
+    version: Option<&semver::Version>,
+) -> Result<(), FragmentError> {
+    let mut data = serde_json::Map::new();
+    if let Some(version) = version {
+        data.insert(
+            "version".to_string(),
+            serde_json::Value::String(version.to_string()),
+        );
+    }
+    for (key, value) in fragment.header() {
+        data.insert(
+            key.to_string(),
+            serde_json::to_value(value).unwrap_or_default(),
+        );
+    }
+
+    let mut handlebars = handlebars::Handlebars::new();
+    handlebars.register_escape_fn(handlebars::no_escape);
+    let text = handlebars
+        .render_template(fragment.text(), &data)
+        .map_err(|e| FragmentError::BodyTemplate(Box::new(e)))?;
+    fragment.set_text(text);
+    Ok(())
 }
 
 /// The data sent to the handlebars template
+///
+/// The names of the fields are the names of the variables in the template.
 #[derive(Clone, Debug, serde::Serialize, serde::Deserialize, getset::Getters)]
 pub struct TemplateData {
     versions: Vec<VersionData>,
+    /// The versions in the same order as `versions`, e.g. for a footer with links to all versions
+    version_list: Vec<String>,
+    /// The number of versions
+    total_versions: usize,
+    /// The number of entries in all versions
+    total_entries: usize,
+    /// The content of the `suffix.md` file in the fragment directory, if any
     suffix: Option<String>,
+    repository_url: Option<String>,
+    /// The `template_vars` from the configuration
+    vars: toml::Table,
+    /// Display headings for the groups of `group_by_header`, see the `group_heading` helper
+    group_headings: IndexMap<String, String>,
+    /// Display names for versions, see the `display_version` helper
+    version_display_map: IndexMap<String, String>,
+    /// The order of the groups of `group_by_header`
+    group_order: Vec<String>,
+    /// The group of `group_by_header` for groups not listed in `group_order`
+    group_fallback: String,
+}
+
+/// The data sent to the template for a single version, see [`emit_version`]
+#[derive(Debug, serde::Serialize)]
+struct VersionTemplateData<'a> {
+    #[serde(flatten)]
+    version: &'a VersionData,
+    group_headings: &'a IndexMap<String, String>,
+    version_display_map: &'a IndexMap<String, String>,
+    group_order: &'a [String],
+    group_fallback: &'a str,
 }
 
 /// Helper type for storing version associated with Fragments
@@ -168,41 +1004,294 @@ pub struct VersionData {
     version: String,
     #[getset(get = "pub")]
     entries: Vec<Fragment>,
+    #[getset(get = "pub")]
+    yanked: bool,
+    /// A unique identifier for the version, usable as markdown anchor (e.g. "v0-1-0")
+    #[getset(get = "pub")]
+    slug: String,
+    /// The version before this one, e.g. for links comparing the two versions
+    #[getset(get = "pub")]
+    previous_version: Option<String>,
+    /// The git tag of the version, rendered with the configured `tag_format`
+    #[getset(get = "pub")]
+    tag: Option<String>,
+    /// The git tag of the previous version
+    #[getset(get = "pub")]
+    previous_tag: Option<String>,
+    /// The configured sort key of the version, see [`VersionData::cmp_order`]
+    #[serde(default)]
+    sort_key: i64,
+}
+
+impl VersionData {
+    /// Compare by the configured sort key first, then by semver
+    ///
+    /// Versions that are not valid semver (i.e. "unreleased") are sorted after all other versions.
+    pub fn cmp_order(&self, other: &Self) -> std::cmp::Ordering {
+        use std::cmp::Ordering;
+
+        self.sort_key.cmp(&other.sort_key).then_with(|| {
+            match (
+                semver::Version::parse(&self.version),
+                semver::Version::parse(&other.version),
+            ) {
+                (Ok(a), Ok(b)) => a.cmp(&b),
+                (Ok(_), Err(_)) => Ordering::Less,
+                (Err(_), Ok(_)) => Ordering::Greater,
+                (Err(_), Err(_)) => self.version.cmp(&other.version),
+            }
+        })
+    }
+}
+
+/// Compute a slug for the version, that is not in `slugs` yet
+///
+/// The slug only consists of lowercase ascii letters, digits and dashes, so it can be used as
+/// anchor in markdown.
+fn unique_slug(version: &str, slugs: &mut HashSet<String>) -> String {
+    let base = if version == crate::consts::UNRELEASED_DIR_NAME {
+        version.to_string()
+    } else {
+        let version = version
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() {
+                    c.to_ascii_lowercase()
+                } else {
+                    '-'
+                }
+            })
+            .collect::<String>();
+        format!("v{version}")
+    };
+
+    let mut slug = base.clone();
+    let mut counter = 1;
+    while slugs.contains(&slug) {
+        counter += 1;
+        slug = format!("{base}-{counter}");
+    }
+    slugs.insert(slug.clone());
+    slug
+}
+
+/// Remove the build metadata of the version, if versions with build metadata are merged
+fn apply_build_metadata_handling(
+    mut version: semver::Version,
+    handling: BuildMetadataHandling,
+) -> semver::Version {
+    if handling == BuildMetadataHandling::Merge {
+        version.build = semver::BuildMetadata::EMPTY;
+    }
+    version
+}
+
+/// Find all versions that are marked as yanked
+///
+/// A version is yanked if its version directory contains a marker file (".yanked").
+fn find_yanked_versions(
+    workdir: &Path,
+    config: &Configuration,
+) -> Result<HashSet<semver::Version>, Error> {
+    let mut yanked = HashSet::new();
+    for entry in std::fs::read_dir(workdir.join(config.fragment_dir()))? {
+        let path = entry?.path();
+        if !path.join(crate::consts::YANKED_MARKER_FILE_NAME).is_file() {
+            continue;
+        }
+
+        let version = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .and_then(|name| semver::Version::parse(name).ok());
+
+        match version {
+            Some(version) => {
+                tracing::debug!("Version {version} is yanked");
+                yanked.insert(version);
+            }
+            None => tracing::warn!("Yanked marker in non-version directory: {}", path.display()),
+        }
+    }
+    Ok(yanked)
+}
+
+/// Find all versions that have a directory in the fragment directory
+fn find_version_dirs(
+    workdir: &Path,
+    config: &Configuration,
+) -> Result<HashSet<semver::Version>, Error> {
+    let mut versions = HashSet::new();
+    for entry in std::fs::read_dir(workdir.join(config.fragment_dir()))? {
+        let entry = entry?;
+        if !entry.file_type()?.is_dir() {
+            continue;
+        }
+
+        if let Some(version) = entry
+            .file_name()
+            .to_str()
+            .and_then(|name| semver::Version::parse(name).ok())
+        {
+            versions.insert(version);
+        }
+    }
+    Ok(versions)
 }
 
+/// Group the fragments by version
+///
+/// Versions in `empty_versions` are part of the data even if they have no fragments, as is
+/// "unreleased" with `include_unreleased`. The versions are sorted by their sort key in `order`
+/// (0 if they have none) first, then by semver. The entries of each version are sorted by
+/// `entry_sort_by`, see [`sort_entries`].
+#[allow(clippy::too_many_arguments)]
 fn generate_template_data(
     release_files: impl Iterator<Item = Result<(Option<semver::Version>, Fragment), Error>>,
+    prerelease: PrereleaseHandling,
+    order: &HashMap<String, i64>,
+    entry_sort_by: EntrySortBy,
+    created_field: Option<&str>,
+    yanked: &HashSet<semver::Version>,
+    empty_versions: &HashSet<semver::Version>,
+    include_unreleased: bool,
     suffix: Option<String>,
 ) -> Result<TemplateData, Error> {
     let versions = {
         use itertools::Itertools;
         let mut hm = HashMap::new();
-        for r in release_files {
-            let (version, fragment) = r?;
+        let versioned_files = empty_versions
+            .iter()
+            .map(|version| Ok((Some(version.clone()), None)))
+            .chain(release_files.map(|r| r.map(|(version, fragment)| (version, Some(fragment)))));
+
+        for r in versioned_files {
+            let (mut version, fragment) = r?;
 
-            if let Some(version) = version {
+            if let Some(v) = version.as_mut().filter(|v| !v.pre.is_empty()) {
+                match prerelease {
+                    PrereleaseHandling::Separate => {}
+                    PrereleaseHandling::Hide => continue,
+                    PrereleaseHandling::Fold => v.pre = semver::Prerelease::EMPTY,
+                }
+            }
+
+            let is_yanked = version.as_ref().is_some_and(|v| yanked.contains(v));
+            let entries = &mut if let Some(version) = version {
                 hm.entry(version.to_string())
             } else {
                 hm.entry("unreleased".to_string())
             }
-            .or_insert_with(Vec::new)
-            .push(fragment);
+            .or_insert_with(|| (Vec::new(), is_yanked))
+            .0;
+            entries.extend(fragment);
+        }
+        if include_unreleased {
+            hm.entry("unreleased".to_string())
+                .or_insert_with(|| (Vec::new(), false));
         }
+        let mut slugs = HashSet::new();
         hm.into_iter()
-            .map(|(version, entries)| VersionData { version, entries })
-            .sorted_by(|va, vb| va.version.cmp(&vb.version))
+            .map(|(version, (mut entries, yanked))| {
+                sort_entries(&mut entries, entry_sort_by, created_field);
+                VersionData {
+                    sort_key: order.get(&version).copied().unwrap_or_default(),
+                    version,
+                    entries,
+                    yanked,
+                    slug: String::new(),
+                    previous_version: None,
+                    tag: None,
+                    previous_tag: None,
+                }
+            })
+            .sorted_by(VersionData::cmp_order)
+            .map(|mut version_data| {
+                version_data.slug = unique_slug(&version_data.version, &mut slugs);
+                version_data
+            })
+            .collect::<Vec<_>>()
     };
+    let versions = with_previous_versions(versions);
+    let version_list = versions.iter().map(|v| v.version.clone()).collect();
 
     Ok(TemplateData {
-        versions: versions.collect(),
+        total_versions: versions.len(),
+        total_entries: versions.iter().map(|v| v.entries.len()).sum(),
+        versions,
+        version_list,
         suffix,
+        repository_url: None,
+        vars: toml::Table::new(),
+        group_headings: IndexMap::new(),
+        version_display_map: IndexMap::new(),
+        group_order: Vec::new(),
+        group_fallback: crate::config::group_fallback_default(),
     })
 }
 
+/// Sort the entries of a version
+///
+/// Entries are sorted by the path of their fragment file, or by the creation time in
+/// `created_field` first. Entries without (valid) creation time are sorted last.
+fn sort_entries(entries: &mut [Fragment], sort_by: EntrySortBy, created_field: Option<&str>) {
+    match sort_by {
+        EntrySortBy::Path => entries.sort_by(|a, b| a.source_path().cmp(b.source_path())),
+        EntrySortBy::Created => entries.sort_by_cached_key(|fragment| {
+            let created = created_field
+                .and_then(|field| fragment.header().get(field))
+                .and_then(|value| match value {
+                    FragmentData::Str(s) => time::OffsetDateTime::parse(
+                        s,
+                        &time::format_description::well_known::Rfc3339,
+                    )
+                    .ok(),
+                    _ => None,
+                });
+            (created.is_none(), created, fragment.source_path().clone())
+        }),
+    }
+}
+
+/// Set the previous version of each version, in semver order
+///
+/// The previous version of "unreleased" is the latest released version.
+fn with_previous_versions(mut versions: Vec<VersionData>) -> Vec<VersionData> {
+    let released = versions
+        .iter()
+        .filter_map(|v| semver::Version::parse(&v.version).ok())
+        .collect::<Vec<_>>();
+
+    for version_data in versions.iter_mut() {
+        let previous = match semver::Version::parse(&version_data.version) {
+            Ok(current) => released.iter().filter(|v| **v < current).max(),
+            Err(_) => released.iter().max(),
+        };
+        version_data.previous_version = previous.map(ToString::to_string);
+    }
+    versions
+}
+
+/// Render the git tags of each version and its previous version with `tag_format`
+///
+/// "unreleased" has no tag of its own.
+fn with_tags(mut versions: Vec<VersionData>, tag_format: &str) -> Result<Vec<VersionData>, Error> {
+    let render = |version: Option<&String>| {
+        version
+            .and_then(|v| semver::Version::parse(v).ok())
+            .map(|v| render_version_template(tag_format, &v))
+            .transpose()
+    };
+
+    for version_data in versions.iter_mut() {
+        version_data.tag = render(Some(&version_data.version))?;
+        version_data.previous_tag = render(version_data.previous_version.as_ref())?;
+    }
+    Ok(versions)
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::fragment::FragmentData;
-
     use super::*;
     use predicates::prelude::*;
 
@@ -214,7 +1303,7 @@ mod tests {
                     Some(semver::Version::new(0, 2, 0)),
                     Fragment::new(
                         {
-                            let mut hm = HashMap::new();
+                            let mut hm = IndexMap::new();
                             hm.insert("issue".to_string(), FragmentData::Int(123));
                             hm
                         },
@@ -225,7 +1314,7 @@ mod tests {
                     Some(semver::Version::new(0, 1, 0)),
                     Fragment::new(
                         {
-                            let mut hm = HashMap::new();
+                            let mut hm = IndexMap::new();
                             hm.insert("issue".to_string(), FragmentData::Int(345));
                             hm
                         },
@@ -234,6 +1323,13 @@ mod tests {
                 )),
             ]
             .into_iter(),
+            PrereleaseHandling::Separate,
+            &HashMap::new(),
+            EntrySortBy::Path,
+            None,
+            &HashSet::new(),
+            &HashSet::new(),
+            false,
             None,
         );
 
@@ -245,9 +1341,419 @@ mod tests {
         assert_eq!(versions[1].version, "0.2.0");
     }
 
+    fn prerelease_fragments(
+    ) -> impl Iterator<Item = Result<(Option<semver::Version>, Fragment), Error>> {
+        [
+            Ok((
+                Some(semver::Version::parse("1.0.0-rc.1").unwrap()),
+                Fragment::new(IndexMap::new(), "text for 1.0.0-rc.1".to_string()),
+            )),
+            Ok((
+                Some(semver::Version::parse("1.0.0").unwrap()),
+                Fragment::new(IndexMap::new(), "text for 1.0.0".to_string()),
+            )),
+        ]
+        .into_iter()
+    }
+
+    fn build_metadata_fragments(
+        handling: BuildMetadataHandling,
+    ) -> impl Iterator<Item = Result<(Option<semver::Version>, Fragment), Error>> {
+        ["1.0.0+build.2", "1.0.0", "1.0.0+build.1"]
+            .into_iter()
+            .map(move |version| {
+                let version = semver::Version::parse(version).unwrap();
+                let text = format!("text for {version}");
+                let version = apply_build_metadata_handling(version, handling);
+                Ok((Some(version), Fragment::new(IndexMap::new(), text)))
+            })
+    }
+
+    #[test]
+    fn test_template_data_build_metadata() {
+        let versions = |handling| {
+            generate_template_data(
+                build_metadata_fragments(handling),
+                PrereleaseHandling::Separate,
+                &HashMap::new(),
+                EntrySortBy::Path,
+                None,
+                &HashSet::new(),
+                &HashSet::new(),
+                false,
+                None,
+            )
+            .unwrap()
+            .versions
+            .into_iter()
+            .map(|v| (v.version, v.entries.len()))
+            .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            versions(BuildMetadataHandling::Separate),
+            [
+                ("1.0.0".to_string(), 1),
+                ("1.0.0+build.1".to_string(), 1),
+                ("1.0.0+build.2".to_string(), 1),
+            ]
+        );
+        assert_eq!(
+            versions(BuildMetadataHandling::Merge),
+            [("1.0.0".to_string(), 3)]
+        );
+    }
+
+    #[test]
+    fn test_template_data_entries_sorted_by_created() {
+        let fragment = |path: &str, created: Option<&str>| {
+            let mut header = IndexMap::new();
+            if let Some(created) = created {
+                header.insert(
+                    "created".to_string(),
+                    FragmentData::Str(created.to_string()),
+                );
+            }
+            let mut fragment = Fragment::new(header, String::new());
+            fragment.set_source_path(PathBuf::from(path));
+            Ok((Some(semver::Version::new(1, 0, 0)), fragment))
+        };
+        let fragments = || {
+            [
+                fragment("a.md", None),
+                fragment("b.md", Some("2024-02-01T00:00:00Z")),
+                fragment("c.md", Some("2024-01-01T12:00:00+02:00")),
+                fragment("d.md", Some("not a timestamp")),
+            ]
+            .into_iter()
+        };
+        let paths = |sort_by| {
+            generate_template_data(
+                fragments(),
+                PrereleaseHandling::Separate,
+                &HashMap::new(),
+                sort_by,
+                Some("created"),
+                &HashSet::new(),
+                &HashSet::new(),
+                false,
+                None,
+            )
+            .unwrap()
+            .versions[0]
+                .entries
+                .iter()
+                .map(|entry| entry.source_path().clone().unwrap())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            paths(EntrySortBy::Created),
+            ["c.md", "b.md", "a.md", "d.md"].map(PathBuf::from)
+        );
+        assert_eq!(
+            paths(EntrySortBy::Path),
+            ["a.md", "b.md", "c.md", "d.md"].map(PathBuf::from)
+        );
+    }
+
+    #[test]
+    fn test_template_data_prerelease_separate() {
+        let result = generate_template_data(
+            prerelease_fragments(),
+            PrereleaseHandling::Separate,
+            &HashMap::new(),
+            EntrySortBy::Path,
+            None,
+            &HashSet::new(),
+            &HashSet::new(),
+            false,
+            None,
+        )
+        .unwrap();
+
+        assert_eq!(result.versions.len(), 2);
+        assert!(result.versions.iter().any(|v| v.version == "1.0.0-rc.1"));
+        assert!(result.versions.iter().any(|v| v.version == "1.0.0"));
+    }
+
+    #[test]
+    fn test_template_data_prerelease_hide() {
+        let result = generate_template_data(
+            prerelease_fragments(),
+            PrereleaseHandling::Hide,
+            &HashMap::new(),
+            EntrySortBy::Path,
+            None,
+            &HashSet::new(),
+            &HashSet::new(),
+            false,
+            None,
+        )
+        .unwrap();
+
+        assert_eq!(result.versions.len(), 1);
+        assert_eq!(result.versions[0].version, "1.0.0");
+        assert_eq!(result.versions[0].entries.len(), 1);
+        assert_eq!(result.versions[0].entries[0].text(), "text for 1.0.0");
+    }
+
+    #[test]
+    fn test_template_data_prerelease_fold() {
+        let result = generate_template_data(
+            prerelease_fragments(),
+            PrereleaseHandling::Fold,
+            &HashMap::new(),
+            EntrySortBy::Path,
+            None,
+            &HashSet::new(),
+            &HashSet::new(),
+            false,
+            None,
+        )
+        .unwrap();
+
+        assert_eq!(result.versions.len(), 1);
+        assert_eq!(result.versions[0].version, "1.0.0");
+        assert_eq!(result.versions[0].entries.len(), 2);
+    }
+
+    #[test]
+    fn test_template_data_marks_yanked_versions() {
+        let yanked = HashSet::from([semver::Version::parse("1.0.0-rc.1").unwrap()]);
+        let result = generate_template_data(
+            prerelease_fragments(),
+            PrereleaseHandling::Separate,
+            &HashMap::new(),
+            EntrySortBy::Path,
+            None,
+            &yanked,
+            &HashSet::new(),
+            false,
+            None,
+        )
+        .unwrap();
+
+        let yanked_versions = result
+            .versions
+            .iter()
+            .filter(|v| v.yanked)
+            .map(|v| v.version.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(yanked_versions, ["1.0.0-rc.1"]);
+    }
+
+    #[test]
+    fn test_template_data_contains_empty_versions() {
+        let empty = HashSet::from([semver::Version::new(2, 0, 0)]);
+        let result = generate_template_data(
+            prerelease_fragments(),
+            PrereleaseHandling::Hide,
+            &HashMap::new(),
+            EntrySortBy::Path,
+            None,
+            &HashSet::new(),
+            &empty,
+            false,
+            None,
+        )
+        .unwrap();
+
+        assert_eq!(result.versions.len(), 2);
+        assert_eq!(result.versions[1].version, "2.0.0");
+        assert!(result.versions[1].entries.is_empty());
+    }
+
+    #[test]
+    fn test_template_data_includes_unreleased() {
+        let result = generate_template_data(
+            prerelease_fragments(),
+            PrereleaseHandling::Separate,
+            &HashMap::new(),
+            EntrySortBy::Path,
+            None,
+            &HashSet::new(),
+            &HashSet::new(),
+            true,
+            None,
+        )
+        .unwrap();
+
+        let unreleased = result.versions.last().unwrap();
+        assert_eq!(unreleased.version, "unreleased");
+        assert!(unreleased.entries.is_empty());
+    }
+
+    #[test]
+    fn test_template_data_has_previous_versions() {
+        let result = generate_template_data(
+            prerelease_fragments(),
+            PrereleaseHandling::Separate,
+            &HashMap::new(),
+            EntrySortBy::Path,
+            None,
+            &HashSet::new(),
+            &HashSet::from([semver::Version::new(0, 9, 0)]),
+            false,
+            None,
+        )
+        .unwrap();
+
+        let previous = result
+            .versions
+            .iter()
+            .map(|v| (v.version.as_str(), v.previous_version.as_deref()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            previous,
+            [
+                ("0.9.0", None),
+                ("1.0.0-rc.1", Some("0.9.0")),
+                ("1.0.0", Some("1.0.0-rc.1")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_template_data_has_tags() {
+        let result = generate_template_data(
+            prerelease_fragments(),
+            PrereleaseHandling::Separate,
+            &HashMap::new(),
+            EntrySortBy::Path,
+            None,
+            &HashSet::new(),
+            &HashSet::new(),
+            true,
+            None,
+        )
+        .unwrap();
+
+        let versions = with_tags(result.versions, "release-{{version}}").unwrap();
+        let tags = versions
+            .iter()
+            .map(|v| (v.tag.as_deref(), v.previous_tag.as_deref()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            tags,
+            [
+                (Some("release-1.0.0-rc.1"), None),
+                (Some("release-1.0.0"), Some("release-1.0.0-rc.1")),
+                (None, Some("release-1.0.0")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_template_data_uses_order() {
+        let fragments = || {
+            ["0.9.0", "0.10.0", "1.0.0"].into_iter().map(|version| {
+                Ok((
+                    Some(semver::Version::parse(version).unwrap()),
+                    Fragment::new(IndexMap::new(), String::new()),
+                ))
+            })
+        };
+        let versions = |data: TemplateData| {
+            data.versions
+                .into_iter()
+                .map(|v| v.version)
+                .collect::<Vec<_>>()
+        };
+
+        let result = generate_template_data(
+            fragments(),
+            PrereleaseHandling::Separate,
+            &HashMap::new(),
+            EntrySortBy::Path,
+            None,
+            &HashSet::new(),
+            &HashSet::new(),
+            false,
+            None,
+        )
+        .unwrap();
+        assert_eq!(versions(result), ["0.9.0", "0.10.0", "1.0.0"]);
+
+        let result = generate_template_data(
+            fragments(),
+            PrereleaseHandling::Separate,
+            &HashMap::from([("0.9.0".to_string(), 1)]),
+            EntrySortBy::Path,
+            None,
+            &HashSet::new(),
+            &HashSet::new(),
+            false,
+            None,
+        )
+        .unwrap();
+        assert_eq!(result.version_list, ["0.10.0", "1.0.0", "0.9.0"]);
+        assert_eq!(result.total_versions, 3);
+        assert_eq!(result.total_entries, 3);
+        assert_eq!(versions(result), ["0.10.0", "1.0.0", "0.9.0"]);
+    }
+
+    #[test]
+    fn test_unique_slug() {
+        let mut slugs = HashSet::new();
+        assert_eq!(unique_slug("0.1.0", &mut slugs), "v0-1-0");
+        assert_eq!(unique_slug("1.0.0-rc.1", &mut slugs), "v1-0-0-rc-1");
+        assert_eq!(unique_slug("1.0.0-rc-1", &mut slugs), "v1-0-0-rc-1-2");
+        assert_eq!(unique_slug("unreleased", &mut slugs), "unreleased");
+    }
+
+    #[test]
+    fn test_version_range_contains() {
+        let v = |s| semver::Version::parse(s).unwrap();
+        let range = VersionRange::builder()
+            .from(Some(v("0.2.0")))
+            .to(Some(v("0.4.0")))
+            .build();
+        assert!(!range.contains(Some(&v("0.1.9"))));
+        assert!(range.contains(Some(&v("0.2.0"))));
+        assert!(range.contains(Some(&v("0.4.0"))));
+        assert!(!range.contains(Some(&v("0.4.1"))));
+        assert!(!range.contains(None));
+
+        let range = VersionRange::builder()
+            .from(Some(v("0.2.0")))
+            .to(None)
+            .build();
+        assert!(range.contains(Some(&v("1.0.0"))));
+        assert!(range.contains(None));
+    }
+
+    #[test]
+    fn test_expand_body_template() {
+        let mut fragment = Fragment::new(
+            {
+                let mut hm = IndexMap::new();
+                hm.insert("issue".to_string(), FragmentData::Int(123));
+                hm
+            },
+            "Fixed in {{version}}, see #{{issue}}".to_string(),
+        );
+
+        let version = semver::Version::new(0, 2, 0);
+        let result = expand_body_template(&mut fragment, Some(&version));
+        assert!(result.is_ok(), "Not ok: {:?}", result.unwrap_err());
+        assert_eq!(fragment.text(), "Fixed in 0.2.0, see #123");
+    }
+
+    #[test]
+    fn test_expand_malformed_body_template_errors() {
+        let mut fragment = Fragment::new(IndexMap::new(), "{{#if}} broken".to_string());
+
+        let result = expand_body_template(&mut fragment, None);
+        assert!(
+            std::matches!(result, Err(FragmentError::BodyTemplate(_))),
+            "Expected body template error: {result:?}"
+        );
+    }
+
     #[test]
     fn default_template_renders_with_empty_data() {
-        let hb = crate::template::new_handlebars(crate::consts::DEFAULT_TEMPLATE).unwrap();
+        let hb = crate::template::new_handlebars(crate::consts::DEFAULT_TEMPLATE, false).unwrap();
         let data: HashMap<String, Vec<String>> = HashMap::new();
         let template = hb.render(crate::consts::INTERNAL_TEMPLATE_NAME, &data);
         assert!(template.is_ok(), "Not ok: {:?}", template.unwrap_err());
@@ -261,15 +1767,21 @@ mod tests {
 
     #[test]
     fn default_template_renders_with_one_entry() {
-        let hb = crate::template::new_handlebars(crate::consts::DEFAULT_TEMPLATE).unwrap();
+        let hb = crate::template::new_handlebars(crate::consts::DEFAULT_TEMPLATE, false).unwrap();
         let mut data: HashMap<String, Vec<_>> = HashMap::new();
         data.insert(
             "versions".to_string(),
             vec![VersionData {
                 version: "0.1.0".to_string(),
+                yanked: false,
+                slug: String::new(),
+                previous_version: None,
+                tag: None,
+                previous_tag: None,
+                sort_key: 0,
                 entries: vec![Fragment::new(
                     {
-                        let mut hdr = HashMap::new();
+                        let mut hdr = IndexMap::new();
                         hdr.insert("issue".to_string(), FragmentData::Int(123));
                         hdr.insert("type".to_string(), FragmentData::Str("Bugfix".to_string()));
                         hdr
@@ -295,15 +1807,21 @@ mod tests {
 
     #[test]
     fn default_template_renders_with_one_entry_with_header() {
-        let hb = crate::template::new_handlebars(crate::consts::DEFAULT_TEMPLATE).unwrap();
+        let hb = crate::template::new_handlebars(crate::consts::DEFAULT_TEMPLATE, false).unwrap();
         let mut data: HashMap<String, Vec<_>> = HashMap::new();
         data.insert(
             "versions".to_string(),
             vec![VersionData {
                 version: "0.1.0".to_string(),
+                yanked: false,
+                slug: String::new(),
+                previous_version: None,
+                tag: None,
+                previous_tag: None,
+                sort_key: 0,
                 entries: vec![Fragment::new(
                     {
-                        let mut hdr = HashMap::new();
+                        let mut hdr = IndexMap::new();
                         hdr.insert("issue".to_string(), FragmentData::Int(123));
                         hdr.insert("type".to_string(), FragmentData::Str("Bugfix".to_string()));
                         hdr
@@ -324,16 +1842,22 @@ mod tests {
 
     #[test]
     fn default_template_renders_versions_sorted() {
-        let hb = crate::template::new_handlebars(crate::consts::DEFAULT_TEMPLATE).unwrap();
+        let hb = crate::template::new_handlebars(crate::consts::DEFAULT_TEMPLATE, false).unwrap();
         let mut data: HashMap<String, Vec<_>> = HashMap::new();
         data.insert(
             "versions".to_string(),
             vec![
                 VersionData {
                     version: "0.1.0".to_string(),
+                    yanked: false,
+                    slug: String::new(),
+                    previous_version: None,
+                    tag: None,
+                    previous_tag: None,
+                    sort_key: 0,
                     entries: vec![Fragment::new(
                         {
-                            let mut hdr = HashMap::new();
+                            let mut hdr = IndexMap::new();
                             hdr.insert("issue".to_string(), FragmentData::Int(123));
                             hdr.insert("type".to_string(), FragmentData::Str("Bugfix".to_string()));
                             hdr
@@ -343,9 +1867,15 @@ mod tests {
                 },
                 VersionData {
                     version: "0.2.0".to_string(),
+                    yanked: false,
+                    slug: String::new(),
+                    previous_version: None,
+                    tag: None,
+                    previous_tag: None,
+                    sort_key: 0,
                     entries: vec![Fragment::new(
                         {
-                            let mut hdr = HashMap::new();
+                            let mut hdr = IndexMap::new();
                             hdr.insert("issue".to_string(), FragmentData::Int(234));
                             hdr.insert(
                                 "type".to_string(),
@@ -0,0 +1,55 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::{cli::ListVersionsFormat, config::Configuration, error::Error};
+
+#[derive(Debug, typed_builder::TypedBuilder)]
+pub struct ListVersionsCommand {
+    format: Option<ListVersionsFormat>,
+}
+
+impl crate::command::Command for ListVersionsCommand {
+    fn execute(
+        self,
+        workdir: &Path,
+        config: &Configuration,
+    ) -> Result<Option<std::process::ExitCode>, Error> {
+        // Load the fragments like `generate-changelog` does, so that skipped fragments are left
+        // out and the compacted database is included
+        let mut versions: BTreeMap<semver::Version, usize> = BTreeMap::new();
+        for r in
+            crate::command::generate_changelog_command::load_release_files(workdir, config, false)?
+        {
+            if let (Some(version), _) = r? {
+                *versions.entry(version).or_default() += 1;
+            }
+        }
+
+        match self.format.unwrap_or_default() {
+            ListVersionsFormat::Text => {
+                for (version, count) in versions {
+                    println!("{version} ({count})");
+                }
+            }
+            ListVersionsFormat::Json => {
+                let reply = versions
+                    .into_iter()
+                    .map(|(version, fragments)| VersionCount {
+                        version: version.to_string(),
+                        fragments,
+                    })
+                    .collect::<Vec<_>>();
+
+                println!("{}", serde_json::to_string(&reply)?);
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+#[derive(Debug, serde::Serialize)]
+struct VersionCount {
+    version: String,
+    fragments: usize,
+}
@@ -3,6 +3,7 @@ use std::path::Path;
 use crate::config::Configuration;
 
 mod common;
+pub use self::common::get_version_of_fragment;
 
 mod add_command;
 pub use self::add_command::AddCommand;
@@ -11,8 +12,10 @@ mod create_release_command;
 pub use self::create_release_command::CreateReleaseCommand;
 
 mod generate_changelog_command;
+pub use self::generate_changelog_command::check_templates_render;
 pub use self::generate_changelog_command::GenerateChangelogCommand;
 pub use self::generate_changelog_command::VersionData;
+pub use self::generate_changelog_command::VersionRange;
 
 mod show;
 pub use self::show::Show;
@@ -23,6 +26,27 @@ pub use self::verify_metadata_command::VerifyMetadataCommand;
 mod has;
 pub use self::has::HasCommand;
 
+mod list_versions;
+pub use self::list_versions::ListVersionsCommand;
+
+mod schema;
+pub use self::schema::SchemaCommand;
+
+mod move_command;
+pub use self::move_command::MoveCommand;
+
+mod stats;
+pub use self::stats::StatsCommand;
+
+mod compact;
+pub use self::compact::CompactCommand;
+
+mod edit;
+pub use self::edit::EditCommand;
+
+mod add_type;
+pub use self::add_type::AddTypeCommand;
+
 pub trait Command {
     fn execute(
         self,
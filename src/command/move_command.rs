@@ -0,0 +1,92 @@
+use std::path::{Path, PathBuf};
+
+use crate::{
+    config::{Configuration, Layout},
+    error::Error,
+    format::Format,
+    fragment::{Fragment, FragmentData},
+};
+
+#[derive(Debug, typed_builder::TypedBuilder)]
+pub struct MoveCommand {
+    fragment: PathBuf,
+    version: String,
+}
+
+impl crate::command::Command for MoveCommand {
+    fn execute(
+        self,
+        workdir: &Path,
+        config: &Configuration,
+    ) -> Result<Option<std::process::ExitCode>, Error> {
+        // make sure we do not move the fragment into a directory that is not a version
+        let version = semver::Version::parse(&self.version)?;
+
+        if !self.fragment.is_file() {
+            return Err(Error::NotAFile(self.fragment));
+        }
+
+        let root = workdir.join(config.fragment_dir());
+        if !self
+            .fragment
+            .canonicalize()?
+            .starts_with(root.canonicalize()?)
+        {
+            return Err(Error::NotInFragmentDir(self.fragment));
+        }
+
+        match config.layout() {
+            Layout::Flat => set_version_header(&self.fragment, &version)?,
+            Layout::Versioned => move_to_version_dir(&root, &self.fragment, &version)?,
+        }
+
+        Ok(None)
+    }
+}
+
+/// Set the version header field of a fragment with the "flat" layout
+fn set_version_header(path: &Path, version: &semver::Version) -> Result<(), Error> {
+    let mut fragment = Fragment::from_path(path)?;
+
+    tracing::info!("Setting version of {} to {}", path.display(), version);
+    fragment.header_mut().insert(
+        crate::consts::VERSION_HEADER_FIELD.to_string(),
+        FragmentData::Str(version.to_string()),
+    );
+
+    // Serialize first, so that the file is not truncated if that fails
+    let content = fragment
+        .to_string(Format::Toml)
+        .map_err(|e| Error::Fragment(e, path.to_path_buf()))?;
+    let mut file = std::fs::OpenOptions::new()
+        .create(false)
+        .truncate(true)
+        .write(true)
+        .open(path)?;
+    std::io::Write::write_all(&mut file, content.as_bytes())?;
+    file.sync_all()?;
+    Ok(())
+}
+
+/// Move the fragment into the directory of the version, without overwriting an existing file
+fn move_to_version_dir(root: &Path, path: &Path, version: &semver::Version) -> Result<(), Error> {
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| Error::NotAFile(path.to_path_buf()))?;
+
+    let version_dir = root.join(version.to_string());
+    match std::fs::create_dir(&version_dir) {
+        Ok(()) => tracing::debug!("Created directory {}", version_dir.display()),
+        Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {}
+        Err(e) => return Err(Error::from(e)),
+    }
+
+    let destination = version_dir.join(file_name);
+    if destination.exists() {
+        return Err(Error::FragmentExists(destination));
+    }
+
+    tracing::info!("Moving: {} -> {}", path.display(), destination.display());
+    std::fs::rename(path, destination)?;
+    Ok(())
+}
@@ -0,0 +1,45 @@
+use std::path::Path;
+
+use crate::{config::Configuration, error::Error};
+
+/// Print a JSON Schema describing the fragment header
+#[derive(Debug, typed_builder::TypedBuilder)]
+pub struct SchemaCommand {}
+
+impl crate::command::Command for SchemaCommand {
+    fn execute(
+        self,
+        _workdir: &Path,
+        config: &Configuration,
+    ) -> Result<Option<std::process::ExitCode>, Error> {
+        let schema = header_schema(config);
+        println!("{}", serde_json::to_string_pretty(&schema)?);
+        Ok(None)
+    }
+}
+
+fn header_schema(config: &Configuration) -> serde_json::Value {
+    let mut properties = serde_json::Map::new();
+    let mut required = Vec::new();
+
+    for (key, desc) in config.header_fields() {
+        let mut property = desc.fragment_type().json_schema();
+        if let Some(default) = desc.default_value() {
+            property["default"] = serde_json::to_value(default).unwrap_or_default();
+        }
+        properties.insert(key.to_string(), property);
+
+        if desc.required() {
+            required.push(key.to_string());
+        }
+    }
+    required.sort();
+
+    serde_json::json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "title": "cargo-changelog fragment header",
+        "type": "object",
+        "properties": properties,
+        "required": required,
+    })
+}
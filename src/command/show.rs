@@ -1,6 +1,5 @@
 use std::{
     collections::HashMap,
-    io::BufReader,
     io::Write,
     path::{Path, PathBuf},
 };
@@ -10,15 +9,17 @@ use yansi::Paint;
 
 use crate::{
     cli::{Selector, ShowFormat},
-    config::Configuration,
-    error::{Error, FragmentError},
-    fragment::Fragment,
+    config::{Configuration, Layout},
+    error::Error,
+    fragment::{Fragment, HeaderFilter},
 };
 
 #[derive(Debug, typed_builder::TypedBuilder)]
 pub struct Show {
     format: Option<crate::cli::ShowFormat>,
     selector: Option<Selector>,
+    no_color: bool,
+    author: Option<String>,
 }
 
 impl crate::command::Command for Show {
@@ -31,22 +32,26 @@ impl crate::command::Command for Show {
             crate::selector::SelectorExecutor::new(self.selector.as_ref()).run(workdir, config)?;
 
         tracing::trace!("Looking at: {pathes:?}");
-        let fragments = pathes.into_iter().map(|path| {
-            std::fs::OpenOptions::new()
-                .read(true)
-                .create(false)
-                .write(false)
-                .open(&path)
-                .map_err(FragmentError::from)
-                .map(BufReader::new)
-                .and_then(|mut reader| {
-                    Fragment::from_reader(&mut reader).map(|f| (path.to_path_buf(), f))
-                })
-                .map_err(|e| Error::Fragment(e, path.to_path_buf()))
+        let fragments = pathes
+            .into_iter()
+            .map(|path| Fragment::from_path(&path).map(|f| (path, f)));
+
+        let filter = self
+            .author
+            .map(|author| HeaderFilter::new(crate::consts::AUTHOR_HEADER_FIELD, author));
+        let fragments = fragments.filter(|r| match (r, filter.as_ref()) {
+            (Ok((_, fragment)), Some(filter)) => filter.matches(fragment),
+            _ => true,
         });
 
         match self.format {
-            None | Some(ShowFormat::Text) => pretty_print(fragments)?,
+            None | Some(ShowFormat::Text) => {
+                let color = !self.no_color
+                    && std::env::var_os("NO_COLOR").is_none_or(|v| v.is_empty())
+                    && std::io::stdout().is_terminal();
+                let root = workdir.join(config.fragment_dir());
+                pretty_print(fragments, &root, config.layout(), color)?
+            }
             Some(ShowFormat::Json) => json_print(fragments)?,
         }
 
@@ -54,31 +59,55 @@ impl crate::command::Command for Show {
     }
 }
 
+/// Print the fragments, grouped by version
+///
+/// The version of each fragment is determined by `layout`, `root` is the fragment directory.
 fn pretty_print(
     mut iter: impl Iterator<Item = Result<(PathBuf, Fragment), Error>>,
+    root: &Path,
+    layout: Layout,
+    color: bool,
 ) -> Result<(), Error> {
     let out = std::io::stdout();
     let mut output = out.lock();
 
-    let is_terminal = std::io::stdout().is_terminal();
-    if !is_terminal {
+    if !color {
         yansi::disable()
     }
 
+    let mut current_version: Option<String> = None;
     iter.try_for_each(|fragment| {
         let (path, fragment) = fragment?;
+
+        let relative_path = path.strip_prefix(root).unwrap_or(&path);
+        let version =
+            crate::command::common::get_version_of_fragment(relative_path, &fragment, layout)?
+                .map(|v| v.to_string())
+                .unwrap_or_else(|| crate::consts::UNRELEASED_DIR_NAME.to_string());
+        if current_version.as_ref() != Some(&version) {
+            writeln!(output, "{}", Paint::new(&version).green().bold())?;
+            writeln!(output)?;
+            current_version = Some(version);
+        }
+
         writeln!(output, "{}", Paint::new(path.display()).bold())?;
         fragment.header().iter().try_for_each(|(key, value)| {
             writeln!(
                 output,
                 "{key}: {value}",
-                key = Paint::new(key).italic(),
+                key = Paint::new(key).cyan().italic(),
                 value = value.display()
             )?;
             Ok(()) as Result<(), Error>
         })?;
 
-        writeln!(output, "{text}", text = fragment.text())?;
+        fragment.text().lines().try_for_each(|line| {
+            if line.is_empty() {
+                writeln!(output)
+            } else {
+                writeln!(output, "    {line}")
+            }
+        })?;
         writeln!(output)?;
         Ok(())
     })
@@ -0,0 +1,143 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::{cli::StatsFormat, config::Configuration, error::Error, fragment::Fragment};
+
+/// Bucket for fragments that do not have the header field that is grouped by
+const NONE_KEY: &str = "(none)";
+
+#[derive(Debug, typed_builder::TypedBuilder)]
+pub struct StatsCommand {
+    format: Option<StatsFormat>,
+}
+
+impl crate::command::Command for StatsCommand {
+    fn execute(
+        self,
+        workdir: &Path,
+        config: &Configuration,
+    ) -> Result<Option<std::process::ExitCode>, Error> {
+        use crate::command::generate_changelog_command::load_release_files;
+
+        let mut stats = Stats::default();
+        for r in load_release_files(workdir, config, true)? {
+            let (version, fragment) = r?;
+            stats.add(version.as_ref(), &fragment);
+        }
+
+        match self.format.unwrap_or_default() {
+            StatsFormat::Text => stats.print(&mut std::io::stdout().lock())?,
+            StatsFormat::Json => println!("{}", serde_json::to_string(&stats)?),
+        }
+
+        Ok(None)
+    }
+}
+
+/// Number of fragments, in total and grouped by version, type and author
+#[derive(Debug, Default, serde::Serialize)]
+struct Stats {
+    total: usize,
+    versions: BTreeMap<String, usize>,
+    types: BTreeMap<String, usize>,
+    authors: BTreeMap<String, usize>,
+}
+
+impl Stats {
+    fn add(&mut self, version: Option<&semver::Version>, fragment: &Fragment) {
+        let header_value = |field: &str| {
+            fragment
+                .header()
+                .get(field)
+                .map_or_else(|| NONE_KEY.to_string(), |v| v.display().to_string())
+        };
+
+        self.total += 1;
+        let version = version.map_or_else(
+            || crate::consts::UNRELEASED_DIR_NAME.to_string(),
+            semver::Version::to_string,
+        );
+        *self.versions.entry(version).or_default() += 1;
+        *self
+            .types
+            .entry(header_value(crate::consts::TYPE_HEADER_FIELD))
+            .or_default() += 1;
+        *self
+            .authors
+            .entry(header_value(crate::consts::AUTHOR_HEADER_FIELD))
+            .or_default() += 1;
+    }
+
+    fn print<W: std::io::Write>(&self, out: &mut W) -> Result<(), Error> {
+        writeln!(out, "Total: {}", self.total)?;
+
+        // Versions in semver order, with "unreleased" last
+        let mut versions = self.versions.iter().collect::<Vec<_>>();
+        versions.sort_by_key(|(version, _)| semver::Version::parse(version).ok().ok_or(()));
+        let groups = [
+            ("version", versions),
+            ("type", self.types.iter().collect()),
+            ("author", self.authors.iter().collect()),
+        ];
+
+        for (name, counts) in groups {
+            writeln!(out)?;
+            writeln!(out, "By {name}:")?;
+            for (key, count) in counts {
+                writeln!(out, "  {key}: {count}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indexmap::IndexMap;
+
+    use super::*;
+    use crate::fragment::FragmentData;
+
+    #[test]
+    fn test_stats() {
+        let fragment = |ty: &str, author: Option<&str>| {
+            let mut header =
+                IndexMap::from([("type".to_string(), FragmentData::Str(ty.to_string()))]);
+            if let Some(author) = author {
+                header.insert("author".to_string(), FragmentData::Str(author.to_string()));
+            }
+            Fragment::new(header, String::new())
+        };
+
+        let mut stats = Stats::default();
+        let v0_2 = semver::Version::new(0, 2, 0);
+        let v0_10 = semver::Version::new(0, 10, 0);
+        stats.add(Some(&v0_10), &fragment("Bugfix", Some("alice")));
+        stats.add(Some(&v0_2), &fragment("Feature", Some("alice")));
+        stats.add(None, &fragment("Bugfix", None));
+
+        let mut out = Vec::new();
+        stats.print(&mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            indoc::indoc!(
+                "
+                Total: 3
+
+                By version:
+                  0.2.0: 1
+                  0.10.0: 1
+                  unreleased: 1
+
+                By type:
+                  Bugfix: 2
+                  Feature: 1
+
+                By author:
+                  (none): 1
+                  alice: 2
+                "
+            )
+        );
+    }
+}
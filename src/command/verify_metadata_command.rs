@@ -1,15 +1,18 @@
-use std::{io::BufReader, path::Path};
-
-use itertools::Itertools;
+use std::path::{Path, PathBuf};
 
 use crate::{
-    config::Configuration,
-    error::{Error, FragmentError, VerificationError},
-    fragment::Fragment,
+    cli::VerifyFormat,
+    config::{BuildMetadataHandling, Configuration, Layout},
+    error::{Error, VerificationError},
+    fragment::{Fragment, FragmentData},
 };
 
 #[derive(Debug, typed_builder::TypedBuilder)]
-pub struct VerifyMetadataCommand {}
+pub struct VerifyMetadataCommand {
+    format: VerifyFormat,
+    /// Only verify these fragment files, instead of all fragments
+    files: Vec<PathBuf>,
+}
 
 impl crate::command::Command for VerifyMetadataCommand {
     fn execute(
@@ -17,39 +20,294 @@ impl crate::command::Command for VerifyMetadataCommand {
         workdir: &Path,
         config: &Configuration,
     ) -> Result<Option<std::process::ExitCode>, Error> {
-        let (_oks, errors): (Vec<_>, Vec<VerificationError>) =
-            walkdir::WalkDir::new(workdir.join(config.fragment_dir()))
-                .follow_links(false)
-                .max_open(100)
-                .same_file_system(true)
-                .into_iter()
-                .map(|rde| {
-                    rde.map(|de| verify_entry(de.path()))
-                        .map_err(VerificationError::from)
-                })
-                .partition_result();
+        let max_version = config
+            .max_version_bump()
+            .map(|bump| {
+                crate::command::common::current_version(workdir)
+                    .map(|current| crate::command::common::bump_version(&current, bump))
+            })
+            .transpose()?;
 
-        if !errors.is_empty() {
-            Err(Error::Verification(errors))
+        let root = workdir.join(config.fragment_dir());
+        let verify_all = self.files.is_empty();
+        let (root, entries): (
+            _,
+            Box<dyn Iterator<Item = Result<PathBuf, VerificationError>>>,
+        ) = if verify_all {
+            warn_about_duplicate_version_dirs(&root, config.build_metadata())?;
+            let entries = crate::command::common::find_fragment_files(workdir, config).map(|rde| {
+                rde.map(walkdir::DirEntry::into_path)
+                    .map_err(VerificationError::from)
+            });
+            (root, Box::new(entries))
         } else {
-            Ok(None)
+            // The paths are relative to the current directory, so compare them as absolute paths
+            // to find the version of the fragments
+            let root = root.canonicalize().unwrap_or(root);
+            let entries = self.files.into_iter().map(|path| {
+                path.canonicalize().map_err(|e| {
+                    VerificationError::FragmentParsing(Error::Fragment(e.into(), path))
+                })
+            });
+            (root, Box::new(entries))
+        };
+
+        let mut errors = Vec::new();
+        let mut fragments_per_version = std::collections::BTreeMap::<_, usize>::new();
+        for entry in entries {
+            let result =
+                entry.and_then(|path| verify_entry(&root, &path, config, max_version.as_ref()));
+
+            match result {
+                Ok(versions) => {
+                    for version in versions {
+                        *fragments_per_version.entry(version).or_default() += 1;
+                    }
+                }
+                Err(e) => errors.push(e),
+            }
+        }
+
+        // Rendering needs all fragments, and would only repeat their errors
+        if verify_all && errors.is_empty() {
+            if let Err(e) = crate::command::check_templates_render(workdir, config) {
+                errors.push(e);
+            }
+        }
+
+        // The number of fragments per version is only known if all fragments were verified
+        if let Some(max) = config.max_fragments_per_version().filter(|_| verify_all) {
+            errors.extend(
+                fragments_per_version
+                    .into_iter()
+                    .filter(|(_, count)| *count > max)
+                    .map(|(version, count)| VerificationError::TooManyFragments {
+                        version: version.to_string(),
+                        count,
+                        max,
+                    }),
+            );
+        }
+
+        match self.format {
+            VerifyFormat::Text if !errors.is_empty() => Err(Error::Verification(errors)),
+            VerifyFormat::Text => Ok(None),
+            VerifyFormat::Json => {
+                let problems = errors.iter().map(Problem::from).collect::<Vec<_>>();
+                println!("{}", serde_json::to_string_pretty(&problems)?);
+
+                if problems.is_empty() {
+                    Ok(None)
+                } else {
+                    Ok(Some(std::process::ExitCode::FAILURE))
+                }
+            }
         }
     }
 }
 
-fn verify_entry(entry: &std::path::Path) -> Result<(), VerificationError> {
-    if crate::command::common::get_version_from_path(entry)?.is_none() {
-        tracing::warn!("No version: {}", entry.display());
-    }
-
-    std::fs::OpenOptions::new()
-        .read(true)
-        .create(false)
-        .write(false)
-        .open(entry)
-        .map_err(FragmentError::from)
-        .map(BufReader::new)
-        .and_then(|mut reader| Fragment::from_reader(&mut reader))
-        .map_err(|e| VerificationError::FragmentParsing(entry.to_path_buf(), e))
-        .map(|_| ())
+/// Warn about version directories with different names, but the same version
+///
+/// Versions that only differ in case are considered the same, as they would collide on
+/// case-insensitive filesystems. Versions that only differ in build metadata are only considered
+/// the same if `build_metadata` merges them.
+fn warn_about_duplicate_version_dirs(
+    root: &Path,
+    build_metadata: BuildMetadataHandling,
+) -> Result<(), Error> {
+    let mut versions = std::collections::BTreeMap::<String, Vec<String>>::new();
+    for entry in std::fs::read_dir(root)? {
+        let entry = entry?;
+        if !entry.file_type()?.is_dir() {
+            continue;
+        }
+
+        let Some(name) = entry.file_name().to_str().map(String::from) else {
+            continue;
+        };
+        if let Ok(mut version) = semver::Version::parse(&name) {
+            if build_metadata == BuildMetadataHandling::Merge {
+                version.build = semver::BuildMetadata::EMPTY;
+            }
+            versions
+                .entry(version.to_string().to_lowercase())
+                .or_default()
+                .push(name);
+        }
+    }
+
+    for (version, mut dirs) in versions {
+        if dirs.len() > 1 {
+            dirs.sort();
+            tracing::warn!(
+                "Multiple directories for version {}: {}",
+                version,
+                dirs.join(", ")
+            );
+        }
+    }
+    Ok(())
+}
+
+fn verify_entry(
+    root: &Path,
+    entry: &Path,
+    config: &Configuration,
+    max_version: Option<&semver::Version>,
+) -> Result<Vec<semver::Version>, VerificationError> {
+    let layout = config.layout();
+
+    // Check the raw file, as conflict markers in the header would otherwise only show up as a
+    // confusing parsing error
+    let content = std::fs::read(entry).map_err(|e| {
+        VerificationError::FragmentParsing(Error::Fragment(e.into(), entry.to_path_buf()))
+    })?;
+    if let Some(line) = find_conflict_marker(&String::from_utf8_lossy(&content)) {
+        return Err(VerificationError::ConflictMarker {
+            path: entry.to_path_buf(),
+            line,
+        });
+    }
+
+    let mut fragment = Fragment::from_path(entry).map_err(VerificationError::FragmentParsing)?;
+
+    if config.coerce_scalars() {
+        fragment.coerce_header(config.header_fields());
+    }
+    if config.normalize_enum_case() {
+        fragment.normalize_enum_case(config.header_fields());
+    }
+    fragment
+        .check_header_types(config.header_fields())
+        .map_err(|e| VerificationError::FragmentParsing(Error::Fragment(e, entry.to_path_buf())))?;
+
+    if let Some(key_case) = config.key_case() {
+        if let Some(key) = fragment.header().keys().find(|key| !key_case.matches(key)) {
+            return Err(VerificationError::KeyCase {
+                path: entry.to_path_buf(),
+                key: key.to_string(),
+                key_case,
+            });
+        }
+    }
+
+    if let Some(max) = config.max_body_length() {
+        let length = fragment.text().trim().chars().count();
+        if length > max {
+            return Err(VerificationError::BodyTooLong {
+                path: entry.to_path_buf(),
+                length,
+                max,
+            });
+        }
+    }
+
+    if let Some(FragmentData::Str(fragment_type)) =
+        fragment.header().get(crate::consts::TYPE_HEADER_FIELD)
+    {
+        let body_required = config
+            .require_body_for()
+            .iter()
+            .any(|ty| ty.eq_ignore_ascii_case(fragment_type));
+        if body_required && fragment.text().trim().is_empty() {
+            return Err(VerificationError::BodyRequired {
+                path: entry.to_path_buf(),
+                fragment_type: fragment_type.to_string(),
+            });
+        }
+    }
+
+    // Only look at the path inside the fragment directory, so that the location of the
+    // repository itself does not matter
+    let relative_path = entry.strip_prefix(root).unwrap_or(entry);
+
+    let version =
+        crate::command::common::get_version_of_fragment(relative_path, &fragment, layout)?;
+    if version.is_none() {
+        let is_unreleased = relative_path
+            .components()
+            .next()
+            .is_some_and(|comp| comp.as_os_str() == crate::consts::UNRELEASED_DIR_NAME);
+
+        if layout == Layout::Versioned && !is_unreleased {
+            return Err(VerificationError::NoVersionDirectory(entry.to_path_buf()));
+        }
+    }
+
+    let versions =
+        crate::command::common::get_versions_of_fragment(relative_path, &fragment, layout)?
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>();
+    if versions.is_empty() {
+        tracing::debug!("No version: {}", entry.display());
+    }
+
+    for version in versions.iter() {
+        if let Some(max_version) = max_version.filter(|max| version > *max) {
+            tracing::warn!(
+                "Version {} is ahead of the next expected version {}: {}",
+                version,
+                max_version,
+                entry.display()
+            );
+        }
+
+        if let Some(field) = config.breaking_change_field() {
+            let is_breaking =
+                std::matches!(fragment.header().get(field), Some(FragmentData::Bool(true)));
+            if is_breaking && version.patch > 0 {
+                return Err(VerificationError::BreakingChangeInPatchRelease {
+                    path: entry.to_path_buf(),
+                    version: version.to_string(),
+                });
+            }
+        }
+    }
+
+    Ok(versions)
+}
+
+/// Find the first line (1-based) that is a merge conflict marker
+///
+/// Only the `<<<<<<<`, `|||||||` and `>>>>>>>` markers are looked for, as a line of `=======` is also
+/// a valid markdown heading underline.
+fn find_conflict_marker(content: &str) -> Option<usize> {
+    const MARKERS: [&str; 3] = ["<<<<<<<", "|||||||", ">>>>>>>"];
+
+    content
+        .lines()
+        .position(|line| {
+            MARKERS.iter().any(|marker| {
+                line.strip_prefix(marker)
+                    .is_some_and(|rest| rest.is_empty() || rest.starts_with(' '))
+            })
+        })
+        .map(|idx| idx + 1)
+}
+
+/// A verification problem, as printed with `--format json`
+#[derive(Debug, serde::Serialize)]
+struct Problem<'a> {
+    path: Option<&'a Path>,
+    kind: &'static str,
+    message: String,
+}
+
+impl<'a> From<&'a VerificationError> for Problem<'a> {
+    fn from(error: &'a VerificationError) -> Self {
+        let mut message = error.to_string();
+        let mut source = std::error::Error::source(error);
+        while let Some(e) = source {
+            message.push_str(": ");
+            message.push_str(&e.to_string());
+            source = e.source();
+        }
+
+        Problem {
+            path: error.path(),
+            kind: error.kind(),
+            message,
+        }
+    }
 }
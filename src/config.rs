@@ -1,13 +1,21 @@
 use std::collections::HashMap;
+
+use indexmap::IndexMap;
 use std::path::Path;
 use std::path::PathBuf;
 
 use miette::IntoDiagnostic;
 
+use crate::cli::Bump;
 use crate::error::Error;
 use crate::fragment::FragmentDataDesc;
 
-pub const CONFIG_FILE_NAMES: &[&str] = &[".changelog.toml", "changelog.toml"];
+pub const CONFIG_FILE_NAMES: &[&str] = &[
+    ".changelog.toml",
+    "changelog.toml",
+    ".changelog.json",
+    "changelog.json",
+];
 pub const CONFIG_FILE_DEFAULT_NAME: &str = CONFIG_FILE_NAMES[1];
 pub const DEFAULT_CONFIG: &str = include_str!("../assets/default_config.toml");
 
@@ -41,6 +49,8 @@ pub struct Configuration {
     ///
     /// By default: "CHANGELOG.md"
     ///
+    /// Overridden by `--changelog` and the CARGO_CHANGELOG_OUTPUT environment variable.
+    ///
     /// ```rust
     /// assert_eq!(changelog_default(), "CHANGELOG.md");
     /// ```
@@ -74,8 +84,257 @@ pub struct Configuration {
     #[getset(get_copy = "pub")]
     git_commit_signoff: bool,
 
+    /// The handlebars template for the commit message when generating the changelog with
+    /// `--commit`
+    ///
+    /// By default: "Release v{{version}}"
+    #[getset(get = "pub")]
+    #[serde(default = "release_commit_message_default")]
+    release_commit_message: String,
+
+    /// The handlebars template for the name of the tag when generating the changelog with
+    /// `--tag`
+    ///
+    /// By default: "v{{version}}"
+    #[getset(get = "pub")]
+    #[serde(default = "tag_format_default")]
+    tag_format: String,
+
+    /// A command to run after the changelog was generated
+    ///
+    /// The released version is passed in the "CHANGELOG_VERSION" environment variable.
+    #[getset(get = "pub")]
+    #[serde(default)]
+    post_release_hook: Option<String>,
+
+    /// How fragments are laid out in the fragment directory
+    ///
+    /// Possible values are "versioned" (default) or "flat".
+    #[getset(get_copy = "pub")]
+    #[serde(default)]
+    layout: Layout,
+
+    /// Whether to render the text of fragments as handlebars template, with the header fields
+    /// of the fragment (and its version) as data
+    #[getset(get_copy = "pub")]
+    #[serde(default)]
+    expand_body_templates: bool,
+
+    /// How to handle pre-release versions (e.g. "1.0.0-rc.1") when generating the changelog
+    ///
+    /// Possible values are "separate" (default), "hide" or "fold".
+    #[getset(get_copy = "pub")]
+    #[serde(default)]
+    prerelease: PrereleaseHandling,
+
+    /// How to handle versions that only differ in build metadata (e.g. "1.0.0" and
+    /// "1.0.0+build.1") when generating the changelog
+    ///
+    /// Possible values are "separate" (default) or "merge".
+    #[getset(get_copy = "pub")]
+    #[serde(default)]
+    build_metadata: BuildMetadataHandling,
+
+    /// How to sort the entries within a version
+    ///
+    /// Possible values are "path" (default) or "created".
+    #[getset(get_copy = "pub")]
+    #[serde(default)]
+    entry_sort_by: EntrySortBy,
+
+    /// Whether `add` writes the creation time of new fragments to the `created_field` header field
+    #[getset(get_copy = "pub")]
+    #[serde(default = "record_created_default")]
+    record_created: bool,
+
+    /// The header field holding the creation time of a fragment, as RFC 3339 timestamp
+    #[getset(get = "pub")]
+    #[serde(default = "created_field_default")]
+    created_field: String,
+
+    /// The header field that leaves a fragment out of the changelog if it is `true`
     #[getset(get = "pub")]
-    header_fields: HashMap<String, FragmentDataDesc>,
+    #[serde(default = "skip_field_default")]
+    skip_field: String,
+
+    /// The audience of fragments without an `audience` header field, for `--audience`
+    ///
+    /// By default: "external"
+    ///
+    /// ```rust
+    /// assert_eq!(default_audience_default(), "external");
+    /// ```
+    #[getset(get = "pub")]
+    #[serde(default = "default_audience_default")]
+    default_audience: String,
+
+    /// Whether to look up the commit that added each fragment, for the `commit` of the entries in
+    /// the template
+    #[getset(get_copy = "pub")]
+    #[serde(default)]
+    resolve_commits: bool,
+
+    /// Whether to convert header values to the type of their header field where this is
+    /// unambiguous, e.g. `issue = "123"` to an integer
+    #[getset(get_copy = "pub")]
+    #[serde(default)]
+    coerce_scalars: bool,
+
+    /// Whether to match values of fields with a list of possible values ignoring case, and
+    /// replace them with the possible value, e.g. `type = "bugfix"` with "Bugfix"
+    #[getset(get_copy = "pub")]
+    #[serde(default)]
+    normalize_enum_case: bool,
+
+    /// The line endings to use in the generated changelog
+    ///
+    /// Possible values are "lf" (default), "crlf" or "native".
+    #[getset(get_copy = "pub")]
+    #[serde(default)]
+    line_ending: LineEnding,
+
+    /// What `add` does if a required header field has no value
+    ///
+    /// Possible values are "error" (default), "warn" or "placeholder".
+    #[getset(get_copy = "pub")]
+    #[serde(default)]
+    missing_required: MissingRequired,
+
+    /// The casing of header keys, which `add` writes keys in and `verify-metadata` enforces
+    ///
+    /// Possible values are "snake" or "kebab" (or none, which is default and allows any casing).
+    #[getset(get_copy = "pub")]
+    #[serde(default)]
+    key_case: Option<KeyCase>,
+
+    /// The largest version bump (relative to the version in the Cargo.toml) a fragment may
+    /// target before `verify-metadata` warns about it
+    ///
+    /// Possible values are "patch", "minor" or "major" (or none, which is default and disables
+    /// the check).
+    #[getset(get_copy = "pub")]
+    #[serde(default)]
+    max_version_bump: Option<Bump>,
+
+    /// The maximum number of fragments a version may have before `verify-metadata` fails
+    #[getset(get_copy = "pub")]
+    #[serde(default)]
+    max_fragments_per_version: Option<usize>,
+
+    /// The name of a bool header field that marks a fragment as breaking change
+    ///
+    /// If set, `verify-metadata` fails for breaking changes in patch releases.
+    #[getset(get = "pub")]
+    #[serde(default)]
+    breaking_change_field: Option<String>,
+
+    /// Explicit sort keys for versions, overriding the semver ordering
+    ///
+    /// Versions without a sort key have the sort key 0.
+    #[getset(get = "pub")]
+    #[serde(default)]
+    order: HashMap<String, i64>,
+
+    /// Whether to follow symlinks in the fragment directory
+    #[getset(get_copy = "pub")]
+    #[serde(default)]
+    follow_symlinks: bool,
+
+    /// How many directory levels below the fragment directory are searched for fragments
+    ///
+    /// With the default of 3, fragments can be in one subdirectory of a version directory.
+    #[getset(get_copy = "pub")]
+    #[serde(default = "max_walk_depth_default")]
+    max_walk_depth: usize,
+
+    /// How many threads load fragments in parallel, by default one per CPU
+    #[getset(get_copy = "pub")]
+    #[serde(default)]
+    threads: Option<std::num::NonZeroUsize>,
+
+    /// A JSON lines file, relative to `fragment_dir`, that `compact` moves released fragments into
+    ///
+    /// The fragments in it are loaded together with the fragment files.
+    #[getset(get = "pub")]
+    #[serde(default)]
+    database: Option<PathBuf>,
+
+    /// The URL of the repository, used by templates for links comparing versions
+    #[getset(get = "pub")]
+    #[serde(default)]
+    repository_url: Option<String>,
+
+    /// The URL relative links in the text of fragments are resolved against when generating the
+    /// changelog
+    #[getset(get = "pub")]
+    #[serde(default)]
+    base_url: Option<String>,
+
+    /// The maximum number of characters the text of a fragment may have
+    #[getset(get_copy = "pub")]
+    #[serde(default)]
+    max_body_length: Option<usize>,
+
+    /// Values of the "type" header field for which fragments must have a non-empty text
+    #[getset(get = "pub")]
+    #[serde(default)]
+    require_body_for: Vec<String>,
+
+    /// Whether versions without any fragments are left out of the changelog
+    ///
+    /// If false, version directories without fragments are rendered as well.
+    #[getset(get_copy = "pub")]
+    #[serde(default = "skip_empty_versions_default")]
+    skip_empty_versions: bool,
+
+    /// Front-matter to put at the top of the changelog, as `---`-delimited YAML block
+    #[getset(get = "pub")]
+    #[serde(default)]
+    changelog_frontmatter: Option<toml::Table>,
+
+    /// Additional files the changelog is rendered to, each with its own template
+    #[getset(get = "pub")]
+    #[serde(default)]
+    outputs: Vec<Output>,
+
+    /// Arbitrary values that are passed to the changelog template as `vars`
+    #[getset(get = "pub")]
+    #[serde(default)]
+    template_vars: toml::Table,
+
+    #[getset(get = "pub")]
+    header_fields: IndexMap<String, FragmentDataDesc>,
+
+    /// Display headings for the groups of `group_by_header` in templates, e.g. for the values of
+    /// the "type" header field
+    #[getset(get = "pub")]
+    #[serde(default)]
+    group_headings: IndexMap<String, String>,
+
+    /// Display names for versions in templates, e.g. marketing names for calendar versions
+    ///
+    /// Versions are still sorted by their semver.
+    #[getset(get = "pub")]
+    #[serde(default)]
+    version_display_map: IndexMap<String, String>,
+
+    /// Whether rendering the changelog fails for variables that do not exist in the template data,
+    /// instead of rendering them as empty string
+    #[getset(get_copy = "pub")]
+    #[serde(default)]
+    strict_templates: bool,
+
+    /// The order of the groups of `group_by_header` in templates
+    ///
+    /// If set, entries of groups that are not listed are moved into the `group_fallback` group.
+    #[getset(get = "pub")]
+    #[serde(default)]
+    group_order: Vec<String>,
+
+    /// The group that takes the entries of groups not listed in `group_order`
+    #[getset(get = "pub")]
+    #[serde(default = "group_fallback_default")]
+    group_fallback: String,
 }
 
 pub fn fragment_dir_default() -> PathBuf {
@@ -90,6 +349,42 @@ pub fn changelog_default() -> PathBuf {
     PathBuf::from("CHANGELOG.md")
 }
 
+pub fn release_commit_message_default() -> String {
+    String::from("Release v{{version}}")
+}
+
+pub fn tag_format_default() -> String {
+    String::from("v{{version}}")
+}
+
+pub fn skip_empty_versions_default() -> bool {
+    true
+}
+
+pub fn max_walk_depth_default() -> usize {
+    3
+}
+
+pub fn group_fallback_default() -> String {
+    String::from("other")
+}
+
+pub fn record_created_default() -> bool {
+    true
+}
+
+pub fn created_field_default() -> String {
+    crate::consts::CREATED_HEADER_FIELD.to_string()
+}
+
+pub fn skip_field_default() -> String {
+    crate::consts::SKIP_HEADER_FIELD.to_string()
+}
+
+pub fn default_audience_default() -> String {
+    "external".to_string()
+}
+
 #[derive(Debug, serde::Deserialize, serde::Serialize)]
 #[serde(rename_all = "lowercase")]
 pub enum EditFormat {
@@ -107,33 +402,210 @@ impl std::str::FromStr for EditFormat {
     }
 }
 
-/// Load the configuration from the repository
-pub fn load(repo_workdir_path: &Path) -> miette::Result<Configuration> {
-    let mut changelog_config_path = None;
-    for config_path in CONFIG_FILE_NAMES {
-        let check_path = {
-            let mut cfg_path = repo_workdir_path.to_path_buf();
-            cfg_path.push(config_path);
-            cfg_path
+impl Configuration {
+    pub fn set_fragment_dir(&mut self, fragment_dir: PathBuf) {
+        self.fragment_dir = fragment_dir;
+    }
+
+    pub fn set_changelog(&mut self, changelog: PathBuf) {
+        self.changelog = changelog;
+    }
+
+    pub fn set_threads(&mut self, threads: std::num::NonZeroUsize) {
+        self.threads = Some(threads);
+    }
+
+    /// Check that the header fields are named in the configured `key_case`
+    ///
+    /// `add` renames the header keys after checking them against the header fields, so a field
+    /// in another casing would be renamed away from its declaration.
+    fn check_key_case(&self) -> Result<(), Error> {
+        let Some(key_case) = self.key_case else {
+            return Ok(());
         };
 
-        if check_path.exists() {
-            changelog_config_path = Some(check_path);
-            break;
+        match self.header_fields.keys().find(|key| !key_case.matches(key)) {
+            Some(field) => Err(Error::HeaderFieldKeyCase {
+                field: field.to_string(),
+                key_case,
+            }),
+            None => Ok(()),
+        }
+    }
+}
+
+/// The format of a configuration file
+#[derive(Copy, Clone, Debug, Eq, PartialEq, clap::ValueEnum)]
+pub enum ConfigFormat {
+    Toml,
+    Json,
+}
+
+impl ConfigFormat {
+    /// Detect the format from the extension of the file, if it is a known one
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()? {
+            "toml" => Some(Self::Toml),
+            "json" => Some(Self::Json),
+            _ => None,
+        }
+    }
+
+    fn parse(self, s: &str) -> Result<toml::Table, Error> {
+        match self {
+            Self::Toml => toml::from_str(s).map_err(Error::from),
+            Self::Json => serde_json::from_str(s).map_err(Error::ConfigJson),
         }
     }
+}
 
-    let Some(changelog_config_path) = changelog_config_path else {
-        miette::bail!(Error::ConfigDoesNotExist)
+/// Find the configuration file in the repository, or at `explicit_path` if given
+///
+/// Returns `None` if there is no configuration file, e.g. because the configuration is in the
+/// Cargo.toml.
+pub fn find_config_file(
+    repo_workdir_path: &Path,
+    explicit_path: Option<&Path>,
+) -> miette::Result<Option<PathBuf>> {
+    match explicit_path {
+        Some(explicit_path) if explicit_path.is_file() => Ok(Some(explicit_path.to_path_buf())),
+        Some(explicit_path) => {
+            tracing::error!("Configuration file not found: {}", explicit_path.display());
+            miette::bail!(Error::ConfigDoesNotExist)
+        }
+        None => Ok(CONFIG_FILE_NAMES
+            .iter()
+            .map(|config_path| repo_workdir_path.join(config_path))
+            .find(|check_path| check_path.exists())),
+    }
+}
+
+/// Load the configuration from the repository
+///
+/// If `explicit_path` is given, the configuration is loaded from there instead.
+///
+/// The configuration file is parsed as `format`, if given. Otherwise the format is detected from
+/// the extension of the file, falling back to TOML.
+///
+/// If no configuration file exists, the `[package.metadata.changelog]` table of the Cargo.toml is
+/// used. A configuration file always takes precedence over that table.
+pub fn load(
+    repo_workdir_path: &Path,
+    explicit_path: Option<&Path>,
+    format: Option<ConfigFormat>,
+) -> miette::Result<Configuration> {
+    let changelog_config_path = find_config_file(repo_workdir_path, explicit_path)?;
+
+    let mut config: toml::Table = match changelog_config_path {
+        Some(changelog_config_path) => {
+            let config = std::fs::read_to_string(&changelog_config_path)
+                .map_err(Error::from)
+                .into_diagnostic()?;
+
+            format
+                .or_else(|| ConfigFormat::from_path(&changelog_config_path))
+                .unwrap_or(ConfigFormat::Toml)
+                .parse(&config)
+                .into_diagnostic()?
+        }
+        None => match load_cargo_metadata_table(repo_workdir_path).into_diagnostic()? {
+            Some(config) => {
+                tracing::debug!("Using configuration from [package.metadata.changelog]");
+                config
+            }
+            None => miette::bail!(Error::ConfigDoesNotExist),
+        },
     };
 
-    let config = std::fs::read_to_string(changelog_config_path)
+    // Only top-level settings are interpolated, so header field definitions (e.g. crawler
+    // commands) are left alone
+    for (_, value) in config.iter_mut() {
+        if let toml::Value::String(s) = value {
+            *s = interpolate_env(s).into_diagnostic()?;
+        }
+    }
+
+    let config: Configuration = toml::Value::Table(config)
+        .try_into()
         .map_err(Error::from)
         .into_diagnostic()?;
+    config.check_key_case().into_diagnostic()?;
+    Ok(config)
+}
 
-    toml::from_str(&config)
+/// Load the `[package.metadata.changelog]` table from the Cargo.toml in the repository, if any
+fn load_cargo_metadata_table(repo_workdir_path: &Path) -> Result<Option<toml::Table>, Error> {
+    let manifest_path = repo_workdir_path.join("Cargo.toml");
+    if !manifest_path.is_file() {
+        return Ok(None);
+    }
+
+    let metadata = cargo_metadata::MetadataCommand::new()
+        .manifest_path(manifest_path)
+        .no_deps()
+        .exec()?;
+
+    metadata
+        .root_package()
+        .and_then(|package| package.metadata.get("changelog"))
+        .map(|table| serde_json::from_value(table.clone()))
+        .transpose()
         .map_err(Error::from)
-        .into_diagnostic()
+}
+
+/// Replace all occurrences of `${VAR}` in a string with the value of the environment variable
+fn interpolate_env(s: &str) -> Result<String, Error> {
+    let mut result = String::with_capacity(s.len());
+    let mut rest = s;
+
+    while let Some(start) = rest.find("${") {
+        let Some(len) = rest[start + 2..].find('}') else {
+            break;
+        };
+
+        let name = &rest[start + 2..start + 2 + len];
+        let value = match std::env::var(name) {
+            Ok(value) => value,
+            Err(std::env::VarError::NotPresent) => return Err(Error::EnvNotSet(name.to_string())),
+            Err(std::env::VarError::NotUnicode(_)) => {
+                return Err(Error::EnvNotUnicode(name.to_string()))
+            }
+        };
+
+        result.push_str(&rest[..start]);
+        result.push_str(&value);
+        rest = &rest[start + 2 + len + 1..];
+    }
+
+    result.push_str(rest);
+    Ok(result)
+}
+
+/// An additional file the changelog is rendered to
+#[derive(Clone, Debug, Eq, PartialEq, getset::Getters, serde::Deserialize, serde::Serialize)]
+pub struct Output {
+    /// The path of the file, relative to the repository root
+    #[getset(get = "pub")]
+    path: PathBuf,
+
+    /// The path of the template, relative to the fragment directory
+    #[getset(get = "pub")]
+    template: PathBuf,
+}
+
+impl std::str::FromStr for Output {
+    type Err = String;
+
+    /// Parse an output from `<path>=<template>`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once('=') {
+            Some((path, template)) if !path.is_empty() && !template.is_empty() => Ok(Output {
+                path: PathBuf::from(path),
+                template: PathBuf::from(template),
+            }),
+            _ => Err(format!("Expected <path>=<template>, got '{s}'")),
+        }
+    }
 }
 
 #[derive(
@@ -144,8 +616,173 @@ pub enum GitSetting {
     Commit,
 }
 
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Layout {
+    /// Fragments are stored in a directory named after their version
+    #[default]
+    Versioned,
+
+    /// Fragments are stored in one directory and carry their version in the "version" header
+    /// field
+    Flat,
+}
+
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PrereleaseHandling {
+    /// Show pre-releases as their own version sections
+    #[default]
+    Separate,
+
+    /// Do not show pre-releases at all
+    Hide,
+
+    /// Show the entries of pre-releases in the section of their final version
+    Fold,
+}
+
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BuildMetadataHandling {
+    /// Versions with different build metadata get their own version sections
+    #[default]
+    Separate,
+
+    /// The entries of all versions that only differ in build metadata are shown in the section of
+    /// the version without build metadata
+    Merge,
+}
+
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EntrySortBy {
+    /// Sort the entries by the path of their fragment file
+    #[default]
+    Path,
+
+    /// Sort the entries by the creation time in the `created_field` header field, with entries
+    /// without creation time last
+    Created,
+}
+
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LineEnding {
+    /// Unix line endings ("\n")
+    #[default]
+    Lf,
+
+    /// Windows line endings ("\r\n")
+    Crlf,
+
+    /// The line endings of the platform cargo-changelog runs on
+    Native,
+}
+
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MissingRequired {
+    /// Fail to add the fragment
+    #[default]
+    Error,
+
+    /// Log a warning and leave the field out
+    Warn,
+
+    /// Log a warning and use a placeholder value, to be filled in later
+    ///
+    /// Fields that cannot hold a string are left out.
+    Placeholder,
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum KeyCase {
+    /// Words separated by underscores, e.g. "breaking_change"
+    Snake,
+
+    /// Words separated by hyphens, e.g. "breaking-change"
+    Kebab,
+}
+
+impl KeyCase {
+    /// Convert `key` to this casing
+    ///
+    /// Hyphens, underscores and spaces separate words, as do uppercase letters following a
+    /// lowercase letter or digit (as in "camelCase").
+    pub fn convert(self, key: &str) -> String {
+        let separator = match self {
+            KeyCase::Snake => '_',
+            KeyCase::Kebab => '-',
+        };
+
+        let mut converted = String::with_capacity(key.len());
+        let mut previous = None::<char>;
+        for c in key.chars() {
+            if c == '-' || c == '_' || c == ' ' {
+                converted.push(separator);
+            } else if c.is_uppercase() {
+                if previous.is_some_and(|p| p.is_lowercase() || p.is_ascii_digit()) {
+                    converted.push(separator);
+                }
+                converted.extend(c.to_lowercase());
+            } else {
+                converted.push(c);
+            }
+            previous = Some(c);
+        }
+        converted
+    }
+
+    /// Whether `key` is already in this casing
+    pub fn matches(self, key: &str) -> bool {
+        self.convert(key) == key
+    }
+}
+
+impl std::fmt::Display for KeyCase {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            KeyCase::Snake => write!(f, "snake_case"),
+            KeyCase::Kebab => write!(f, "kebab-case"),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use std::path::Path;
+
+    use super::{ConfigFormat, KeyCase, Output};
+
+    #[test]
+    fn test_key_case_convert() {
+        assert_eq!(KeyCase::Snake.convert("breaking-change"), "breaking_change");
+        assert_eq!(KeyCase::Snake.convert("breakingChange"), "breaking_change");
+        assert_eq!(KeyCase::Snake.convert("Breaking Change"), "breaking_change");
+        assert_eq!(KeyCase::Kebab.convert("breaking_change"), "breaking-change");
+        assert_eq!(KeyCase::Kebab.convert("issue2Fix"), "issue2-fix");
+        assert_eq!(KeyCase::Kebab.convert("PR"), "pr");
+        assert!(KeyCase::Snake.matches("issue"));
+        assert!(KeyCase::Kebab.matches("issue"));
+        assert!(!KeyCase::Kebab.matches("breaking_change"));
+    }
+
+    #[test]
+    fn test_config_format_from_path() {
+        assert_eq!(
+            ConfigFormat::from_path(Path::new("changelog.toml")),
+            Some(ConfigFormat::Toml)
+        );
+        assert_eq!(
+            ConfigFormat::from_path(Path::new(".changelog.json")),
+            Some(ConfigFormat::Json)
+        );
+        assert_eq!(ConfigFormat::from_path(Path::new("changelog.yaml")), None);
+        assert_eq!(ConfigFormat::from_path(Path::new("changelog")), None);
+    }
+
     #[test]
     fn test_default_config_deserializes_to_configuration() {
         let config = toml::from_str::<super::Configuration>(super::DEFAULT_CONFIG);
@@ -163,6 +800,41 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_interpolate_env_replaces_variables() {
+        std::env::set_var("CARGO_CHANGELOG_TEST_INTERPOLATE", "docs");
+        let s = super::interpolate_env("${CARGO_CHANGELOG_TEST_INTERPOLATE}/CHANGELOG.md");
+        assert_eq!(s.unwrap(), "docs/CHANGELOG.md");
+    }
+
+    #[test]
+    fn test_interpolate_env_without_variables() {
+        let s = super::interpolate_env("CHANGELOG.md {{version}} $HOME ${unterminated");
+        assert_eq!(s.unwrap(), "CHANGELOG.md {{version}} $HOME ${unterminated");
+    }
+
+    #[test]
+    fn test_interpolate_env_missing_variable_errors() {
+        let s = super::interpolate_env("${CARGO_CHANGELOG_TEST_INTERPOLATE_MISSING}");
+        assert!(
+            std::matches!(s, Err(crate::error::Error::EnvNotSet(ref name)) if name == "CARGO_CHANGELOG_TEST_INTERPOLATE_MISSING"),
+            "Expected EnvNotSet error: {s:?}"
+        );
+    }
+
+    #[test]
+    fn test_output_from_str() {
+        use std::str::FromStr;
+
+        let output = Output::from_str("CHANGES.txt=changes.txt").unwrap();
+        assert_eq!(output.path(), Path::new("CHANGES.txt"));
+        assert_eq!(output.template(), Path::new("changes.txt"));
+
+        for s in ["CHANGES.txt", "=changes.txt", "CHANGES.txt="] {
+            assert!(Output::from_str(s).is_err(), "Parsed '{s}'");
+        }
+    }
+
     #[test]
     fn test_default_config_has_default_template_path() {
         let config: super::Configuration = toml::from_str(super::DEFAULT_CONFIG).unwrap();
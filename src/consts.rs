@@ -1,5 +1,64 @@
 pub const UNRELEASED_DIR_NAME: &str = "unreleased";
 
-pub const INTERNAL_TEMPLATE_NAME: &str = "template";
+/// The directory in the fragment directory where all fragments are stored with the "flat" layout
+pub const FLAT_DIR_NAME: &str = "fragments";
+
+/// The header field holding the version of a fragment with the "flat" layout
+pub const VERSION_HEADER_FIELD: &str = "version";
+
+/// The header field listing all versions of a fragment, e.g. for changes that were backported
+pub const VERSIONS_HEADER_FIELD: &str = "versions";
+
+/// The header field holding the type of a fragment, e.g. "Bugfix"
+pub const TYPE_HEADER_FIELD: &str = "type";
+
+/// The header field holding the author of a fragment, used by `--author`
+pub const AUTHOR_HEADER_FIELD: &str = "author";
+
+/// The header field holding the audience of a fragment (e.g. "internal"), used by `--audience`
+pub const AUDIENCE_HEADER_FIELD: &str = "audience";
+
+/// The default header field holding the creation time of a fragment
+pub const CREATED_HEADER_FIELD: &str = "created";
+
+/// The default header field that leaves a fragment out of the changelog, e.g. for internal changes
+pub const SKIP_HEADER_FIELD: &str = "skip";
+
+/// The file in a version directory that marks the version as yanked
+pub const YANKED_MARKER_FILE_NAME: &str = ".yanked";
+
+/// The file in the fragment directory listing fragments to leave out, in gitignore syntax
+pub const IGNORE_FILE_NAME: &str = ".changelogignore";
+
+/// The environment variable overriding the path of the changelog file, e.g. for CI matrices
+pub const CHANGELOG_OUTPUT_ENV_VAR: &str = "CARGO_CHANGELOG_OUTPUT";
+
+/// The value `add` writes for missing required header fields with `missing_required = "placeholder"`
+pub const MISSING_REQUIRED_PLACEHOLDER: &str = "TODO";
+
+/// The commit of fragments that are not committed yet, with `resolve_commits`
+pub const UNCOMMITTED: &str = "uncommitted";
+
+/// The name the changelog template is registered with in handlebars
+///
+/// The name is namespaced, so that it does not clash with templates and partials registered by
+/// the user (e.g. a partial called "template").
+pub const INTERNAL_TEMPLATE_NAME: &str = "cargo-changelog::template";
 
 pub const DEFAULT_TEMPLATE: &str = include_str!("../assets/default_template.handlebars.md");
+
+/// The template for rendering a single version with `--emit-version-only`
+pub const VERSION_TEMPLATE: &str = include_str!("../assets/version_template.handlebars.md");
+
+/// The default template with a table of contents of all versions at the top
+pub const TOC_TEMPLATE: &str = include_str!("../assets/toc_template.handlebars.md");
+
+/// A template following <https://keepachangelog.com>, used by `--format keepachangelog`
+pub const KEEPACHANGELOG_TEMPLATE: &str =
+    include_str!("../assets/keepachangelog_template.handlebars.md");
+
+/// A template with a plain list of subjects per version, used by `--format simple`
+pub const SIMPLE_TEMPLATE: &str = include_str!("../assets/simple_template.handlebars.md");
+
+/// A template in the style of GitHub release notes, used by `--format github`
+pub const GITHUB_TEMPLATE: &str = include_str!("../assets/github_template.handlebars.md");
@@ -0,0 +1,101 @@
+//! The database of released fragments, compacted into a single file by `compact`
+//!
+//! Each line of the database is a JSON object with the version and the fragment.
+
+use std::io::{BufRead, Write};
+use std::path::Path;
+
+use crate::error::Error;
+use crate::fragment::Fragment;
+
+#[derive(serde::Deserialize, serde::Serialize)]
+struct Record<'a> {
+    version: String,
+    fragment: std::borrow::Cow<'a, Fragment>,
+}
+
+/// Load all fragments with their versions from the database
+///
+/// A database that does not exist (yet) is empty.
+pub fn load(path: &Path) -> Result<Vec<(semver::Version, Fragment)>, Error> {
+    let file = match std::fs::File::open(path) {
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(Error::from(e)),
+        Ok(file) => file,
+    };
+
+    let mut fragments = Vec::new();
+    for (idx, line) in std::io::BufReader::new(file).lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let database_error = |source| Error::Database {
+            path: path.to_path_buf(),
+            line: idx + 1,
+            source,
+        };
+        let record = serde_json::from_str::<Record>(&line).map_err(database_error)?;
+        let version = semver::Version::parse(&record.version)?;
+        fragments.push((version, record.fragment.into_owned()));
+    }
+    Ok(fragments)
+}
+
+/// Append the fragments with their versions to the database, creating it if necessary
+pub fn append<'a>(
+    path: &Path,
+    fragments: impl IntoIterator<Item = (&'a semver::Version, &'a Fragment)>,
+) -> Result<(), Error> {
+    // Serialize first, so that the database is not left with only some of the fragments
+    let mut lines = String::new();
+    for (version, fragment) in fragments {
+        let record = Record {
+            version: version.to_string(),
+            fragment: std::borrow::Cow::Borrowed(fragment),
+        };
+        lines.push_str(&serde_json::to_string(&record)?);
+        lines.push('\n');
+    }
+
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?;
+    file.write_all(lines.as_bytes())?;
+    file.sync_all()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use indexmap::IndexMap;
+
+    use super::*;
+    use crate::fragment::FragmentData;
+
+    #[test]
+    fn test_append_and_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("released.jsonl");
+        assert!(load(&path).unwrap().is_empty());
+
+        let version = semver::Version::new(0, 1, 0);
+        let fragment = Fragment::new(
+            IndexMap::from([
+                ("issue".to_string(), FragmentData::Int(1)),
+                ("ratio".to_string(), FragmentData::Float(1.0)),
+            ]),
+            "Text\n".to_string(),
+        );
+        append(&path, [(&version, &fragment)]).unwrap();
+        append(&path, [(&version, &fragment)]).unwrap();
+
+        let loaded = load(&path).unwrap();
+        assert_eq!(
+            loaded,
+            vec![(version.clone(), fragment.clone()), (version, fragment)]
+        );
+    }
+}
@@ -1,4 +1,4 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 #[derive(Debug, thiserror::Error, miette::Diagnostic)]
 pub enum Error {
@@ -14,6 +14,24 @@ pub enum Error {
     #[error("Repository dirty")]
     GitRepoDirty,
 
+    #[error("Repository has staged changes unrelated to the changelog: {}", .0.join(", "))]
+    GitUnrelatedStagedChanges(Vec<String>),
+
+    #[error("No released version found")]
+    NoReleasedVersion,
+
+    #[error("No changelog fragments found for version {0}")]
+    VersionNotFound(String),
+
+    #[error("Not a valid post release hook command: '{0}'")]
+    NoValidHookCommand(String),
+
+    #[error("Post release hook '{0}' exited unsuccessfully: {1}")]
+    HookNoSuccess(String, std::process::ExitStatus),
+
+    #[error("Tag '{0}' already exists")]
+    GitTagExists(String),
+
     #[error("TOML deserialization error")]
     Toml(#[from] toml::de::Error),
 
@@ -36,14 +54,54 @@ pub enum Error {
     NoWorkTree,
 
     #[error(
-        "Configuration file does not exist, tried {:?}",
+        "Configuration file does not exist, tried {:?} and [package.metadata.changelog] in Cargo.toml",
         crate::config::CONFIG_FILE_NAMES
     )]
     ConfigDoesNotExist,
 
+    #[error("Template file does not exist: {}", .0.display())]
+    TemplateDoesNotExist(PathBuf),
+
+    #[error("Fragment directory does not exist: {}", .0.display())]
+    FragmentDirDoesNotExist(PathBuf),
+
+    #[error("Version directory does not exist and --create-dirs=false was given: {}", .0.display())]
+    VersionDirDoesNotExist(PathBuf),
+
     #[error("Not a file: {0}")]
     NotAFile(PathBuf),
 
+    #[error("Not in the fragment directory: {}", .0.display())]
+    NotInFragmentDir(PathBuf),
+
+    #[error("Not a changelog fragment: {}", .0.display())]
+    NotAFragment(PathBuf),
+
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Interactive(#[from] InteractiveError),
+
+    #[error("Type '{0}' already exists")]
+    TypeExists(String),
+
+    #[error("The configuration has no \"type\" header field with a list of possible values")]
+    NoTypeList,
+
+    #[error("Header field '{field}' is not {key_case}, as configured with key_case")]
+    HeaderFieldKeyCase {
+        field: String,
+        key_case: crate::config::KeyCase,
+    },
+
+    #[error("The configuration can only be edited in a TOML configuration file")]
+    ConfigNotEditable,
+
+    #[error("TOML error")]
+    TomlEdit(#[from] toml_edit::TomlError),
+
+    #[error("Fragment already exists: {}", .0.display())]
+    FragmentExists(PathBuf),
+
     #[error("No version found in Cargo.toml, that should never happen...")]
     NoVersionInCargoToml,
 
@@ -58,15 +116,26 @@ pub enum Error {
     #[error("Environment variable '{0}' is not unicode")]
     EnvNotUnicode(String),
 
+    #[error("Environment variable '{0}' is not set")]
+    EnvNotSet(String),
+
     #[error(transparent)]
     SemVer(#[from] semver::Error),
 
     #[error("Fragment Error: {}", .1.display())]
-    Fragment(#[source] FragmentError, PathBuf),
+    Fragment(
+        #[source]
+        #[diagnostic_source]
+        FragmentError,
+        PathBuf,
+    ),
 
     #[error(transparent)]
     Json(#[from] serde_json::Error),
 
+    #[error("JSON deserialization error")]
+    ConfigJson(#[source] serde_json::Error),
+
     #[error("Version error")]
     Version(#[from] VersionError),
 
@@ -75,6 +144,31 @@ pub enum Error {
 
     #[error("Verification failed")]
     Verification(#[related] Vec<VerificationError>),
+
+    #[error("Invalid entry in the fragment database {} in line {line}", .path.display())]
+    Database {
+        path: PathBuf,
+        line: usize,
+        #[source]
+        source: serde_json::Error,
+    },
+
+    #[error("--author only renders part of the changelog, use it with --check, --emit-version-only or an explicit changelog path")]
+    AuthorWithoutOutput,
+
+    #[error("No database configured, set `database` in the configuration to compact fragments")]
+    NoDatabase,
+
+    #[error("Failed to write {} of the changelog files", .0.len())]
+    Outputs(#[related] Vec<OutputError>),
+
+    #[cfg(feature = "github")]
+    #[error("Error fetching pull request from GitHub")]
+    Github(#[from] GithubError),
+
+    #[cfg(feature = "github")]
+    #[error("Cannot find the GitHub repository, neither repository_url is configured nor a remote named 'origin' exists")]
+    NoGithubRepository,
 }
 
 #[derive(Debug, thiserror::Error, miette::Diagnostic)]
@@ -82,8 +176,19 @@ pub enum FragmentError {
     #[error("IO")]
     Io(#[from] std::io::Error),
 
-    #[error("Expected header seperator: '+++', found: '{0}'")]
-    ExpectedSeperator(String),
+    #[error("Expected header seperator: '+++', found: '{found}'")]
+    ExpectedSeperator {
+        found: String,
+        #[source_code]
+        src: String,
+        #[label("expected '+++'")]
+        span: miette::SourceSpan,
+        #[help]
+        help: Option<String>,
+    },
+
+    #[error("Fragment is not valid UTF-8, invalid byte at offset {0}")]
+    NotUtf8(usize),
 
     #[error("Header seperator '+++' missing")]
     HeaderSeperatorMissing,
@@ -92,7 +197,13 @@ pub enum FragmentError {
     TomlSer(#[from] toml::ser::Error),
 
     #[error("TOML deserialization error")]
-    TomlDe(#[from] toml::de::Error),
+    TomlDe {
+        #[source_code]
+        src: String,
+        #[label("{reason}")]
+        span: Option<miette::SourceSpan>,
+        reason: String,
+    },
 
     #[error("Type Error: Expected {exp}, got {recv} for field {field_name}")]
     DataType {
@@ -107,6 +218,30 @@ pub enum FragmentError {
     #[error("Required value '{}', but value is missing", .0)]
     RequiredValueMissing(String),
 
+    #[error("Field '{}' is not a finite number", .0)]
+    NonFiniteFloat(String),
+
+    #[error("Field '{0}' has maps nested deeper than {1} levels")]
+    MapTooDeep(String, usize),
+
+    #[error("Header keys '{first}' and '{second}' are both '{converted}' in {key_case}")]
+    KeyCollision {
+        first: String,
+        second: String,
+        converted: String,
+        key_case: crate::config::KeyCase,
+    },
+
+    #[error("Failed to render body template")]
+    BodyTemplate(#[source] Box<handlebars::RenderError>),
+
+    #[error("Failed to render default value template for field '{field_name}'")]
+    DefaultTemplate {
+        field_name: String,
+        #[source]
+        source: Box<handlebars::RenderError>,
+    },
+
     #[error("Not a valid command: '{}'", .0)]
     NoValidCommand(String),
 
@@ -121,6 +256,26 @@ pub enum FragmentError {
 pub enum VersionError {
     #[error("UTF8 Error with path: {}", .0.display())]
     Utf8(PathBuf),
+
+    #[error("Fragment {} is in a version directory, but the configured layout is 'flat'", .0.display())]
+    VersionDirInFlatLayout(PathBuf),
+
+    #[error("Version header field in {} is not a valid version", .0.display())]
+    InvalidVersionHeader(PathBuf),
+
+    #[error("Versions header field in {} is not a non-empty list of valid versions", .0.display())]
+    InvalidVersionsHeader(PathBuf),
+}
+
+impl VersionError {
+    pub fn path(&self) -> &Path {
+        match self {
+            VersionError::Utf8(path)
+            | VersionError::VersionDirInFlatLayout(path)
+            | VersionError::InvalidVersionHeader(path)
+            | VersionError::InvalidVersionsHeader(path) => path,
+        }
+    }
 }
 
 #[derive(Debug, thiserror::Error, miette::Diagnostic)]
@@ -132,23 +287,155 @@ pub enum TextProviderError {
     Utf8(#[from] std::string::FromUtf8Error),
 }
 
+#[cfg(feature = "github")]
+#[derive(Debug, thiserror::Error, miette::Diagnostic)]
+pub enum GithubError {
+    #[error("curl was not found, it is needed to query the GitHub API")]
+    #[diagnostic(help("Install curl or make sure it is in the PATH"))]
+    CurlNotFound,
+
+    #[error("Could not run curl")]
+    Curl(#[source] std::io::Error),
+
+    #[error("Request failed, are you offline? {0}")]
+    Request(String),
+
+    #[error("Not a GitHub repository URL: '{0}'")]
+    NotGithub(String),
+
+    #[error("Not authorized to access {0}, set GITHUB_TOKEN or GH_TOKEN to a valid token")]
+    Unauthorized(String),
+
+    #[error("Pull request {0} not found, for private repositories set GITHUB_TOKEN or GH_TOKEN")]
+    NotFound(u64),
+
+    #[error("Unexpected HTTP status {0} from {1}")]
+    Status(String, String),
+
+    #[error("Cannot parse the pull request returned by GitHub")]
+    Json(#[source] serde_json::Error),
+}
+
+/// An error writing one of the files the changelog is rendered to
+#[derive(Debug, thiserror::Error, miette::Diagnostic)]
+#[error("Failed to write {}", .path.display())]
+pub struct OutputError {
+    pub path: PathBuf,
+
+    #[source]
+    #[diagnostic_source]
+    pub source: Error,
+}
+
 #[derive(Debug, thiserror::Error, miette::Diagnostic)]
 pub enum VerificationError {
     #[error("Version error")]
     Version(#[from] VersionError),
 
-    #[error("Error while parsing fragment {0}")]
-    FragmentParsing(PathBuf, #[source] FragmentError),
+    #[error("Error while parsing fragment")]
+    FragmentParsing(
+        #[source]
+        #[diagnostic_source]
+        Error,
+    ),
+
+    #[error("Fragment {} is neither in a valid semver version directory nor in the unreleased directory", .0.display())]
+    NoVersionDirectory(PathBuf),
+
+    #[error("Version {version} has {count} fragments, more than the maximum of {max}")]
+    TooManyFragments {
+        version: String,
+        count: usize,
+        max: usize,
+    },
+
+    #[error("Fragment {} is a breaking change, but version {version} is a patch release", .path.display())]
+    BreakingChangeInPatchRelease { path: PathBuf, version: String },
+
+    #[error("Fragment {} has a text of {length} characters, more than the maximum of {max}, consider trimming it or moving details to the documentation", .path.display())]
+    BodyTooLong {
+        path: PathBuf,
+        length: usize,
+        max: usize,
+    },
+
+    #[error("Fragment {} contains a merge conflict marker in line {line}", .path.display())]
+    ConflictMarker { path: PathBuf, line: usize },
+
+    #[error("Fragment {} has type '{fragment_type}', which requires a text, but its text is empty", .path.display())]
+    BodyRequired {
+        path: PathBuf,
+        fragment_type: String,
+    },
+
+    #[error("Fragment {} has header key '{key}', which is not {key_case}", .path.display())]
+    KeyCase {
+        path: PathBuf,
+        key: String,
+        key_case: crate::config::KeyCase,
+    },
+
+    #[error("Template {} cannot be rendered", .path.display())]
+    TemplateRender {
+        path: PathBuf,
+
+        #[source]
+        source: Box<Error>,
+    },
 
     #[error("Error while walking directory")]
     WalkDir(#[from] walkdir::Error),
 }
 
+impl VerificationError {
+    /// The path of the file this error is about, if any
+    pub fn path(&self) -> Option<&Path> {
+        match self {
+            VerificationError::Version(e) => Some(e.path()),
+            VerificationError::FragmentParsing(e) => match e {
+                Error::Fragment(_, path) => Some(path),
+                _ => None,
+            },
+            VerificationError::NoVersionDirectory(path) => Some(path),
+            VerificationError::TooManyFragments { .. } => None,
+            VerificationError::BreakingChangeInPatchRelease { path, .. } => Some(path),
+            VerificationError::BodyTooLong { path, .. } => Some(path),
+            VerificationError::ConflictMarker { path, .. } => Some(path),
+            VerificationError::BodyRequired { path, .. } => Some(path),
+            VerificationError::KeyCase { path, .. } => Some(path),
+            VerificationError::TemplateRender { path, .. } => Some(path),
+            VerificationError::WalkDir(e) => e.path(),
+        }
+    }
+
+    /// A short, machine-readable name for the kind of this error
+    pub fn kind(&self) -> &'static str {
+        match self {
+            VerificationError::Version(_) => "version",
+            VerificationError::FragmentParsing(_) => "fragment-parsing",
+            VerificationError::NoVersionDirectory(_) => "no-version-directory",
+            VerificationError::TooManyFragments { .. } => "too-many-fragments",
+            VerificationError::BreakingChangeInPatchRelease { .. } => {
+                "breaking-change-in-patch-release"
+            }
+            VerificationError::BodyTooLong { .. } => "body-too-long",
+            VerificationError::ConflictMarker { .. } => "conflict-marker",
+            VerificationError::BodyRequired { .. } => "body-required",
+            VerificationError::KeyCase { .. } => "key-case",
+            VerificationError::TemplateRender { .. } => "template-render",
+            VerificationError::WalkDir(_) => "walk-dir",
+        }
+    }
+}
+
 #[derive(Debug, thiserror::Error, miette::Diagnostic)]
 pub enum InteractiveError {
     #[error("User interrupted interactive session")]
     Interrupted,
 
+    #[error("User input is required, but disabled with --no-interactive")]
+    Disabled,
+
     #[error("IO Error")]
     Io(#[from] std::io::Error),
 
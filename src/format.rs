@@ -2,3 +2,20 @@
 pub enum Format {
     Toml,
 }
+
+impl Format {
+    /// The format of a fragment with this header separator
+    pub fn from_separator(separator: &str) -> Option<Self> {
+        match separator {
+            "+++" => Some(Format::Toml),
+            _ => None,
+        }
+    }
+
+    /// The line before and after the header of a fragment in this format
+    pub fn separator(self) -> &'static str {
+        match self {
+            Format::Toml => "+++",
+        }
+    }
+}
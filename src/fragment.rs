@@ -1,26 +1,53 @@
 use std::collections::HashMap;
 use std::io::Read;
 use std::io::Write;
+use std::path::Path;
 use std::path::PathBuf;
 
+use indexmap::IndexMap;
+
+use crate::config::KeyCase;
+use crate::error::Error;
 use crate::error::FragmentError;
 use crate::format::Format;
 
+/// How deep maps in the header of a fragment can be nested
+pub const MAX_MAP_DEPTH: usize = 8;
+
+/// The UTF-8 encoded byte order mark, which is skipped at the start of fragment files
+const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+
 #[derive(
-    Clone, Debug, getset::Getters, getset::MutGetters, serde::Deserialize, serde::Serialize,
+    Clone,
+    Debug,
+    PartialEq,
+    getset::Getters,
+    getset::MutGetters,
+    serde::Deserialize,
+    serde::Serialize,
 )]
 pub struct Fragment {
     #[getset(get = "pub", get_mut = "pub")]
-    header: HashMap<String, FragmentData>,
+    header: IndexMap<String, FragmentData>,
     #[getset(get = "pub")]
     text: String,
+    /// The path of the file this fragment was loaded from, relative to the repository root
+    #[getset(get = "pub")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    source_path: Option<PathBuf>,
+    /// The short hash of the commit that added the fragment file, with `resolve_commits`
+    #[getset(get = "pub")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    commit: Option<String>,
 }
 
 impl Fragment {
     pub fn empty() -> Self {
         Fragment {
-            header: HashMap::new(),
+            header: IndexMap::new(),
             text: String::new(),
+            source_path: None,
+            commit: None,
         }
     }
 
@@ -28,47 +55,125 @@ impl Fragment {
         self.text = text;
     }
 
+    pub fn set_source_path(&mut self, source_path: PathBuf) {
+        self.source_path = Some(source_path);
+    }
+
+    pub fn set_commit(&mut self, commit: String) {
+        self.commit = Some(commit);
+    }
+
+    /// Read the fragment from the file at `path`
+    ///
+    /// Errors, including errors opening the file, carry the path of the file.
+    pub fn from_path(path: &Path) -> Result<Self, Error> {
+        std::fs::File::open(path)
+            .map(std::io::BufReader::new)
+            .map_err(FragmentError::from)
+            .and_then(|mut reader| Self::from_reader(&mut reader))
+            .map_err(|e| Error::Fragment(e, path.to_path_buf()))
+    }
+
     pub fn from_reader<R: Read>(reader: &mut R) -> Result<Self, FragmentError> {
-        let mut buf = String::new();
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes)?;
 
-        reader.read_to_string(&mut buf)?;
+        // Some editors on Windows save files with a byte order mark, which is not part of the
+        // header seperator
+        let bytes = bytes.strip_prefix(UTF8_BOM).unwrap_or(&bytes);
+        let buf = std::str::from_utf8(bytes)
+            .map_err(|e| FragmentError::NotUtf8(e.valid_up_to()))?
+            .to_string();
 
-        let mut lines = buf.lines();
-        let format = if let Some(header_sep) = lines.next() {
-            if header_sep == "+++" {
-                Format::Toml
-            } else {
-                return Err(FragmentError::ExpectedSeperator(header_sep.to_string()));
-            }
-        } else {
-            return Err(FragmentError::HeaderSeperatorMissing);
+        // Keep the offset of each line, so errors can point at the offending part of the file
+        let mut offset = 0;
+        let lines = buf
+            .split_inclusive('\n')
+            .map(|line| {
+                let start = offset;
+                offset += line.len();
+                let line = line.strip_suffix('\n').unwrap_or(line);
+                (start, line.strip_suffix('\r').unwrap_or(line))
+            })
+            .collect::<Vec<_>>();
+
+        let format = match lines.first() {
+            Some((start, header_sep)) => Format::from_separator(header_sep).ok_or_else(|| {
+                FragmentError::ExpectedSeperator {
+                    found: header_sep.to_string(),
+                    src: buf.clone(),
+                    span: (*start, header_sep.len()).into(),
+                    help: (*header_sep == "---").then(|| {
+                        "YAML headers are not supported, write the header in TOML between '+++' lines"
+                            .to_string()
+                    }),
+                }
+            })?,
+            None => return Err(FragmentError::HeaderSeperatorMissing),
         };
 
-        let header = {
-            let header = lines
-                .by_ref()
-                .take_while(|line| *line != "+++")
-                .collect::<Vec<_>>();
+        let header_start = lines.get(1).map_or(buf.len(), |(start, _)| *start);
+        let (header_end, text_lines) = match lines
+            .iter()
+            .skip(1)
+            .position(|(_, l)| *l == format.separator())
+        {
+            Some(idx) => (lines[idx + 1].0, &lines[idx + 2..]),
+            None => (buf.len(), &[][..]),
+        };
 
-            match format {
-                Format::Toml => {
-                    toml::from_str::<HashMap<String, FragmentData>>(&header.join("\n"))?
-                }
+        let header = match format {
+            Format::Toml => {
+                toml::from_str::<IndexMap<String, FragmentData>>(&buf[header_start..header_end])
+                    .map_err(|e| FragmentError::TomlDe {
+                        span: e
+                            .span()
+                            .map(|span| (header_start + span.start, span.len()).into()),
+                        reason: e.message().to_string(),
+                        src: buf.clone(),
+                    })?
             }
         };
 
-        let text = lines.collect::<Vec<_>>().join("\n");
+        if let Some((key, _)) = header
+            .iter()
+            .find(|(_, value)| value.depth() > MAX_MAP_DEPTH)
+        {
+            return Err(FragmentError::MapTooDeep(key.to_string(), MAX_MAP_DEPTH));
+        }
+
+        if let Some((key, _)) = header.iter().find(|(_, value)| !value.is_finite()) {
+            return Err(FragmentError::NonFiniteFloat(key.to_string()));
+        }
 
-        Ok(Fragment { header, text })
+        let text = text_lines
+            .iter()
+            .map(|(_, line)| *line)
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        Ok(Fragment {
+            header,
+            text,
+            source_path: None,
+            commit: None,
+        })
     }
 
-    pub fn write_to<W: Write>(&self, writer: &mut W, format: Format) -> Result<(), FragmentError> {
-        let (seperator, header) = match format {
+    /// Write the fragment in its on-disk form, the inverse of [`Fragment::from_reader`]
+    pub fn to_writer<W: Write>(&self, writer: &mut W, format: Format) -> Result<(), FragmentError> {
+        let seperator = format.separator();
+        let header = match format {
             Format::Toml => {
-                let header = toml::to_string(&self.header)?.trim_end().to_string();
-                ("+++", header)
+                let header = toml::to_string(&self.header)?;
+                if header.lines().any(|line| line == seperator) {
+                    escape_separator_lines(&header, seperator)
+                } else {
+                    header
+                }
             }
         };
+        let header = header.trim_end();
 
         writeln!(writer, "{seperator}")?;
         writeln!(writer, "{header}")?;
@@ -77,18 +182,194 @@ impl Fragment {
         Ok(())
     }
 
+    /// The fragment in its on-disk form, see [`Fragment::to_writer`]
+    pub fn to_string(&self, format: Format) -> Result<String, FragmentError> {
+        let mut buf = Vec::new();
+        self.to_writer(&mut buf, format)?;
+        Ok(String::from_utf8(buf).expect("TOML and the text are valid UTF-8"))
+    }
+
+    /// A short hash of the header and the text of the fragment
+    ///
+    /// Fragments with the same header values and text (ignoring surrounding whitespace) have the
+    /// same hash. The `ignored_fields` are left out, e.g. fields that differ between otherwise
+    /// identical fragments, like the creation time.
+    pub fn content_hash(&self, ignored_fields: &[&str]) -> String {
+        use sha2::Digest;
+
+        let mut hasher = sha2::Sha256::new();
+        let mut header = self
+            .header
+            .iter()
+            .filter(|(key, _)| !ignored_fields.contains(&key.as_str()))
+            .collect::<Vec<_>>();
+        header.sort_by_key(|(key, _)| *key);
+        for (key, value) in header {
+            hasher.update(format!("{key}={}\n", value.display()));
+        }
+        hasher.update("\n");
+        hasher.update(self.text.trim());
+
+        hasher
+            .finalize()
+            .iter()
+            .take(6)
+            .fold(String::new(), |mut hash, byte| {
+                use std::fmt::Write;
+                let _ = write!(hash, "{byte:02x}");
+                hash
+            })
+    }
+
+    /// Convert header values to the type of their header field, where this is unambiguous
+    pub fn coerce_header(&mut self, header_fields: &IndexMap<String, FragmentDataDesc>) {
+        for (key, value) in self.header.iter_mut() {
+            if let Some(desc) = header_fields.get(key) {
+                if let Some(coerced) = desc.fragment_type().coerce(value) {
+                    tracing::debug!("Coerced field '{key}' from {value:?} to {coerced:?}");
+                    *value = coerced;
+                }
+            }
+        }
+    }
+
+    /// Replace header values that match a possible value of their header field ignoring case
+    /// with that possible value
+    pub fn normalize_enum_case(&mut self, header_fields: &IndexMap<String, FragmentDataDesc>) {
+        for (key, value) in self.header.iter_mut() {
+            if let Some(desc) = header_fields.get(key) {
+                if let Some(normalized) = desc.fragment_type().normalize_case(value) {
+                    tracing::debug!("Normalized field '{key}' from {value:?} to {normalized:?}");
+                    *value = normalized;
+                }
+            }
+        }
+    }
+
+    /// Rename the header keys to `key_case`, keeping their order
+    ///
+    /// Fails if two keys are the same in `key_case`, e.g. "breaking-change" and
+    /// "breaking_change".
+    pub fn normalize_key_case(&mut self, key_case: KeyCase) -> Result<(), FragmentError> {
+        let mut header = IndexMap::with_capacity(self.header.len());
+        let mut original_keys = HashMap::new();
+        for (key, value) in self.header.iter() {
+            let converted = key_case.convert(key);
+            if let Some(first) = original_keys.insert(converted.clone(), key) {
+                return Err(FragmentError::KeyCollision {
+                    first: first.to_string(),
+                    second: key.to_string(),
+                    converted,
+                    key_case,
+                });
+            }
+            if converted != *key {
+                tracing::debug!("Renamed field '{key}' to '{converted}'");
+            }
+            header.insert(converted, value.clone());
+        }
+
+        self.header = header;
+        Ok(())
+    }
+
+    /// Order the header fields as they are declared in the configuration
+    ///
+    /// Fields that are not declared keep their order, after all declared fields.
+    pub fn sort_header(&mut self, header_fields: &IndexMap<String, FragmentDataDesc>) {
+        let position = |key: &String| header_fields.get_index_of(key).unwrap_or(usize::MAX);
+        self.header
+            .sort_by(|key_a, _, key_b, _| position(key_a).cmp(&position(key_b)));
+    }
+
+    /// Check that all header values have the type of their header field
+    pub fn check_header_types(
+        &self,
+        header_fields: &IndexMap<String, FragmentDataDesc>,
+    ) -> Result<(), FragmentError> {
+        for (key, value) in self.header.iter() {
+            if let Some(desc) = header_fields.get(key) {
+                if !desc.fragment_type().matches(value) {
+                    return Err(FragmentError::DataType {
+                        exp: desc.fragment_type().type_name(),
+                        recv: value.type_name().to_string(),
+                        field_name: key.to_string(),
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+
     #[cfg(test)]
-    pub fn new(header: HashMap<String, FragmentData>, text: String) -> Self {
-        Self { header, text }
+    pub fn new(header: IndexMap<String, FragmentData>, text: String) -> Self {
+        Self {
+            header,
+            text,
+            source_path: None,
+            commit: None,
+        }
     }
 }
 
-#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
+/// Write strings with a line that is the header separator as single-line strings
+///
+/// TOML writes strings with newlines as multi-line strings, which would end the header early if
+/// one of their lines is the separator.
+fn escape_separator_lines(header: &str, separator: &str) -> String {
+    fn escape_value(value: &mut toml_edit::Value, separator: &str) {
+        match value {
+            toml_edit::Value::String(s) if s.value().lines().any(|line| line == separator) => {
+                // JSON string escapes are valid in TOML basic strings
+                let escaped = serde_json::to_string(s.value())
+                    .expect("Strings can be serialized")
+                    .parse::<toml_edit::Value>()
+                    .expect("Escaped string is a valid TOML value");
+                let decor = s.decor().clone();
+                *value = escaped;
+                *value.decor_mut() = decor;
+            }
+            toml_edit::Value::Array(array) => {
+                array.iter_mut().for_each(|v| escape_value(v, separator))
+            }
+            toml_edit::Value::InlineTable(table) => table
+                .iter_mut()
+                .for_each(|(_, v)| escape_value(v, separator)),
+            _ => {}
+        }
+    }
+
+    fn escape_item(item: &mut toml_edit::Item, separator: &str) {
+        match item {
+            toml_edit::Item::Value(value) => escape_value(value, separator),
+            toml_edit::Item::Table(table) => table
+                .iter_mut()
+                .for_each(|(_, item)| escape_item(item, separator)),
+            toml_edit::Item::ArrayOfTables(tables) => tables.iter_mut().for_each(|table| {
+                table
+                    .iter_mut()
+                    .for_each(|(_, item)| escape_item(item, separator))
+            }),
+            toml_edit::Item::None => {}
+        }
+    }
+
+    let mut document = header
+        .parse::<toml_edit::DocumentMut>()
+        .expect("Serialized header is valid TOML");
+    escape_item(document.as_item_mut(), separator);
+    document.to_string()
+}
+
+#[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
 #[serde(untagged)]
 pub enum FragmentData {
     Bool(bool),
-    Int(u64),
+    Int(i64),
+    Float(f64),
     Str(String),
+    List(Vec<FragmentData>),
+    Map(IndexMap<String, FragmentData>),
 }
 
 impl FragmentData {
@@ -100,7 +381,45 @@ impl FragmentData {
         match self {
             FragmentData::Bool(_) => "bool",
             FragmentData::Int(_) => "int",
+            FragmentData::Float(_) => "float",
             FragmentData::Str(_) => "string",
+            FragmentData::List(_) => "list",
+            FragmentData::Map(_) => "map",
+        }
+    }
+
+    /// Whether this is not a NaN or infinite float, and does not contain one
+    pub fn is_finite(&self) -> bool {
+        match self {
+            FragmentData::Float(f) => f.is_finite(),
+            FragmentData::List(list) => list.iter().all(FragmentData::is_finite),
+            FragmentData::Map(map) => map.values().all(FragmentData::is_finite),
+            _ => true,
+        }
+    }
+
+    /// How deep maps are nested in this value, 0 for values that are not maps
+    ///
+    /// Stops counting after [`MAX_MAP_DEPTH`], so that arbitrarily deep values are not traversed.
+    pub fn depth(&self) -> usize {
+        self.depth_bounded(0)
+    }
+
+    fn depth_bounded(&self, current: usize) -> usize {
+        match self {
+            FragmentData::Map(_) if current > MAX_MAP_DEPTH => current,
+            FragmentData::Map(map) => map
+                .values()
+                .map(|value| value.depth_bounded(current + 1))
+                .max()
+                .unwrap_or(current + 1),
+            // Lists do not add a level, but the maps in them do
+            FragmentData::List(list) => list
+                .iter()
+                .map(|value| value.depth_bounded(current))
+                .max()
+                .unwrap_or(current),
+            _ => current,
         }
     }
 
@@ -113,12 +432,26 @@ impl FragmentData {
             Ok(FragmentData::Bool(true))
         } else if s == "false" {
             Ok(FragmentData::Bool(false))
-        } else if let Ok(u) = u64::from_str(s) {
-            Ok(FragmentData::Int(u))
+        } else if let Ok(i) = i64::from_str(s) {
+            Ok(FragmentData::Int(i))
+        } else if let Some(f) = f64::from_str(s).ok().filter(|f| f.is_finite()) {
+            Ok(FragmentData::Float(f))
         } else {
             Ok(FragmentData::Str(s.to_string()))
         }
     }
+
+    /// Parse `s` as a value for a header field of type `ty`
+    ///
+    /// Values for string fields are taken as they are, so that e.g. "1.5" stays a string.
+    pub fn parse_as(s: &str, ty: &FragmentDataType) -> Result<Self, FragmentError> {
+        match ty {
+            FragmentDataType::Ty(FragmentDataTypeDefinite::Str) | FragmentDataType::OneOf(_) => {
+                Ok(FragmentData::Str(s.to_string()))
+            }
+            _ => Self::parse(s),
+        }
+    }
 }
 
 pub struct FragmentDataDisplay<'a>(&'a FragmentData);
@@ -128,7 +461,28 @@ impl std::fmt::Display for FragmentDataDisplay<'_> {
         match self.0 {
             FragmentData::Bool(b) => write!(f, "{b}"),
             FragmentData::Int(i) => write!(f, "{i}"),
+            // Debug keeps the fractional part, so that "1.0" is not shown as an integer
+            FragmentData::Float(fl) => write!(f, "{fl:?}"),
             FragmentData::Str(s) => write!(f, "{s}"),
+            FragmentData::List(list) => {
+                write!(f, "[")?;
+                for (idx, value) in list.iter().enumerate() {
+                    let sep = if idx == 0 { "" } else { ", " };
+                    write!(f, "{sep}{}", value.display())?;
+                }
+                write!(f, "]")
+            }
+            FragmentData::Map(map) => {
+                let mut entries = map.iter().collect::<Vec<_>>();
+                entries.sort_by_key(|(key, _)| *key);
+
+                write!(f, "{{")?;
+                for (idx, (key, value)) in entries.into_iter().enumerate() {
+                    let sep = if idx == 0 { " " } else { ", " };
+                    write!(f, "{sep}{key} = {}", value.display())?;
+                }
+                write!(f, " }}")
+            }
         }
     }
 }
@@ -143,6 +497,9 @@ pub struct FragmentDataDesc {
     fragment_type: FragmentDataType,
     #[getset(get = "pub")]
     default_value: Option<FragmentData>,
+    /// A handlebars template for computing the default value from the other header fields
+    #[getset(get = "pub")]
+    default_template: Option<String>,
     #[getset(get_copy = "pub")]
     required: bool,
     #[getset(get = "pub")]
@@ -162,8 +519,12 @@ pub enum FragmentDataTypeDefinite {
     Bool,
     #[serde(rename = "int")]
     Int,
+    #[serde(rename = "float")]
+    Float,
     #[serde(rename = "string")]
     Str,
+    #[serde(rename = "map")]
+    Map,
 }
 
 impl FragmentDataType {
@@ -171,7 +532,9 @@ impl FragmentDataType {
         match self {
             FragmentDataType::Ty(FragmentDataTypeDefinite::Bool) => "bool".to_string(),
             FragmentDataType::Ty(FragmentDataTypeDefinite::Int) => "int".to_string(),
+            FragmentDataType::Ty(FragmentDataTypeDefinite::Float) => "float".to_string(),
             FragmentDataType::Ty(FragmentDataTypeDefinite::Str) => "string".to_string(),
+            FragmentDataType::Ty(FragmentDataTypeDefinite::Map) => "map".to_string(),
             FragmentDataType::OneOf(list) => {
                 let list = list
                     .iter()
@@ -183,11 +546,100 @@ impl FragmentDataType {
         }
     }
 
+    /// Describe this type as JSON Schema
+    pub fn json_schema(&self) -> serde_json::Value {
+        match self {
+            FragmentDataType::Ty(FragmentDataTypeDefinite::Bool) => {
+                serde_json::json!({ "type": "boolean" })
+            }
+            FragmentDataType::Ty(FragmentDataTypeDefinite::Int) => {
+                serde_json::json!({ "type": "integer" })
+            }
+            FragmentDataType::Ty(FragmentDataTypeDefinite::Float) => {
+                serde_json::json!({ "type": "number" })
+            }
+            FragmentDataType::Ty(FragmentDataTypeDefinite::Str) => {
+                serde_json::json!({ "type": "string" })
+            }
+            FragmentDataType::Ty(FragmentDataTypeDefinite::Map) => {
+                serde_json::json!({ "type": "object" })
+            }
+            FragmentDataType::OneOf(possible_values) => {
+                serde_json::json!({ "type": "string", "enum": possible_values })
+            }
+        }
+    }
+
+    /// The possible value that matches the data ignoring case, if the data does not match already
+    ///
+    /// Only returns a value for fields with a list of possible values.
+    pub fn normalize_case(&self, data: &FragmentData) -> Option<FragmentData> {
+        match (self, data) {
+            (FragmentDataType::OneOf(possible_values), FragmentData::Str(s))
+                if !possible_values.contains(s) =>
+            {
+                let s = s.to_lowercase();
+                possible_values
+                    .iter()
+                    .find(|value| value.to_lowercase() == s)
+                    .map(|value| FragmentData::Str(value.clone()))
+            }
+            _ => None,
+        }
+    }
+
+    /// Convert the data to this type, if it does not match already and the conversion is
+    /// unambiguous
+    ///
+    /// E.g. the string "123" is converted to an integer, but "abc" is not.
+    pub fn coerce(&self, data: &FragmentData) -> Option<FragmentData> {
+        use std::str::FromStr;
+
+        if self.matches(data) {
+            return None;
+        }
+
+        let coerced = match (self, data) {
+            (FragmentDataType::Ty(FragmentDataTypeDefinite::Int), FragmentData::Str(s)) => {
+                i64::from_str(s.trim()).ok().map(FragmentData::Int)
+            }
+            (FragmentDataType::Ty(FragmentDataTypeDefinite::Float), FragmentData::Str(s)) => {
+                f64::from_str(s.trim())
+                    .ok()
+                    .filter(|f| f.is_finite())
+                    .map(FragmentData::Float)
+            }
+            (FragmentDataType::Ty(FragmentDataTypeDefinite::Float), FragmentData::Int(i)) => {
+                Some(FragmentData::Float(*i as f64))
+            }
+            (FragmentDataType::Ty(FragmentDataTypeDefinite::Bool), FragmentData::Str(s)) => {
+                match s.trim() {
+                    "true" => Some(FragmentData::Bool(true)),
+                    "false" => Some(FragmentData::Bool(false)),
+                    _ => None,
+                }
+            }
+            (
+                FragmentDataType::Ty(FragmentDataTypeDefinite::Str) | FragmentDataType::OneOf(_),
+                FragmentData::Int(_) | FragmentData::Float(_) | FragmentData::Bool(_),
+            ) => Some(FragmentData::Str(data.display().to_string())),
+            (_, _) => None,
+        };
+
+        coerced.filter(|coerced| self.matches(coerced))
+    }
+
     pub fn matches(&self, data: &FragmentData) -> bool {
         match (self, data) {
             (FragmentDataType::Ty(FragmentDataTypeDefinite::Bool), FragmentData::Bool(_)) => true,
             (FragmentDataType::Ty(FragmentDataTypeDefinite::Int), FragmentData::Int(_)) => true,
+            (FragmentDataType::Ty(FragmentDataTypeDefinite::Float), FragmentData::Float(f)) => {
+                f.is_finite()
+            }
             (FragmentDataType::Ty(FragmentDataTypeDefinite::Str), FragmentData::Str(_)) => true,
+            (FragmentDataType::Ty(FragmentDataTypeDefinite::Map), FragmentData::Map(_)) => {
+                data.depth() <= MAX_MAP_DEPTH && data.is_finite()
+            }
             (FragmentDataType::OneOf(possible_values), FragmentData::Str(s)) => {
                 possible_values.contains(s)
             }
@@ -196,6 +648,39 @@ impl FragmentDataType {
     }
 }
 
+/// Selects fragments by the value of one of their header fields
+///
+/// Fragments without the header field never match.
+#[derive(Clone, Debug)]
+pub struct HeaderFilter {
+    field: String,
+    value: String,
+    default: Option<String>,
+}
+
+impl HeaderFilter {
+    pub fn new(field: impl Into<String>, value: impl Into<String>) -> Self {
+        Self {
+            field: field.into(),
+            value: value.into(),
+            default: None,
+        }
+    }
+
+    /// Treat fragments without the field as if they had this value
+    pub fn with_default(mut self, default: impl Into<String>) -> Self {
+        self.default = Some(default.into());
+        self
+    }
+
+    pub fn matches(&self, fragment: &Fragment) -> bool {
+        match fragment.header().get(&self.field) {
+            Some(data) => data.display().to_string() == self.value,
+            None => self.default.as_ref() == Some(&self.value),
+        }
+    }
+}
+
 #[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
 #[serde(tag = "type", content = "value")]
 #[serde(rename_all = "lowercase")]
@@ -210,6 +695,104 @@ mod tests {
 
     use super::*;
 
+    #[test]
+    fn test_round_trip() {
+        let sources = [
+            "+++\n+++\n",
+            "+++\nissue = 123\nsubject = \"Subject\"\n+++\nText\n",
+            "+++\nbreaking = true\nratio = 0.5\n+++\nMultiple\n\nlines of text\n",
+            "+++\nlinks = { issue = 2, nested = { pr = 1 } }\n+++\n",
+        ];
+
+        for source in sources {
+            let fragment = Fragment::from_reader(&mut Cursor::new(source)).unwrap();
+            let serialized = fragment.to_string(Format::Toml).unwrap();
+            let parsed = Fragment::from_reader(&mut Cursor::new(&serialized)).unwrap();
+            assert_eq!(parsed, fragment, "For {source:?}");
+        }
+
+        // Header fields keep their order
+        let source = "+++\nsubject = \"Subject\"\nissue = 123\nbreaking = true\n+++\nText\n";
+        let fragment = Fragment::from_reader(&mut Cursor::new(source)).unwrap();
+        assert_eq!(fragment.to_string(Format::Toml).unwrap(), source);
+    }
+
+    #[test]
+    fn test_multiline_string_round_trip() {
+        let sources = [
+            "+++\nnotes = \"\"\"\nfirst line\n  second line\n\"\"\"\n+++\nText\n",
+            "+++\nnotes = '''\n\"quoted\"\n\\not escaped\n'''\n+++\nText\n",
+            "+++\nlist = [\"a\\nb\", \"c\"]\nmap = { notes = \"\"\"\nline\n\"\"\" }\n+++\n",
+        ];
+
+        for source in sources {
+            let fragment = Fragment::from_reader(&mut Cursor::new(source)).unwrap();
+            let serialized = fragment.to_string(Format::Toml).unwrap();
+            let parsed = Fragment::from_reader(&mut Cursor::new(&serialized)).unwrap();
+            assert_eq!(parsed, fragment, "For {source:?}");
+        }
+
+        // Multi-line strings stay readable
+        let source = "+++\nnotes = \"\"\"\nfirst line\n  second line\n\"\"\"\n+++\nText\n";
+        let fragment = Fragment::from_reader(&mut Cursor::new(source)).unwrap();
+        assert_eq!(fragment.to_string(Format::Toml).unwrap(), source);
+
+        // Line endings in the file are not part of the value
+        let source = "+++\r\nnotes = \"\"\"\r\na\r\nb\"\"\"\r\n+++\r\nText\r\n";
+        let fragment = Fragment::from_reader(&mut Cursor::new(source)).unwrap();
+        assert_eq!(
+            fragment.header().get("notes"),
+            Some(&FragmentData::Str("a\nb".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_multiline_string_with_separator_line_round_trip() {
+        let notes = FragmentData::Str("before\n+++\nafter".to_string());
+        let mut fragment = Fragment::empty();
+        fragment.header.insert("notes".to_string(), notes.clone());
+        fragment.header.insert(
+            "nested".to_string(),
+            FragmentData::List(vec![notes.clone(), FragmentData::Str("a\nb".to_string())]),
+        );
+        fragment.set_text("Text".to_string());
+
+        let serialized = fragment.to_string(Format::Toml).unwrap();
+        assert!(
+            serialized.starts_with("+++\nnotes = \"before\\n+++\\nafter\"\n"),
+            "{serialized}"
+        );
+        let parsed = Fragment::from_reader(&mut Cursor::new(&serialized)).unwrap();
+        assert_eq!(parsed, fragment);
+    }
+
+    #[test]
+    fn test_sort_header() {
+        let desc = |ty: &str| -> FragmentDataDesc {
+            toml::from_str(&format!("type = \"{ty}\"\nrequired = false")).unwrap()
+        };
+        let header_fields = IndexMap::from([
+            ("type".to_string(), desc("string")),
+            ("issue".to_string(), desc("int")),
+        ]);
+
+        let mut fragment = Fragment::new(
+            IndexMap::from([
+                ("extra".to_string(), FragmentData::Bool(true)),
+                ("issue".to_string(), FragmentData::Int(1)),
+                ("created".to_string(), FragmentData::Str("now".to_string())),
+                ("type".to_string(), FragmentData::Str("Bugfix".to_string())),
+            ]),
+            String::new(),
+        );
+        fragment.sort_header(&header_fields);
+
+        assert_eq!(
+            fragment.header().keys().collect::<Vec<_>>(),
+            ["type", "issue", "extra", "created"]
+        );
+    }
+
     #[test]
     fn read_empty_fragment() {
         let s = indoc::indoc!(
@@ -278,6 +861,56 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_normalize_case() {
+        let ty = FragmentDataType::OneOf(vec!["Bugfix".to_string(), "Feature".to_string()]);
+        for value in ["bugfix", "BUGFIX", "bugFix"] {
+            let normalized = ty.normalize_case(&FragmentData::Str(value.to_string()));
+            assert!(
+                std::matches!(normalized, Some(FragmentData::Str(ref s)) if s == "Bugfix"),
+                "Not normalized: {normalized:?}"
+            );
+        }
+
+        assert!(ty
+            .normalize_case(&FragmentData::Str("Bugfix".to_string()))
+            .is_none());
+        assert!(ty
+            .normalize_case(&FragmentData::Str("bug".to_string()))
+            .is_none());
+        assert!(FragmentDataType::Ty(FragmentDataTypeDefinite::Str)
+            .normalize_case(&FragmentData::Str("bugfix".to_string()))
+            .is_none());
+    }
+
+    #[test]
+    fn test_coerce_string_to_int() {
+        let ty = FragmentDataType::Ty(FragmentDataTypeDefinite::Int);
+        let coerced = ty.coerce(&FragmentData::Str("123".to_string()));
+        assert!(
+            std::matches!(coerced, Some(FragmentData::Int(123))),
+            "Not coerced: {coerced:?}"
+        );
+        assert!(ty.coerce(&FragmentData::Str("abc".to_string())).is_none());
+    }
+
+    #[test]
+    fn test_coerce_int_to_string() {
+        let ty = FragmentDataType::Ty(FragmentDataTypeDefinite::Str);
+        let coerced = ty.coerce(&FragmentData::Int(123));
+        assert!(
+            std::matches!(coerced, Some(FragmentData::Str(ref s)) if s == "123"),
+            "Not coerced: {coerced:?}"
+        );
+    }
+
+    #[test]
+    fn test_coerce_int_to_one_of_requires_possible_value() {
+        let ty = FragmentDataType::OneOf(vec!["1".to_string(), "2".to_string()]);
+        assert!(ty.coerce(&FragmentData::Int(1)).is_some());
+        assert!(ty.coerce(&FragmentData::Int(3)).is_none());
+    }
+
     #[test]
     fn test_deserializing_data_desc_with_one_of() {
         let s = r#"
@@ -298,10 +931,314 @@ mod tests {
                     ]),
                     required: false,
                     default_value: None,
+                    default_template: None,
                     crawler: None,
                 };
                 toml::to_string(&fdd).unwrap()
             }
         );
     }
+
+    #[test]
+    fn test_float_round_trip() {
+        let s = indoc::indoc!(
+            r#"+++
+        ratio = 0.5
+        whole = 2.0
+        count = 2
+        +++
+        "#
+        );
+
+        let f = Fragment::from_reader(&mut Cursor::new(s)).unwrap();
+        assert!(std::matches!(f.header().get("ratio"), Some(FragmentData::Float(r)) if *r == 0.5));
+        assert!(std::matches!(f.header().get("whole"), Some(FragmentData::Float(w)) if *w == 2.0));
+        assert!(
+            std::matches!(f.header().get("count"), Some(FragmentData::Int(2))),
+            "Integer became float: {:?}",
+            f.header()
+        );
+
+        let mut buf = Vec::new();
+        f.to_writer(&mut buf, Format::Toml).unwrap();
+        let f = Fragment::from_reader(&mut Cursor::new(buf)).unwrap();
+        assert!(std::matches!(f.header().get("ratio"), Some(FragmentData::Float(r)) if *r == 0.5));
+        assert!(std::matches!(f.header().get("whole"), Some(FragmentData::Float(w)) if *w == 2.0));
+        assert!(std::matches!(
+            f.header().get("count"),
+            Some(FragmentData::Int(2))
+        ));
+    }
+
+    #[test]
+    fn test_non_finite_float_is_rejected() {
+        for value in ["nan", "inf", "-inf"] {
+            let s = format!("+++\nratio = {value}\n+++\n");
+            let f = Fragment::from_reader(&mut Cursor::new(s));
+            assert!(
+                std::matches!(f, Err(FragmentError::NonFiniteFloat(ref key)) if key == "ratio"),
+                "Not rejected: {f:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_float() {
+        assert!(std::matches!(FragmentData::parse("1.5"), Ok(FragmentData::Float(f)) if f == 1.5));
+        assert!(std::matches!(
+            FragmentData::parse("2"),
+            Ok(FragmentData::Int(2))
+        ));
+        assert!(std::matches!(
+            FragmentData::parse("nan"),
+            Ok(FragmentData::Str(_))
+        ));
+        assert_eq!(FragmentData::Float(2.0).display().to_string(), "2.0");
+    }
+
+    #[test]
+    fn test_negative_integer_is_int() {
+        let f = Fragment::from_reader(&mut Cursor::new(
+            "+++
+score = -3
++++
+",
+        ))
+        .unwrap();
+        assert!(std::matches!(
+            f.header().get("score"),
+            Some(FragmentData::Int(-3))
+        ));
+        assert!(std::matches!(
+            FragmentData::parse("-3"),
+            Ok(FragmentData::Int(-3))
+        ));
+    }
+
+    #[test]
+    fn test_parse_as_keeps_numeric_looking_strings() {
+        let string = FragmentDataType::Ty(FragmentDataTypeDefinite::Str);
+        assert!(std::matches!(
+            FragmentData::parse_as("1.5", &string),
+            Ok(FragmentData::Str(ref s)) if s == "1.5"
+        ));
+        assert!(std::matches!(
+            FragmentData::parse_as("007", &FragmentDataType::OneOf(vec!["007".to_string()])),
+            Ok(FragmentData::Str(ref s)) if s == "007"
+        ));
+        assert!(std::matches!(
+            FragmentData::parse_as("1.5", &FragmentDataType::Ty(FragmentDataTypeDefinite::Float)),
+            Ok(FragmentData::Float(f)) if f == 1.5
+        ));
+    }
+
+    #[test]
+    fn test_coerce_to_float() {
+        let ty = FragmentDataType::Ty(FragmentDataTypeDefinite::Float);
+        assert!(!ty.matches(&FragmentData::Int(2)));
+        assert!(
+            std::matches!(ty.coerce(&FragmentData::Int(2)), Some(FragmentData::Float(f)) if f == 2.0)
+        );
+        assert!(std::matches!(
+            ty.coerce(&FragmentData::Str("0.25".to_string())),
+            Some(FragmentData::Float(f)) if f == 0.25
+        ));
+        assert!(ty.coerce(&FragmentData::Str("inf".to_string())).is_none());
+    }
+
+    #[test]
+    fn test_header_filter() {
+        let filter = HeaderFilter::new("author", "alice");
+        let mut header = IndexMap::new();
+        assert!(!filter.matches(&Fragment::new(header.clone(), String::new())));
+
+        header.insert("author".to_string(), FragmentData::Str("bob".to_string()));
+        assert!(!filter.matches(&Fragment::new(header.clone(), String::new())));
+
+        header.insert("author".to_string(), FragmentData::Str("alice".to_string()));
+        assert!(filter.matches(&Fragment::new(header, String::new())));
+    }
+
+    #[test]
+    fn test_header_filter_with_default() {
+        let filter = HeaderFilter::new("audience", "external").with_default("external");
+        let mut header = IndexMap::new();
+        assert!(filter.matches(&Fragment::new(header.clone(), String::new())));
+
+        header.insert(
+            "audience".to_string(),
+            FragmentData::Str("internal".to_string()),
+        );
+        assert!(!filter.matches(&Fragment::new(header, String::new())));
+
+        let filter = HeaderFilter::new("audience", "internal").with_default("external");
+        assert!(!filter.matches(&Fragment::new(IndexMap::new(), String::new())));
+    }
+
+    #[test]
+    fn test_header_error_points_at_offending_line() {
+        let s = "+++\nsubject = \"x\"\nissue = \n+++\ntext\n";
+        let err = Fragment::from_reader(&mut Cursor::new(s)).unwrap_err();
+        match err {
+            FragmentError::TomlDe { span, src, .. } => {
+                assert_eq!(src, s);
+                let span = span.expect("No span");
+                assert_eq!(
+                    span.offset(),
+                    s.find("issue = ").unwrap() + "issue = ".len()
+                );
+            }
+            other => panic!("Expected TOML error, found: {other:?}"),
+        }
+
+        let err = Fragment::from_reader(&mut Cursor::new("---\n+++\n")).unwrap_err();
+        assert!(
+            std::matches!(err, FragmentError::ExpectedSeperator { ref found, span, ref help, .. } if found == "---" && span.offset() == 0 && span.len() == 3 && help.is_some()),
+            "Unexpected error: {err:?}"
+        );
+    }
+
+    #[test]
+    fn test_content_hash() {
+        let header = IndexMap::from([
+            ("issue".to_string(), FragmentData::Int(123)),
+            (
+                "subject".to_string(),
+                FragmentData::Str("Subject".to_string()),
+            ),
+        ]);
+
+        let hash = Fragment::new(header.clone(), "Text".to_string()).content_hash(&[]);
+        assert_eq!(hash.len(), 12);
+        assert_eq!(
+            hash,
+            Fragment::new(header.clone(), "Text\n".to_string()).content_hash(&[])
+        );
+        assert_ne!(
+            hash,
+            Fragment::new(header.clone(), "Other text".to_string()).content_hash(&[])
+        );
+        assert_ne!(
+            hash,
+            Fragment::new(IndexMap::new(), "Text".to_string()).content_hash(&[])
+        );
+
+        let mut created_header = header.clone();
+        created_header.insert(
+            "created".to_string(),
+            FragmentData::Str("2024-01-31T12:00:00Z".to_string()),
+        );
+        let created = Fragment::new(created_header, "Text".to_string());
+        assert_ne!(hash, created.content_hash(&[]));
+        assert_eq!(hash, created.content_hash(&["created"]));
+    }
+
+    #[test]
+    fn test_from_path_reports_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("broken.md");
+        std::fs::write(&path, "---\n+++\n").unwrap();
+
+        for path in [path, dir.path().join("missing.md")] {
+            match Fragment::from_path(&path).unwrap_err() {
+                Error::Fragment(_, error_path) => assert_eq!(error_path, path),
+                other => panic!("Expected fragment error, found: {other:?}"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_byte_order_mark_is_skipped() {
+        let mut reader = Cursor::new(b"\xEF\xBB\xBF+++\nissue = 1\n+++\ntext\n".to_vec());
+        let fragment = Fragment::from_reader(&mut reader).unwrap();
+        assert!(std::matches!(
+            fragment.header().get("issue"),
+            Some(FragmentData::Int(1))
+        ));
+        assert_eq!(fragment.text(), "text");
+    }
+
+    #[test]
+    fn test_non_utf8_is_rejected() {
+        let mut reader = Cursor::new(b"+++\nsubject = \"caf\xE9\"\n+++\n".to_vec());
+        let err = Fragment::from_reader(&mut reader).unwrap_err();
+        assert!(
+            std::matches!(err, FragmentError::NotUtf8(18)),
+            "Unexpected error: {err:?}"
+        );
+    }
+
+    #[test]
+    fn test_map_round_trip() {
+        let s = indoc::indoc!(
+            r#"+++
+        subject = "Subject"
+        links = { pr = 1, issue = 2, nested = { ratio = 0.5 } }
+        +++
+        text
+        "#
+        );
+
+        let f = Fragment::from_reader(&mut Cursor::new(s)).unwrap();
+        let links = f.header().get("links").unwrap();
+        assert_eq!(
+            links.display().to_string(),
+            "{ issue = 2, nested = { ratio = 0.5 }, pr = 1 }"
+        );
+        assert_eq!(links.depth(), 2);
+        assert!(FragmentDataType::Ty(FragmentDataTypeDefinite::Map).matches(links));
+        assert!(!FragmentDataType::Ty(FragmentDataTypeDefinite::Str).matches(links));
+
+        let mut buf = Vec::new();
+        f.to_writer(&mut buf, Format::Toml).unwrap();
+        let f = Fragment::from_reader(&mut Cursor::new(buf)).unwrap();
+        assert_eq!(
+            f.header().get("links").unwrap().display().to_string(),
+            "{ issue = 2, nested = { ratio = 0.5 }, pr = 1 }"
+        );
+        assert_eq!(f.text(), "text");
+
+        // Templates can access nested values
+        let json = serde_json::to_value(f.header()).unwrap();
+        assert_eq!(json["links"]["pr"], 1);
+    }
+
+    #[test]
+    fn test_map_depth_is_limited() {
+        let nested =
+            (0..=MAX_MAP_DEPTH).fold("1".to_string(), |inner, _| format!("{{ a = {inner} }}"));
+        let s = format!("+++\nlinks = {nested}\n+++\n");
+
+        let err = Fragment::from_reader(&mut Cursor::new(s)).unwrap_err();
+        assert!(
+            std::matches!(err, FragmentError::MapTooDeep(ref key, MAX_MAP_DEPTH) if key == "links"),
+            "Unexpected error: {err:?}"
+        );
+    }
+
+    #[test]
+    fn test_normalize_key_case() {
+        let mut f = Fragment::empty();
+        f.header_mut()
+            .insert("prNumber".to_string(), FragmentData::Int(1));
+        f.header_mut()
+            .insert("issue".to_string(), FragmentData::Int(2));
+        f.normalize_key_case(KeyCase::Kebab).unwrap();
+        assert_eq!(
+            f.header().keys().collect::<Vec<_>>(),
+            ["pr-number", "issue"]
+        );
+
+        f.header_mut()
+            .insert("pr_number".to_string(), FragmentData::Int(3));
+        let err = f.normalize_key_case(KeyCase::Kebab).unwrap_err();
+        assert!(
+            std::matches!(
+                err,
+                FragmentError::KeyCollision { ref first, ref second, .. }
+                    if first == "pr-number" && second == "pr_number"
+            ),
+            "Unexpected error: {err:?}"
+        );
+    }
 }
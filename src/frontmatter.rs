@@ -0,0 +1,82 @@
+use std::io::Write;
+
+/// Write the table as `---`-delimited YAML front-matter
+///
+/// Values are written as JSON, which is valid YAML, so no YAML serializer is needed.
+pub fn write_frontmatter<W: Write>(writer: &mut W, table: &toml::Table) -> std::io::Result<()> {
+    writeln!(writer, "---")?;
+    for (key, value) in table {
+        let value = serde_json::to_string(&to_json(value)).map_err(std::io::Error::other)?;
+        writeln!(writer, "{}: {value}", yaml_key(key))?;
+    }
+    writeln!(writer, "---")?;
+    Ok(())
+}
+
+/// Keys that are not plain words are quoted
+fn yaml_key(key: &str) -> String {
+    let is_plain = !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+
+    if is_plain {
+        key.to_string()
+    } else {
+        serde_json::Value::String(key.to_string()).to_string()
+    }
+}
+
+fn to_json(value: &toml::Value) -> serde_json::Value {
+    match value {
+        toml::Value::String(s) => serde_json::Value::String(s.clone()),
+        toml::Value::Integer(i) => serde_json::Value::from(*i),
+        toml::Value::Float(f) => serde_json::Value::from(*f),
+        toml::Value::Boolean(b) => serde_json::Value::Bool(*b),
+        toml::Value::Datetime(dt) => serde_json::Value::String(dt.to_string()),
+        toml::Value::Array(array) => array.iter().map(to_json).collect(),
+        toml::Value::Table(table) => table
+            .iter()
+            .map(|(key, value)| (key.clone(), to_json(value)))
+            .collect::<serde_json::Map<_, _>>()
+            .into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_frontmatter() {
+        let table: toml::Table = toml::from_str(indoc::indoc!(
+            r#"
+            title = "Changelog"
+            layout = "page"
+            weight = 10
+            tags = ["release", "notes"]
+            "nav order" = 2
+            "#
+        ))
+        .unwrap();
+
+        let mut buf = Vec::new();
+        write_frontmatter(&mut buf, &table).unwrap();
+        let frontmatter = String::from_utf8(buf).unwrap();
+
+        assert_eq!(
+            frontmatter,
+            indoc::indoc!(
+                r#"
+                ---
+                title: "Changelog"
+                layout: "page"
+                weight: 10
+                tags: ["release","notes"]
+                "nav order": 2
+                ---
+                "#
+            )
+        );
+    }
+}
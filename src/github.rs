@@ -0,0 +1,232 @@
+//! Pre-filling new changelog entries from GitHub pull requests
+//!
+//! The GitHub API is queried with `curl`, so no HTTP client is needed.
+
+use crate::cli::KV;
+use crate::config::Configuration;
+use crate::error::Error;
+use crate::error::GithubError;
+use crate::fragment::FragmentDataType;
+
+/// The environment variables the API token is read from, in this order
+const TOKEN_ENV_VARS: &[&str] = &["GITHUB_TOKEN", "GH_TOKEN"];
+
+/// A pull request, as returned by the GitHub API
+#[derive(Debug, serde::Deserialize)]
+pub struct PullRequest {
+    number: u64,
+    title: String,
+    #[serde(default)]
+    labels: Vec<Label>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct Label {
+    name: String,
+}
+
+/// Add the values of the pull request `number` to the header values and text of a new entry
+///
+/// Values given on the commandline take precedence. The repository is found via the
+/// `repository_url` setting, or the URL of the "origin" remote.
+pub fn prefill(
+    repository: &git2::Repository,
+    config: &Configuration,
+    number: u64,
+    mut set: Vec<KV>,
+    text: Option<String>,
+) -> Result<(Vec<KV>, Option<String>), Error> {
+    check_curl()?;
+
+    let repository_url = match config.repository_url() {
+        Some(url) => url.clone(),
+        None => repository
+            .find_remote("origin")
+            .ok()
+            .and_then(|remote| remote.url().map(String::from))
+            .ok_or(Error::NoGithubRepository)?,
+    };
+    let pull_request = fetch_pull_request(&repository_url, number)?;
+
+    // `add` uses the first value given for a header field
+    set.extend(pull_request.header_values(config));
+    let text = text.or_else(|| Some(pull_request.title.clone()));
+    Ok((set, text))
+}
+
+/// Make sure `curl` can be run, before anything else is done
+fn check_curl() -> Result<(), GithubError> {
+    let status = std::process::Command::new("curl")
+        .arg("--version")
+        .stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .status();
+    match status {
+        Ok(status) if status.success() => Ok(()),
+        Ok(_) => Err(GithubError::CurlNotFound),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Err(GithubError::CurlNotFound),
+        Err(e) => Err(GithubError::Curl(e)),
+    }
+}
+
+/// Fetch the pull request `number` of the repository at `repository_url`
+pub fn fetch_pull_request(repository_url: &str, number: u64) -> Result<PullRequest, GithubError> {
+    let url = api_url(repository_url, number)?;
+    tracing::debug!("Fetching {url}");
+
+    // The headers are passed on stdin, so that the token does not show up in the process list
+    let mut headers = String::from("Accept: application/vnd.github+json\n");
+    match TOKEN_ENV_VARS
+        .iter()
+        .find_map(|name| std::env::var(name).ok())
+    {
+        Some(token) => headers.push_str(&format!("Authorization: Bearer {token}\n")),
+        None => tracing::debug!("No GitHub token set, sending unauthenticated request"),
+    }
+
+    let mut child = std::process::Command::new("curl")
+        .arg("--silent")
+        .arg("--show-error")
+        .arg("--location")
+        .arg("--header")
+        .arg("@-")
+        .arg("--write-out")
+        .arg("\n%{http_code}")
+        .arg(&url)
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped())
+        .spawn()
+        .map_err(GithubError::Curl)?;
+    {
+        use std::io::Write;
+        let mut stdin = child.stdin.take().expect("stdin is piped");
+        stdin
+            .write_all(headers.as_bytes())
+            .map_err(GithubError::Curl)?;
+    }
+    let output = child.wait_with_output().map_err(GithubError::Curl)?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+        return Err(GithubError::Request(stderr));
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let (body, status) = stdout.rsplit_once('\n').unwrap_or(("", &stdout));
+    match status.trim() {
+        "200" => serde_json::from_str(body).map_err(GithubError::Json),
+        "401" | "403" => Err(GithubError::Unauthorized(url)),
+        "404" => Err(GithubError::NotFound(number)),
+        status => Err(GithubError::Status(status.to_string(), url)),
+    }
+}
+
+/// The API URL of the pull request, from the URL of the repository on GitHub
+///
+/// Both HTTPS (`https://github.com/owner/repo`) and SSH (`git@github.com:owner/repo.git`) URLs
+/// are supported.
+fn api_url(repository_url: &str, number: u64) -> Result<String, GithubError> {
+    let path = repository_url
+        .strip_prefix("https://github.com/")
+        .or_else(|| repository_url.strip_prefix("http://github.com/"))
+        .or_else(|| repository_url.strip_prefix("ssh://git@github.com/"))
+        .or_else(|| repository_url.strip_prefix("git@github.com:"))
+        .ok_or_else(|| GithubError::NotGithub(repository_url.to_string()))?;
+
+    let path = path.trim_end_matches('/');
+    let path = path.strip_suffix(".git").unwrap_or(path);
+    match path.split_once('/') {
+        Some((owner, repo)) if !owner.is_empty() && !repo.is_empty() && !repo.contains('/') => Ok(
+            format!("https://api.github.com/repos/{owner}/{repo}/pulls/{number}"),
+        ),
+        _ => Err(GithubError::NotGithub(repository_url.to_string())),
+    }
+}
+
+impl PullRequest {
+    /// Header values for the new entry: the number as "issue" and a label as "type"
+    ///
+    /// Values are only returned for header fields that are configured. If the "type" field has a
+    /// list of possible values, the first label matching one of them (ignoring case) is used.
+    pub fn header_values(&self, config: &Configuration) -> Vec<KV> {
+        let mut values = Vec::new();
+        if config.header_fields().contains_key("issue") {
+            values.push(KV::new("issue".to_string(), self.number.to_string()));
+        }
+
+        let label = match config
+            .header_fields()
+            .get(crate::consts::TYPE_HEADER_FIELD)
+            .map(|desc| desc.fragment_type())
+        {
+            Some(FragmentDataType::OneOf(possible_values)) => {
+                self.labels.iter().find_map(|label| {
+                    possible_values
+                        .iter()
+                        .find(|value| value.eq_ignore_ascii_case(&label.name))
+                        .cloned()
+                })
+            }
+            Some(FragmentDataType::Ty(_)) => self.labels.first().map(|label| label.name.clone()),
+            None => None,
+        };
+        if let Some(label) = label {
+            values.push(KV::new(crate::consts::TYPE_HEADER_FIELD.to_string(), label));
+        }
+
+        values
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_api_url() {
+        let expected = "https://api.github.com/repos/owner/repo/pulls/12";
+        for url in [
+            "https://github.com/owner/repo",
+            "https://github.com/owner/repo/",
+            "https://github.com/owner/repo.git",
+            "git@github.com:owner/repo.git",
+            "ssh://git@github.com/owner/repo",
+        ] {
+            assert_eq!(api_url(url, 12).unwrap(), expected, "For {url}");
+        }
+
+        for url in [
+            "https://gitlab.com/owner/repo",
+            "https://github.com/owner",
+            "https://github.com/owner/repo/pulls",
+        ] {
+            assert!(
+                std::matches!(api_url(url, 12), Err(GithubError::NotGithub(_))),
+                "For {url}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_header_values() {
+        let config: Configuration = toml::from_str(crate::config::DEFAULT_CONFIG).unwrap();
+        let pr: PullRequest = serde_json::from_str(
+            r#"{
+                "number": 42,
+                "title": "Fix the thing",
+                "labels": [{ "name": "dependencies" }, { "name": "bugfix" }]
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(pr.title, "Fix the thing");
+        assert_eq!(
+            pr.header_values(&config),
+            [
+                KV::new("issue".to_string(), "42".to_string()),
+                KV::new("type".to_string(), "Bugfix".to_string()),
+            ]
+        );
+    }
+}
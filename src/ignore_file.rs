@@ -0,0 +1,225 @@
+//! Support for `.changelogignore` files, which use the gitignore syntax
+//!
+//! Supported are comments, negation with `!`, anchoring with `/`, directory-only patterns with a
+//! trailing `/`, and the wildcards `*`, `?`, `**` and `[...]`.
+
+use std::path::Path;
+
+use crate::error::Error;
+
+#[derive(Debug)]
+pub struct IgnoreFile {
+    rules: Vec<Rule>,
+}
+
+#[derive(Debug)]
+struct Rule {
+    pattern: Vec<char>,
+    negated: bool,
+    dir_only: bool,
+    /// Whether the pattern is matched against the whole path, instead of the name of the file
+    anchored: bool,
+}
+
+impl IgnoreFile {
+    /// Load the ignore file from `dir`, if there is one
+    pub fn load(dir: &Path) -> Result<Option<Self>, Error> {
+        let path = dir.join(crate::consts::IGNORE_FILE_NAME);
+        match std::fs::read_to_string(&path) {
+            Ok(content) => {
+                tracing::debug!("Using ignore file {}", path.display());
+                Ok(Some(Self::parse(&content)))
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(Error::from(e)),
+        }
+    }
+
+    pub fn parse(content: &str) -> Self {
+        let rules = content
+            .lines()
+            .filter_map(|line| {
+                let line = line.trim_end();
+                if line.is_empty() || line.starts_with('#') {
+                    return None;
+                }
+
+                let (negated, line) = match line.strip_prefix('!') {
+                    Some(rest) => (true, rest),
+                    None => (false, line.strip_prefix('\\').unwrap_or(line)),
+                };
+                let (dir_only, line) = match line.strip_suffix('/') {
+                    Some(rest) => (true, rest),
+                    None => (false, line),
+                };
+                let anchored = line.contains('/');
+                let line = line.strip_prefix('/').unwrap_or(line);
+
+                Some(Rule {
+                    pattern: line.chars().collect(),
+                    negated,
+                    dir_only,
+                    anchored,
+                })
+            })
+            .collect();
+
+        Self { rules }
+    }
+
+    /// Whether the file at `path`, relative to the directory of the ignore file, is ignored
+    ///
+    /// As with git, a file in an ignored directory cannot be re-included.
+    pub fn is_ignored(&self, path: &Path) -> bool {
+        let components = path
+            .components()
+            .filter_map(|comp| comp.as_os_str().to_str())
+            .collect::<Vec<_>>();
+
+        (1..=components.len()).any(|len| {
+            let is_dir = len < components.len();
+            self.matches(&components[..len], is_dir)
+        })
+    }
+
+    /// Whether the last rule matching the path ignores it
+    fn matches(&self, components: &[&str], is_dir: bool) -> bool {
+        let path = components.join("/").chars().collect::<Vec<_>>();
+        let name = components
+            .last()
+            .map(|name| name.chars().collect::<Vec<_>>())
+            .unwrap_or_default();
+
+        self.rules
+            .iter()
+            .rev()
+            .find(|rule| {
+                (is_dir || !rule.dir_only)
+                    && glob_match(&rule.pattern, if rule.anchored { &path } else { &name })
+            })
+            .is_some_and(|rule| !rule.negated)
+    }
+}
+
+/// Match `text` against the glob `pattern`
+fn glob_match(pattern: &[char], text: &[char]) -> bool {
+    match pattern {
+        [] => text.is_empty(),
+        ['*', '*', '/', rest @ ..] => {
+            // Zero or more directories
+            glob_match(rest, text)
+                || text
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, c)| **c == '/')
+                    .any(|(idx, _)| glob_match(rest, &text[idx + 1..]))
+        }
+        ['*', '*'] => true,
+        ['*', rest @ ..] => (0..=text.len())
+            .take_while(|idx| *idx == 0 || text[idx - 1] != '/')
+            .any(|idx| glob_match(rest, &text[idx..])),
+        ['?', rest @ ..] => text
+            .split_first()
+            .is_some_and(|(c, text)| *c != '/' && glob_match(rest, text)),
+        ['[', rest @ ..] => match (text.split_first(), class_match(rest)) {
+            (Some((c, text)), Some((matcher, rest))) => {
+                *c != '/' && matcher(*c) && glob_match(rest, text)
+            }
+            // Without closing bracket, the bracket is matched literally
+            (Some((c, text)), None) => *c == '[' && glob_match(rest, text),
+            (None, _) => false,
+        },
+        ['\\', p, rest @ ..] | [p, rest @ ..] => text
+            .split_first()
+            .is_some_and(|(c, text)| c == p && glob_match(rest, text)),
+    }
+}
+
+/// Parse a character class (after the opening bracket), returning a matcher and the rest of the
+/// pattern
+#[allow(clippy::type_complexity)]
+fn class_match(pattern: &[char]) -> Option<(Box<dyn Fn(char) -> bool + '_>, &[char])> {
+    let (negated, pattern) = match pattern {
+        ['!' | '^', rest @ ..] => (true, rest),
+        rest => (false, rest),
+    };
+
+    // A closing bracket directly after the opening one is part of the class
+    let end = pattern
+        .iter()
+        .skip(1)
+        .position(|c| *c == ']')
+        .map(|idx| idx + 1)?;
+    let class = &pattern[..end];
+
+    let matcher = move |c: char| {
+        let mut idx = 0;
+        let mut found = false;
+        while idx < class.len() {
+            if idx + 2 < class.len() && class[idx + 1] == '-' {
+                found |= class[idx] <= c && c <= class[idx + 2];
+                idx += 3;
+            } else {
+                found |= class[idx] == c;
+                idx += 1;
+            }
+        }
+        found != negated
+    };
+    Some((Box::new(matcher), &pattern[end + 1..]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn is_ignored(content: &str, path: &str) -> bool {
+        IgnoreFile::parse(content).is_ignored(Path::new(path))
+    }
+
+    #[test]
+    fn test_glob_match() {
+        let matches = |pattern: &str, text: &str| {
+            glob_match(
+                &pattern.chars().collect::<Vec<_>>(),
+                &text.chars().collect::<Vec<_>>(),
+            )
+        };
+
+        assert!(matches("*.md", "wip.md"));
+        assert!(!matches("*.md", "dir/wip.md"));
+        assert!(matches("wip-?.md", "wip-1.md"));
+        assert!(matches("**/wip.md", "wip.md"));
+        assert!(matches("**/wip.md", "a/b/wip.md"));
+        assert!(matches("a/**", "a/b/c.md"));
+        assert!(matches("a/**/c.md", "a/c.md"));
+        assert!(matches("a/**/c.md", "a/b/b/c.md"));
+        assert!(matches("wip-[0-9].md", "wip-5.md"));
+        assert!(!matches("wip-[!0-9].md", "wip-5.md"));
+        assert!(matches("[]].md", "].md"));
+        assert!(matches("\\*.md", "*.md"));
+        assert!(!matches("\\*.md", "a.md"));
+    }
+
+    #[test]
+    fn test_ignore_file() {
+        let content = indoc::indoc!(
+            "
+            # WIP fragments
+            wip-*.md
+            !wip-keep.md
+            /unreleased/draft.md
+            drafts/
+            "
+        );
+
+        assert!(is_ignored(content, "unreleased/wip-1.md"));
+        assert!(is_ignored(content, "0.1.0/wip-1.md"));
+        assert!(!is_ignored(content, "unreleased/wip-keep.md"));
+        assert!(is_ignored(content, "unreleased/draft.md"));
+        assert!(!is_ignored(content, "0.1.0/unreleased/draft.md"));
+        assert!(is_ignored(content, "unreleased/drafts/fix.md"));
+        assert!(!is_ignored(content, "unreleased/drafts"));
+        assert!(!is_ignored(content, "unreleased/fix.md"));
+    }
+}
@@ -0,0 +1,138 @@
+use std::io::Write;
+
+use crate::config::LineEnding;
+
+/// A writer that normalizes all line endings written to it
+///
+/// Both "\n" and "\r\n" are written as the configured line ending. Trailing newlines are held
+/// back, so that [`LineEndingWriter::finish`] can end the output with exactly one newline.
+pub struct LineEndingWriter<W: Write> {
+    inner: W,
+    eol: &'static [u8],
+    pending_newlines: usize,
+    pending_cr: bool,
+}
+
+impl<W: Write> LineEndingWriter<W> {
+    pub fn new(inner: W, line_ending: LineEnding) -> Self {
+        let eol: &'static [u8] = match line_ending {
+            LineEnding::Lf => b"\n",
+            LineEnding::Crlf => b"\r\n",
+            LineEnding::Native if cfg!(windows) => b"\r\n",
+            LineEnding::Native => b"\n",
+        };
+
+        LineEndingWriter {
+            inner,
+            eol,
+            pending_newlines: 0,
+            pending_cr: false,
+        }
+    }
+
+    /// Write the final newline and return the inner writer
+    pub fn finish(mut self) -> std::io::Result<W> {
+        let mut buf = Vec::new();
+        self.flush_pending_cr(&mut buf);
+        buf.extend_from_slice(self.eol);
+        self.inner.write_all(&buf)?;
+        self.inner.flush()?;
+        Ok(self.inner)
+    }
+
+    fn flush_pending_newlines(&mut self, buf: &mut Vec<u8>) {
+        for _ in 0..self.pending_newlines {
+            buf.extend_from_slice(self.eol);
+        }
+        self.pending_newlines = 0;
+    }
+
+    /// A carriage return that is not followed by a line feed is kept as is
+    fn flush_pending_cr(&mut self, buf: &mut Vec<u8>) {
+        if self.pending_cr {
+            self.flush_pending_newlines(buf);
+            buf.push(b'\r');
+            self.pending_cr = false;
+        }
+    }
+}
+
+impl<W: Write> Write for LineEndingWriter<W> {
+    fn write(&mut self, data: &[u8]) -> std::io::Result<usize> {
+        let mut buf = Vec::with_capacity(data.len());
+
+        for byte in data {
+            match byte {
+                b'\n' => {
+                    self.pending_cr = false;
+                    self.pending_newlines += 1;
+                }
+                b'\r' => {
+                    self.flush_pending_cr(&mut buf);
+                    self.pending_cr = true;
+                }
+                other => {
+                    self.flush_pending_cr(&mut buf);
+                    self.flush_pending_newlines(&mut buf);
+                    buf.push(*other);
+                }
+            }
+        }
+
+        self.inner.write_all(&buf)?;
+        Ok(data.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn normalize(chunks: &[&str], line_ending: LineEnding) -> String {
+        let mut writer = LineEndingWriter::new(Vec::new(), line_ending);
+        for chunk in chunks {
+            writer.write_all(chunk.as_bytes()).unwrap();
+        }
+        String::from_utf8(writer.finish().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_lf_line_endings() {
+        let s = normalize(
+            &["# Changelog\r\n\r\n", "## v0.1.0\n- entry"],
+            LineEnding::Lf,
+        );
+        assert_eq!(s, "# Changelog\n\n## v0.1.0\n- entry\n");
+    }
+
+    #[test]
+    fn test_crlf_line_endings() {
+        let s = normalize(
+            &["# Changelog\n\n", "## v0.1.0\r\n- entry"],
+            LineEnding::Crlf,
+        );
+        assert_eq!(s, "# Changelog\r\n\r\n## v0.1.0\r\n- entry\r\n");
+    }
+
+    #[test]
+    fn test_exactly_one_trailing_newline() {
+        let s = normalize(&["entry\n\n", "\r\n", "\n"], LineEnding::Lf);
+        assert_eq!(s, "entry\n");
+    }
+
+    #[test]
+    fn test_crlf_split_between_writes() {
+        let s = normalize(&["a\r", "\nb"], LineEnding::Lf);
+        assert_eq!(s, "a\nb\n");
+    }
+
+    #[test]
+    fn test_lone_carriage_return_is_kept() {
+        let s = normalize(&["a\rb"], LineEnding::Lf);
+        assert_eq!(s, "a\rb\n");
+    }
+}
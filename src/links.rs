@@ -0,0 +1,214 @@
+//! Rewriting relative links in the markdown text of fragments
+//!
+//! Only inline links and images (`[text](url)`) and link reference definitions (`[ref]: url`) are
+//! rewritten. Code spans and fenced code blocks are left as they are.
+
+/// Make all relative links in `text` absolute, by prefixing them with `base_url`
+///
+/// Links with a scheme (e.g. `https:` or `mailto:`), protocol-relative links and links to anchors
+/// (`#section`) are left untouched. Links starting with `/` are resolved against `base_url` as well.
+pub fn absolutize_links(text: &str, base_url: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut fence: Option<(char, usize)> = None;
+
+    for line in text.split_inclusive('\n') {
+        let trimmed = line.trim_start();
+        let fence_marker = ['`', '~'].into_iter().find_map(|c| {
+            let len = trimmed.chars().take_while(|t| *t == c).count();
+            (len >= 3).then_some((c, len))
+        });
+
+        match (fence, fence_marker) {
+            (None, Some(marker)) => {
+                fence = Some(marker);
+                result.push_str(line);
+            }
+            (Some((c, len)), Some((marker_c, marker_len)))
+                if c == marker_c && marker_len >= len =>
+            {
+                fence = None;
+                result.push_str(line);
+            }
+            (Some(_), _) => result.push_str(line),
+            (None, None) => match reference_definition_url(line) {
+                Some((start, end)) => {
+                    result.push_str(&line[..start]);
+                    result.push_str(&absolutize(&line[start..end], base_url));
+                    result.push_str(&line[end..]);
+                }
+                None => absolutize_inline_links(line, base_url, &mut result),
+            },
+        }
+    }
+
+    result
+}
+
+/// The byte range of the URL, if the line is a link reference definition (`[ref]: url`)
+fn reference_definition_url(line: &str) -> Option<(usize, usize)> {
+    let indent = line.len() - line.trim_start_matches(' ').len();
+    if indent > 3 || !line[indent..].starts_with('[') {
+        return None;
+    }
+
+    let label_end = indent + line[indent..].find("]:")?;
+    let after_label = label_end + 2;
+    let start = after_label + (line[after_label..].len() - line[after_label..].trim_start().len());
+    let end = start
+        + line[start..]
+            .find(char::is_whitespace)
+            .unwrap_or(line.len() - start);
+
+    match &line[start..end] {
+        "" => None,
+        url if url.starts_with('<') && url.ends_with('>') => Some((start + 1, end - 1)),
+        _ => Some((start, end)),
+    }
+}
+
+/// Push the line to `result`, with the URLs of inline links made absolute
+fn absolutize_inline_links(line: &str, base_url: &str, result: &mut String) {
+    let mut rest = line;
+
+    while !rest.is_empty() {
+        let next_code = rest.find('`');
+        let next_link = rest.find("](");
+
+        match (next_code, next_link) {
+            // A code span comes first, which is copied as it is
+            (Some(code), link) if link.is_none_or(|link| code < link) => {
+                let ticks = rest[code..].chars().take_while(|c| *c == '`').count();
+                let delimiter = "`".repeat(ticks);
+                let content_start = code + ticks;
+                let end = rest[content_start..]
+                    .find(&delimiter)
+                    .map_or(rest.len(), |idx| content_start + idx + ticks);
+                result.push_str(&rest[..end]);
+                rest = &rest[end..];
+            }
+            (_, Some(link)) => {
+                let url_start = link + 2;
+                result.push_str(&rest[..url_start]);
+                rest = &rest[url_start..];
+
+                let (start, end) = inline_link_url(rest);
+                result.push_str(&rest[..start]);
+                result.push_str(&absolutize(&rest[start..end], base_url));
+                rest = &rest[end..];
+            }
+            (_, None) => {
+                result.push_str(rest);
+                rest = "";
+            }
+        }
+    }
+}
+
+/// The byte range of the URL at the start of the destination of an inline link
+fn inline_link_url(destination: &str) -> (usize, usize) {
+    if let Some(inner) = destination.strip_prefix('<') {
+        return (1, 1 + inner.find('>').unwrap_or(inner.len()));
+    }
+
+    // Parentheses in the URL are allowed if they are balanced
+    let mut depth = 0usize;
+    let end = destination
+        .char_indices()
+        .find(|(_, c)| match c {
+            '(' => {
+                depth += 1;
+                false
+            }
+            ')' if depth == 0 => true,
+            ')' => {
+                depth -= 1;
+                false
+            }
+            c => c.is_whitespace(),
+        })
+        .map_or(destination.len(), |(idx, _)| idx);
+    (0, end)
+}
+
+/// Prefix the URL with `base_url`, if it is relative
+fn absolutize(url: &str, base_url: &str) -> String {
+    if is_relative(url) {
+        let path = url.trim_start_matches("./").trim_start_matches('/');
+        format!("{}/{}", base_url.trim_end_matches('/'), path)
+    } else {
+        url.to_string()
+    }
+}
+
+fn is_relative(url: &str) -> bool {
+    let has_scheme = url.split_once(':').is_some_and(|(scheme, _)| {
+        scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+            && scheme
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+    });
+
+    !url.is_empty() && !url.starts_with('#') && !url.starts_with("//") && !has_scheme
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BASE_URL: &str = "https://github.com/owner/repo/blob/main/";
+
+    #[test]
+    fn test_absolutize_inline_links() {
+        let text = indoc::indoc!(
+            r#"
+            See [the docs](docs/usage.md "Usage") and ![logo](./assets/logo.png).
+            Also [root](/README.md), [anchor](#usage), [web](https://example.com),
+            [mail](mailto:me@example.com), [angle](<docs/with space.md>) and [fn](docs/a_(b).md).
+            "#
+        );
+
+        assert_eq!(
+            absolutize_links(text, BASE_URL),
+            indoc::indoc!(
+                r#"
+                See [the docs](https://github.com/owner/repo/blob/main/docs/usage.md "Usage") and ![logo](https://github.com/owner/repo/blob/main/assets/logo.png).
+                Also [root](https://github.com/owner/repo/blob/main/README.md), [anchor](#usage), [web](https://example.com),
+                [mail](mailto:me@example.com), [angle](<https://github.com/owner/repo/blob/main/docs/with space.md>) and [fn](https://github.com/owner/repo/blob/main/docs/a_(b).md).
+                "#
+            )
+        );
+    }
+
+    #[test]
+    fn test_absolutize_reference_definitions() {
+        let text = "[docs]: docs/usage.md\n[web]: https://example.com\n";
+        assert_eq!(
+            absolutize_links(text, BASE_URL),
+            "[docs]: https://github.com/owner/repo/blob/main/docs/usage.md\n[web]: https://example.com\n"
+        );
+    }
+
+    #[test]
+    fn test_code_is_left_untouched() {
+        let text = indoc::indoc!(
+            "
+            Use `[x](y)` or ``[a](b)``, but [this](z.md).
+            ```markdown
+            [link](docs/usage.md)
+            ```
+            "
+        );
+
+        assert_eq!(
+            absolutize_links(text, BASE_URL),
+            indoc::indoc!(
+                "
+                Use `[x](y)` or ``[a](b)``, but [this](https://github.com/owner/repo/blob/main/z.md).
+                ```markdown
+                [link](docs/usage.md)
+                ```
+                "
+            )
+        );
+    }
+}
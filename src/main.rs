@@ -12,11 +12,19 @@ mod cli;
 mod command;
 mod config;
 mod consts;
+mod database;
 mod error;
 mod format;
 mod fragment;
+mod frontmatter;
+#[cfg(feature = "github")]
+mod github;
+mod ignore_file;
+mod line_ending;
+mod links;
 mod selector;
 mod template;
+mod text_provider;
 mod util;
 
 use crate::cli::Command;
@@ -30,7 +38,9 @@ fn main() -> miette::Result<std::process::ExitCode> {
         .with_default_directive(args.verbose.tracing_level_filter().into())
         .from_env_lossy();
 
+    // Log to stderr, so that logging does not interfere with the output of commands on stdout
     let fmt_layer = tracing_subscriber::fmt::layer()
+        .with_writer(std::io::stderr)
         .with_timer(tracing_subscriber::fmt::time::uptime())
         .with_level(true)
         .with_file(true)
@@ -42,6 +52,13 @@ fn main() -> miette::Result<std::process::ExitCode> {
         .with(fmt_layer)
         .init();
 
+    // Completions neither need a repository nor a configuration
+    if let Command::GenerationCompletions { shell } = args.command {
+        let mut cmd = Args::command();
+        generate(shell, &mut cmd, "cargo-changelog", &mut io::stdout());
+        return Ok(std::process::ExitCode::SUCCESS);
+    }
+
     let cwd = std::env::current_dir()
         .map_err(Error::from)
         .into_diagnostic()?;
@@ -56,11 +73,31 @@ fn main() -> miette::Result<std::process::ExitCode> {
         .into_diagnostic()?
         .to_path_buf();
 
-    if let Command::Init = args.command {
-        return init(repo_workdir_path).map(|_| std::process::ExitCode::SUCCESS);
+    if let Command::Init { toc } = args.command {
+        return init(repo_workdir_path, toc).map(|_| std::process::ExitCode::SUCCESS);
     }
 
-    let config = crate::config::load(&repo_workdir_path)?;
+    let mut config = crate::config::load(
+        &repo_workdir_path,
+        args.config.as_deref(),
+        args.config_format,
+    )?;
+    if let Some(fragment_dir) = args.fragment_dir {
+        config.set_fragment_dir(fragment_dir);
+    }
+    // The changelog path from the command line takes precedence over the environment
+    let changelog = args.changelog.or_else(|| {
+        std::env::var_os(crate::consts::CHANGELOG_OUTPUT_ENV_VAR)
+            .filter(|path| !path.is_empty())
+            .map(PathBuf::from)
+    });
+    let changelog_is_explicit = changelog.is_some();
+    if let Some(changelog) = changelog {
+        config.set_changelog(changelog);
+    }
+    if let Some(threads) = args.threads {
+        config.set_threads(threads);
+    }
 
     if !config.fragment_dir().exists() {
         let fragment_dir_path = {
@@ -74,66 +111,182 @@ fn main() -> miette::Result<std::process::ExitCode> {
             .into_diagnostic()?;
     }
 
+    let quiet = args.verbose.is_silent();
     let opt_exit_code = match args.command {
-        Command::Init => unreachable!(), // reached above
+        Command::Init { .. } => unreachable!(), // reached above
 
         Command::Add {
             interactive,
             edit,
             format,
             read,
+            text,
             set,
             git,
-        } => crate::command::AddCommand::builder()
-            .interactive(interactive)
-            .edit(edit)
-            .format(format)
-            .text(read)
-            .set(set)
-            .git(git)
-            .build()
-            .execute(&repo_workdir_path, &config)?,
+            bump,
+            create_dirs,
+            hash_name,
+            dry_run,
+            #[cfg(feature = "github")]
+            from_pr,
+        } => {
+            #[cfg(feature = "github")]
+            let (set, text) = match from_pr {
+                Some(number) => crate::github::prefill(&repository, &config, number, set, text)
+                    .into_diagnostic()?,
+                None => (set, text),
+            };
+
+            crate::command::AddCommand::builder()
+                .quiet(quiet)
+                .interactive(interactive)
+                .edit(edit)
+                .no_interactive(args.no_interactive)
+                .format(format)
+                .read(read)
+                .text(text)
+                .set(set)
+                .git(git)
+                .bump(bump)
+                .create_dirs(create_dirs)
+                .hash_name(hash_name)
+                .dry_run(dry_run)
+                .build()
+                .execute(&repo_workdir_path, &config)?
+        }
 
-        Command::VerifyMetadata => crate::command::VerifyMetadataCommand::builder()
-            .build()
-            .execute(&repo_workdir_path, &config)?,
+        Command::VerifyMetadata { format, files } => {
+            crate::command::VerifyMetadataCommand::builder()
+                .format(format.unwrap_or_default())
+                .files(files)
+                .build()
+                .execute(&repo_workdir_path, &config)?
+        }
 
         Command::CreateRelease(version) => crate::command::CreateReleaseCommand::builder()
             .version(version)
             .build()
             .execute(&repo_workdir_path, &config)?,
 
-        Command::GenerateChangelog { all, allow_dirty } => {
+        Command::GenerateChangelog {
+            all,
+            include_unreleased,
+            allow_dirty,
+            commit,
+            tag,
+            check,
+            template,
+            format,
+            author,
+            audience,
+            base_url,
+            outputs,
+            manifest,
+            emit_version_only,
+            from,
+            to,
+            open,
+        } => {
+            // A changelog of one author must not overwrite the changelog of the project
+            if author.is_some() && !check && emit_version_only.is_none() && !changelog_is_explicit {
+                return Err(Error::AuthorWithoutOutput.into());
+            }
+
             crate::command::GenerateChangelogCommand::builder()
                 .repository(repository)
                 .all(all)
+                .include_unreleased(include_unreleased)
                 .allow_dirty(allow_dirty)
+                .commit(commit)
+                .tag(tag)
+                .check(check)
+                .template(template)
+                .preset(format)
+                .author(author)
+                .audience(audience)
+                .base_url(base_url)
+                .outputs(outputs)
+                .manifest(manifest)
+                .emit_version_only(emit_version_only)
+                .range(
+                    crate::command::VersionRange::builder()
+                        .from(from)
+                        .to(to)
+                        .build(),
+                )
+                .open(open && !args.no_interactive)
                 .build()
                 .execute(&repo_workdir_path, &config)?
         }
 
-        Command::Show { format, selector } => crate::command::Show::builder()
+        Command::Show {
+            format,
+            no_color,
+            author,
+            selector,
+        } => crate::command::Show::builder()
             .format(format)
             .selector(selector)
+            .no_color(no_color)
+            .author(author)
             .build()
             .execute(&repo_workdir_path, &config)?,
-        Command::GenerationCompletions { shell } => {
-            let mut cmd = Args::command();
-            generate(shell, &mut cmd, "cargo-changelog", &mut io::stdout());
-            None
-        }
+        Command::GenerationCompletions { .. } => unreachable!(), // reached above
 
         Command::Has { format, selector } => crate::command::HasCommand::builder()
             .format(format)
             .selector(selector)
             .build()
             .execute(&repo_workdir_path, &config)?,
+
+        Command::Move {
+            fragment,
+            new_version,
+        } => crate::command::MoveCommand::builder()
+            .fragment(fragment)
+            .version(new_version)
+            .build()
+            .execute(&repo_workdir_path, &config)?,
+
+        Command::Schema => crate::command::SchemaCommand::builder()
+            .build()
+            .execute(&repo_workdir_path, &config)?,
+
+        Command::ListVersions { format } => crate::command::ListVersionsCommand::builder()
+            .format(format)
+            .build()
+            .execute(&repo_workdir_path, &config)?,
+
+        Command::Stats { format } => crate::command::StatsCommand::builder()
+            .format(format)
+            .build()
+            .execute(&repo_workdir_path, &config)?,
+
+        Command::Edit { fragment } => crate::command::EditCommand::builder()
+            .fragment(fragment)
+            .no_interactive(args.no_interactive)
+            .build()
+            .execute(&repo_workdir_path, &config)?,
+
+        Command::AddType { name } => crate::command::AddTypeCommand::builder()
+            .name(name)
+            .config_file(crate::config::find_config_file(
+                &repo_workdir_path,
+                args.config.as_deref(),
+            )?)
+            .config_format(args.config_format)
+            .build()
+            .execute(&repo_workdir_path, &config)?,
+
+        Command::Compact => crate::command::CompactCommand::builder()
+            .build()
+            .execute(&repo_workdir_path, &config)?,
     };
 
     Ok(opt_exit_code.unwrap_or(std::process::ExitCode::SUCCESS))
 }
 
-fn init(repo_workdir_path: PathBuf) -> miette::Result<()> {
+fn init(repo_workdir_path: PathBuf, toc: bool) -> miette::Result<()> {
     use std::io::Write;
 
     let unreleased_dir_path = repo_workdir_path
@@ -179,7 +332,12 @@ fn init(repo_workdir_path: PathBuf) -> miette::Result<()> {
         .map_err(Error::from)
         .into_diagnostic()?;
 
-    write!(&mut template_file, "{}", crate::consts::DEFAULT_TEMPLATE)
+    let template = if toc {
+        crate::consts::TOC_TEMPLATE
+    } else {
+        crate::consts::DEFAULT_TEMPLATE
+    };
+    write!(&mut template_file, "{template}")
         .map_err(Error::from)
         .into_diagnostic()?;
 
@@ -1,6 +1,14 @@
 use std::path::{Path, PathBuf};
 
-use crate::{cli::Selector, error::Error};
+use crate::{
+    cli::Selector,
+    config::{Configuration, Layout},
+    error::Error,
+    fragment::Fragment,
+};
+
+/// Decides whether a fragment with the given version is selected
+type VersionFilter = Box<dyn Fn(Option<&semver::Version>) -> bool>;
 
 pub struct SelectorExecutor<'sel> {
     selector: Option<&'sel Selector>,
@@ -11,11 +19,11 @@ impl<'sel> SelectorExecutor<'sel> {
         Self { selector }
     }
 
-    pub fn run(
-        &self,
-        workdir: &Path,
-        config: &crate::config::Configuration,
-    ) -> Result<Vec<PathBuf>, Error> {
+    pub fn run(&self, workdir: &Path, config: &Configuration) -> Result<Vec<PathBuf>, Error> {
+        if config.layout() == Layout::Flat {
+            return self.run_flat(workdir, config);
+        }
+
         match self.selector.as_ref() {
             None | Some(Selector::Unreleased) => {
                 tracing::debug!("Showing unreleased");
@@ -23,7 +31,7 @@ impl<'sel> SelectorExecutor<'sel> {
                     .join(config.fragment_dir())
                     .join(crate::consts::UNRELEASED_DIR_NAME);
 
-                Self::walk_dir(unreleased_dir_path)
+                Self::walk_dir(unreleased_dir_path, config, 1)
                     .filter_map(Self::result_dir_entry_to_pathbuf)
                     .filter(|r| !Self::is_gitkeep(r))
                     .collect::<Result<Vec<PathBuf>, Error>>()
@@ -36,7 +44,7 @@ impl<'sel> SelectorExecutor<'sel> {
                     return Ok(vec![]);
                 }
 
-                Self::walk_dir(path)
+                Self::walk_dir(path, config, 1)
                     .filter_map(Self::result_dir_entry_to_pathbuf)
                     .filter(|r| !Self::is_gitkeep(r))
                     .collect::<Result<Vec<PathBuf>, Error>>()
@@ -48,7 +56,7 @@ impl<'sel> SelectorExecutor<'sel> {
 
                 let fragment_dir_path = workdir.join(config.fragment_dir());
 
-                Self::walk_dir(fragment_dir_path)
+                Self::walk_dir(fragment_dir_path, config, 0)
                     .filter_entry(|de| {
                         tracing::debug!("Looking at {de:?}");
                         if de.path().is_dir() {
@@ -78,9 +86,54 @@ impl<'sel> SelectorExecutor<'sel> {
         }
     }
 
-    fn walk_dir(path: PathBuf) -> walkdir::IntoIter {
+    /// Select from the flat fragment directory by the version header of each fragment
+    fn run_flat(&self, workdir: &Path, config: &Configuration) -> Result<Vec<PathBuf>, Error> {
+        let matches: VersionFilter = match self.selector {
+            None | Some(Selector::Unreleased) => {
+                tracing::debug!("Showing unreleased");
+                Box::new(|version| version.is_none())
+            }
+            Some(Selector::Exact { exact }) => {
+                tracing::debug!("Showing exact {exact}");
+                let exact = semver::Version::parse(exact)?;
+                Box::new(move |version| version == Some(&exact))
+            }
+            Some(Selector::Range { from, until }) => {
+                tracing::debug!("Showing range from {from} until {until}");
+                let from = semver::Version::parse(from)?;
+                let until = semver::Version::parse(until)?;
+                Box::new(move |version| version.is_some_and(|v| *v > from && *v < until))
+            }
+        };
+
+        let fragment_dir_path = workdir.join(config.fragment_dir());
+        let flat_dir_path = fragment_dir_path.join(crate::consts::FLAT_DIR_NAME);
+        if !flat_dir_path.exists() {
+            return Ok(vec![]);
+        }
+
+        let mut pathes = Vec::new();
+        for rpath in Self::walk_dir(flat_dir_path, config, 1)
+            .filter_map(Self::result_dir_entry_to_pathbuf)
+            .filter(|r| !Self::is_gitkeep(r))
+        {
+            let path = rpath?;
+            let fragment = Fragment::from_path(&path)?;
+            let relative_path = path.strip_prefix(&fragment_dir_path).unwrap_or(&path);
+            let version =
+                crate::command::get_version_of_fragment(relative_path, &fragment, Layout::Flat)?;
+            if matches(version.as_ref()) {
+                pathes.push(path);
+            }
+        }
+        Ok(pathes)
+    }
+
+    /// Walk the directory at `path`, which is `depth` levels below the fragment directory
+    fn walk_dir(path: PathBuf, config: &Configuration, depth: usize) -> walkdir::IntoIter {
         walkdir::WalkDir::new(path)
-            .follow_links(false)
+            .follow_links(config.follow_symlinks())
+            .max_depth(config.max_walk_depth().saturating_sub(depth))
             .max_open(100)
             .same_file_system(true)
             .into_iter()
@@ -0,0 +1,59 @@
+use handlebars::{
+    Context, Handlebars, Helper, HelperDef, RenderContext, RenderError, RenderErrorReason,
+    ScopedJson,
+};
+
+use serde_json::Value;
+
+/// Look up the display name of a version in the `version_display_map` of the template data
+///
+/// Versions without a display name are displayed as they are.
+#[derive(Clone, Copy)]
+pub struct DisplayVersionHelper;
+
+impl HelperDef for DisplayVersionHelper {
+    fn call_inner<'reg: 'rc, 'rc>(
+        &self,
+        h: &Helper<'rc>,
+        _: &'reg Handlebars<'reg>,
+        ctx: &'rc Context,
+        _: &mut RenderContext<'reg, 'rc>,
+    ) -> Result<handlebars::ScopedJson<'rc>, RenderError> {
+        let version = h
+            .param(0)
+            .map(|p| p.value())
+            .ok_or_else(|| RenderErrorReason::ParamNotFoundForIndex("display_version", 0))?
+            .as_str()
+            .ok_or_else(|| RenderErrorReason::InvalidParamType("string"))?;
+
+        let display = ctx
+            .data()
+            .get("version_display_map")
+            .and_then(|names| names.get(version))
+            .and_then(Value::as_str)
+            .unwrap_or(version);
+
+        Ok(ScopedJson::Derived(Value::String(display.to_string())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn test_display_version() {
+        let handlebars = crate::template::new_handlebars(
+            "{{#each versions}}{{display_version this}};{{/each}}",
+            false,
+        )
+        .unwrap();
+        let data = serde_json::json!({
+            "versions": ["24.4.0", "24.10.0"],
+            "version_display_map": { "24.4.0": "Spring 2024" },
+        });
+
+        let rendered = handlebars
+            .render(crate::consts::INTERNAL_TEMPLATE_NAME, &data)
+            .unwrap();
+        assert_eq!(rendered, "Spring 2024;24.10.0;");
+    }
+}
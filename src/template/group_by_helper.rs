@@ -3,9 +3,12 @@ use handlebars::{
     ScopedJson,
 };
 
-use itertools::Itertools;
+use indexmap::IndexMap;
 use serde_json::Value;
 
+/// The group that entries of unlisted groups are moved to, if `group_fallback` is not set
+const DEFAULT_GROUP_FALLBACK: &str = "other";
+
 #[derive(Clone, Copy)]
 pub struct GroupByHelper;
 
@@ -14,7 +17,7 @@ impl HelperDef for GroupByHelper {
         &self,
         h: &Helper<'rc>,
         _: &'reg Handlebars<'reg>,
-        _: &'rc Context,
+        ctx: &'rc Context,
         _: &mut RenderContext<'reg, 'rc>,
     ) -> Result<handlebars::ScopedJson<'rc>, RenderError> {
         let group_by_attr = h
@@ -37,39 +40,120 @@ impl HelperDef for GroupByHelper {
         match h.param(0).map(|p| p.value()) {
             None => Err(RenderErrorReason::ParamNotFoundForIndex("group_by_header", 0).into()),
             Some(Value::Array(list)) => {
-                let mut res: serde_json::Map<String, _> = serde_json::Map::new();
-
-                let object_list = list
-                    .iter()
-                    .map(|elt| match elt {
-                        serde_json::Value::Object(map) => {
-                            Ok(serde_json::Value::Object(map.clone()))
-                        }
-                        _other => Err(RenderErrorReason::InvalidParamType("array of object")),
-                    })
-                    .collect::<Result<Vec<serde_json::Value>, RenderErrorReason>>()?;
-
-                for (group, list) in object_list
-                    .into_iter()
-                    .chunk_by(|elt: &serde_json::Value| {
-                        elt.get("header")
-                            .and_then(|hdr| hdr.get(group_by_attr))
-                            .cloned()
-                    })
-                    .into_iter()
-                {
-                    let list = list.into_iter().collect();
-                    let group = group.map(|v| v.to_string()).or_else(|| unknown_group.clone()).ok_or_else(|| {
+                let mut groups = IndexMap::<String, Vec<Value>>::new();
+
+                for elt in list {
+                    if !elt.is_object() {
+                        return Err(RenderErrorReason::InvalidParamType("array of object").into());
+                    }
+
+                    let group = elt.get("header").and_then(|hdr| hdr.get(group_by_attr));
+                    // Strings are used as they are, not as quoted JSON strings
+                    let group = group.map(|v| v.as_str().map_or_else(|| v.to_string(), String::from)).or_else(|| unknown_group.clone()).ok_or_else(|| {
                         RenderErrorReason::Other(format!("Failed to group by '{group_by_attr}', not all elements in the list have that attribute,\
                                 and no default has been given! Use `group_by_header default=<name>` to set a default. List: {list:?}"))
                     })?;
 
-                    res.insert(group, serde_json::Value::Array(list));
+                    groups.entry(group).or_default().push(elt.clone());
                 }
 
-                Ok(ScopedJson::Derived(serde_json::Value::from(res)))
+                let groups = match ctx.data().get("group_order").and_then(Value::as_array) {
+                    Some(group_order) if !group_order.is_empty() => {
+                        let fallback = ctx
+                            .data()
+                            .get("group_fallback")
+                            .and_then(Value::as_str)
+                            .unwrap_or(DEFAULT_GROUP_FALLBACK);
+                        order_groups(groups, group_order, fallback)
+                    }
+                    _ => {
+                        groups.sort_keys();
+                        groups
+                    }
+                };
+
+                let res = groups
+                    .into_iter()
+                    .map(|(group, list)| (group, Value::Array(list)))
+                    .collect::<serde_json::Map<_, _>>();
+                Ok(ScopedJson::Derived(Value::Object(res)))
             }
             Some(_other) => Err(RenderErrorReason::InvalidParamType("array of object").into()),
         }
     }
 }
+
+/// Order the groups as listed in `group_order`, moving the entries of all unlisted groups into the
+/// `fallback` group
+///
+/// The fallback group comes last, unless it is listed. Empty groups are left out.
+fn order_groups(
+    groups: IndexMap<String, Vec<Value>>,
+    group_order: &[Value],
+    fallback: &str,
+) -> IndexMap<String, Vec<Value>> {
+    let mut ordered = group_order
+        .iter()
+        .filter_map(Value::as_str)
+        .map(|group| (group.to_string(), Vec::new()))
+        .collect::<IndexMap<_, _>>();
+    ordered.entry(fallback.to_string()).or_default();
+
+    for (group, list) in groups {
+        match ordered.get_mut(&group) {
+            Some(entries) => entries.extend(list),
+            None => ordered[fallback].extend(list),
+        }
+    }
+
+    ordered.retain(|_, entries| !entries.is_empty());
+    ordered
+}
+
+#[cfg(test)]
+mod tests {
+    fn render(data: serde_json::Value) -> String {
+        let handlebars = crate::template::new_handlebars(
+            "{{#each (group_by_header this.entries \"type\" default=\"Misc\")}}{{@key}}: {{#each this}}{{this.text}} {{/each}}\n{{/each}}",
+            false,
+        )
+        .unwrap();
+        handlebars
+            .render(crate::consts::INTERNAL_TEMPLATE_NAME, &data)
+            .unwrap()
+    }
+
+    fn entries() -> serde_json::Value {
+        serde_json::json!([
+            { "header": { "type": "Bugfix" }, "text": "a" },
+            { "header": { "type": "Feature" }, "text": "b" },
+            { "header": { "type": "Bugfix" }, "text": "c" },
+            { "header": { "type": "Docs" }, "text": "d" },
+            { "header": {}, "text": "e" },
+        ])
+    }
+
+    #[test]
+    fn test_groups_are_sorted() {
+        assert_eq!(
+            render(serde_json::json!({ "entries": entries() })),
+            "Bugfix: a c \nDocs: d \nFeature: b \nMisc: e \n"
+        );
+    }
+
+    #[test]
+    fn test_group_order() {
+        let data = serde_json::json!({
+            "entries": entries(),
+            "group_order": ["Feature", "Security", "other", "Bugfix"],
+        });
+        assert_eq!(render(data), "Feature: b \nother: d e \nBugfix: a c \n");
+
+        let data = serde_json::json!({
+            "entries": entries(),
+            "group_order": ["Feature", "Bugfix"],
+            "group_fallback": "Misc",
+        });
+        assert_eq!(render(data), "Feature: b \nBugfix: a c \nMisc: d e \n");
+    }
+}
@@ -0,0 +1,56 @@
+use handlebars::{
+    Context, Handlebars, Helper, HelperDef, RenderContext, RenderError, RenderErrorReason,
+    ScopedJson,
+};
+
+use serde_json::Value;
+
+/// Look up the display heading of a group in the `group_headings` of the template data
+///
+/// Groups without a heading are displayed as they are.
+#[derive(Clone, Copy)]
+pub struct GroupHeadingHelper;
+
+impl HelperDef for GroupHeadingHelper {
+    fn call_inner<'reg: 'rc, 'rc>(
+        &self,
+        h: &Helper<'rc>,
+        _: &'reg Handlebars<'reg>,
+        ctx: &'rc Context,
+        _: &mut RenderContext<'reg, 'rc>,
+    ) -> Result<handlebars::ScopedJson<'rc>, RenderError> {
+        let group = h
+            .param(0)
+            .map(|p| p.value())
+            .ok_or_else(|| RenderErrorReason::ParamNotFoundForIndex("group_heading", 0))?
+            .as_str()
+            .ok_or_else(|| RenderErrorReason::InvalidParamType("string"))?;
+
+        let heading = ctx
+            .data()
+            .get("group_headings")
+            .and_then(|headings| headings.get(group))
+            .and_then(Value::as_str)
+            .unwrap_or(group);
+
+        Ok(ScopedJson::Derived(Value::String(heading.to_string())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn test_group_heading() {
+        let handlebars = crate::template::new_handlebars(
+            "{{group_heading \"Feature\"}}, {{group_heading \"Misc\"}}",
+            false,
+        )
+        .unwrap();
+        let data = serde_json::json!({ "group_headings": { "Feature": "🚀 Features" } });
+
+        let rendered = handlebars
+            .render(crate::consts::INTERNAL_TEMPLATE_NAME, &data)
+            .unwrap();
+        assert_eq!(rendered, "🚀 Features, Misc");
+    }
+}
@@ -2,14 +2,23 @@ use handlebars::Handlebars;
 
 use crate::error::Error;
 
+mod display_version_helper;
 mod group_by_helper;
+mod group_heading_helper;
 mod indent_helper;
 mod reverse_helper;
 mod sort_versions_helper;
+mod time_ago_helper;
 
-pub fn new_handlebars(template_source: &str) -> Result<Handlebars, Error> {
+/// Create a handlebars registry with all helpers and the template registered as
+/// [`INTERNAL_TEMPLATE_NAME`](crate::consts::INTERNAL_TEMPLATE_NAME)
+///
+/// In `strict` mode, rendering fails if the template references a variable that does not exist,
+/// instead of rendering it as empty string.
+pub fn new_handlebars(template_source: &str, strict: bool) -> Result<Handlebars<'static>, Error> {
     let mut handlebars = Handlebars::new();
     handlebars.register_escape_fn(handlebars::no_escape);
+    handlebars.set_strict_mode(strict);
     handlebars
         .register_template_string(crate::consts::INTERNAL_TEMPLATE_NAME, template_source)
         .map_err(Box::new)?;
@@ -22,6 +31,50 @@ pub fn new_handlebars(template_source: &str) -> Result<Handlebars, Error> {
         "group_by_header",
         Box::new(self::group_by_helper::GroupByHelper),
     );
+    handlebars.register_helper(
+        "group_heading",
+        Box::new(self::group_heading_helper::GroupHeadingHelper),
+    );
+    handlebars.register_helper(
+        "display_version",
+        Box::new(self::display_version_helper::DisplayVersionHelper),
+    );
     handlebars.register_helper("indent", Box::new(self::indent_helper::IndentHelper));
+    handlebars.register_helper("time_ago", Box::new(self::time_ago_helper::TimeAgoHelper));
     Ok(handlebars)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_internal_template_name_does_not_clash() {
+        let mut handlebars = new_handlebars("{{> template}}", false).unwrap();
+        handlebars
+            .register_partial("template", "partial content")
+            .unwrap();
+
+        let rendered = handlebars
+            .render(crate::consts::INTERNAL_TEMPLATE_NAME, &())
+            .unwrap();
+        assert_eq!(rendered, "partial content");
+    }
+
+    #[test]
+    fn test_strict_mode_fails_for_missing_variable() {
+        let data = serde_json::json!({ "version": "0.1.0" });
+
+        let lenient = new_handlebars("v{{version}} {{verison}}", false).unwrap();
+        let rendered = lenient
+            .render(crate::consts::INTERNAL_TEMPLATE_NAME, &data)
+            .unwrap();
+        assert_eq!(rendered, "v0.1.0 ");
+
+        let strict = new_handlebars("v{{version}} {{verison}}", true).unwrap();
+        let error = strict
+            .render(crate::consts::INTERNAL_TEMPLATE_NAME, &data)
+            .unwrap_err();
+        assert!(error.to_string().contains("verison"), "{error}");
+    }
+}
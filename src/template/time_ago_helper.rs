@@ -0,0 +1,119 @@
+use handlebars::{
+    Context, Handlebars, Helper, HelperDef, RenderContext, RenderError, RenderErrorReason,
+    ScopedJson,
+};
+
+use serde_json::Value;
+use time::{Date, OffsetDateTime};
+
+/// Render the time from a date to now, e.g. "3 months ago"
+///
+/// The date is either an RFC 3339 timestamp or a plain `YYYY-MM-DD` date. Only whole days are
+/// considered, so everything on the current (UTC) day is "today".
+#[derive(Clone, Copy)]
+pub struct TimeAgoHelper;
+
+impl HelperDef for TimeAgoHelper {
+    fn call_inner<'reg: 'rc, 'rc>(
+        &self,
+        h: &Helper<'rc>,
+        _: &'reg Handlebars<'reg>,
+        _: &'rc Context,
+        _: &mut RenderContext<'reg, 'rc>,
+    ) -> Result<handlebars::ScopedJson<'rc>, RenderError> {
+        let date = h
+            .param(0)
+            .map(|p| p.value())
+            .ok_or_else(|| RenderErrorReason::ParamNotFoundForIndex("time_ago", 0))?
+            .as_str()
+            .ok_or_else(|| RenderErrorReason::InvalidParamType("string"))?;
+
+        let date = parse_date(date)
+            .ok_or_else(|| RenderErrorReason::Other(format!("time_ago: not a date: \"{date}\"")))?;
+
+        Ok(ScopedJson::Derived(Value::String(time_ago(
+            date,
+            OffsetDateTime::now_utc().date(),
+        ))))
+    }
+}
+
+fn parse_date(date: &str) -> Option<Date> {
+    OffsetDateTime::parse(date, &time::format_description::well_known::Rfc3339)
+        .map(|datetime| datetime.to_offset(time::UtcOffset::UTC).date())
+        .or_else(|_| {
+            Date::parse(
+                date,
+                time::macros::format_description!("[year]-[month]-[day]"),
+            )
+        })
+        .ok()
+}
+
+fn time_ago(date: Date, today: Date) -> String {
+    let days = (today - date).whole_days();
+    let (amount, unit) = match days.unsigned_abs() {
+        0 => return "today".to_string(),
+        1 if days > 0 => return "yesterday".to_string(),
+        1 => return "tomorrow".to_string(),
+        d @ 2..=6 => (d, "day"),
+        d @ 7..=29 => (d / 7, "week"),
+        d @ 30..=364 => ((d / 30).min(11), "month"),
+        d => (d / 365, "year"),
+    };
+    let plural = if amount == 1 { "" } else { "s" };
+
+    if days > 0 {
+        format!("{amount} {unit}{plural} ago")
+    } else {
+        format!("in {amount} {unit}{plural}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::macros::date;
+
+    #[test]
+    fn test_time_ago() {
+        let today = date!(2024 - 06 - 15);
+        assert_eq!(time_ago(date!(2024 - 06 - 15), today), "today");
+        assert_eq!(time_ago(date!(2024 - 06 - 14), today), "yesterday");
+        assert_eq!(time_ago(date!(2024 - 06 - 12), today), "3 days ago");
+        assert_eq!(time_ago(date!(2024 - 06 - 08), today), "1 week ago");
+        assert_eq!(time_ago(date!(2024 - 05 - 20), today), "3 weeks ago");
+        assert_eq!(time_ago(date!(2024 - 05 - 15), today), "1 month ago");
+        assert_eq!(time_ago(date!(2024 - 03 - 01), today), "3 months ago");
+        assert_eq!(time_ago(date!(2023 - 06 - 01), today), "1 year ago");
+        assert_eq!(time_ago(date!(2020 - 01 - 01), today), "4 years ago");
+    }
+
+    #[test]
+    fn test_time_ago_future() {
+        let today = date!(2024 - 06 - 15);
+        assert_eq!(time_ago(date!(2024 - 06 - 16), today), "tomorrow");
+        assert_eq!(time_ago(date!(2024 - 06 - 20), today), "in 5 days");
+        assert_eq!(time_ago(date!(2026 - 07 - 01), today), "in 2 years");
+    }
+
+    #[test]
+    fn test_parse_date() {
+        assert_eq!(parse_date("2024-06-15"), Some(date!(2024 - 06 - 15)));
+        assert_eq!(
+            parse_date("2024-06-15T23:30:00-02:00"),
+            Some(date!(2024 - 06 - 16))
+        );
+        assert_eq!(parse_date("last tuesday"), None);
+    }
+
+    #[test]
+    fn test_time_ago_helper_rejects_invalid_date() {
+        let handlebars =
+            crate::template::new_handlebars("{{time_ago \"last tuesday\"}}", false).unwrap();
+        let error = handlebars
+            .render(crate::consts::INTERNAL_TEMPLATE_NAME, &())
+            .unwrap_err();
+        assert!(error.to_string().contains("not a date"), "{error}");
+    }
+}
@@ -0,0 +1,238 @@
+use std::path::Path;
+use std::path::PathBuf;
+use std::process::Command;
+
+use crate::error::Error;
+use crate::error::TextProviderError;
+use crate::fragment::Fragment;
+
+/// Something that provides the text of a new changelog fragment
+pub trait TextProvider: std::fmt::Debug {
+    /// Provide the text of `fragment`
+    ///
+    /// When this is called, the fragment is already written to `fragment_path`.
+    fn provide(&self, fragment: &mut Fragment, fragment_path: &Path) -> Result<(), Error>;
+}
+
+/// Read the text from stdin
+#[derive(Debug)]
+pub struct StdinTextProvider;
+
+impl TextProvider for StdinTextProvider {
+    fn provide(&self, fragment: &mut Fragment, _: &Path) -> Result<(), Error> {
+        use std::io::Read;
+
+        let mut buf = Vec::new();
+        std::io::stdin()
+            .read_to_end(&mut buf)
+            .map_err(TextProviderError::from)?;
+
+        let text = String::from_utf8(buf).map_err(TextProviderError::from)?;
+        fragment.set_text(text);
+        Ok(())
+    }
+}
+
+/// Read the text from a file
+#[derive(Debug)]
+pub struct PathTextProvider(pub PathBuf);
+
+impl TextProvider for PathTextProvider {
+    fn provide(&self, fragment: &mut Fragment, _: &Path) -> Result<(), Error> {
+        let text = std::fs::read_to_string(&self.0).map_err(TextProviderError::from)?;
+        fragment.set_text(text);
+        Ok(())
+    }
+}
+
+/// Use a text that is already known, e.g. from the commandline
+#[derive(Debug)]
+pub struct LiteralTextProvider(pub String);
+
+impl TextProvider for LiteralTextProvider {
+    fn provide(&self, fragment: &mut Fragment, _: &Path) -> Result<(), Error> {
+        fragment.set_text(self.0.clone());
+        Ok(())
+    }
+}
+
+/// Open the fragment file in $EDITOR (or $VISUAL)
+///
+/// Because the whole file is edited, this can change the header of the fragment as well.
+#[derive(Debug)]
+pub struct EditorTextProvider;
+
+impl TextProvider for EditorTextProvider {
+    fn provide(&self, fragment: &mut Fragment, fragment_path: &Path) -> Result<(), Error> {
+        let mut editor_command = get_editor_command()?;
+        let std::process::Output { status, .. } = editor_command
+            .arg(fragment_path)
+            .stderr(std::process::Stdio::inherit())
+            .stdout(std::process::Stdio::inherit())
+            .output()?;
+
+        if status.success() {
+            tracing::info!("Successfully edited");
+        } else {
+            tracing::error!("Failure editing {}", fragment_path.display());
+        }
+
+        *fragment = Fragment::from_path(fragment_path)?;
+        Ok(())
+    }
+}
+
+/// The command of the editor in $EDITOR (or $VISUAL), without the file to edit
+pub(crate) fn get_editor_command() -> Result<Command, Error> {
+    let editor = match std::env::var("EDITOR") {
+        Ok(editor) => editor,
+        Err(std::env::VarError::NotPresent) => match std::env::var("VISUAL") {
+            Ok(editor) => editor,
+            Err(std::env::VarError::NotPresent) => return Err(Error::EditorEnvNotSet),
+            Err(std::env::VarError::NotUnicode(_)) => {
+                return Err(Error::EnvNotUnicode("VISUAL".to_string()))
+            }
+        },
+        Err(std::env::VarError::NotUnicode(_)) => {
+            return Err(Error::EnvNotUnicode("EDITOR".to_string()))
+        }
+    };
+
+    let (program, args) = editor_command_line(&editor).ok_or(Error::EditorEnvNotSet)?;
+    let mut command = Command::new(program);
+    command.args(args);
+    Ok(command)
+}
+
+/// GUI editors that return immediately unless they are passed a flag to wait for the file to be
+/// closed, with that flag
+const GUI_EDITOR_WAIT_FLAGS: &[(&str, &str, &[&str])] = &[
+    ("code", "--wait", &["--wait", "-w"]),
+    ("code-insiders", "--wait", &["--wait", "-w"]),
+    ("codium", "--wait", &["--wait", "-w"]),
+    ("subl", "-w", &["--wait", "-w"]),
+    ("atom", "--wait", &["--wait", "-w"]),
+    ("mate", "-w", &["--wait", "-w"]),
+    ("zed", "--wait", &["--wait"]),
+];
+
+/// Split the editor command into program and arguments
+///
+/// If the program is a known GUI editor, its wait flag is appended, unless it is already given.
+/// Returns `None` if the command is empty.
+fn editor_command_line(editor: &str) -> Option<(String, Vec<String>)> {
+    let mut parts = split_command_line(editor).into_iter();
+    let program = parts.next()?;
+    let mut args = parts.collect::<Vec<_>>();
+
+    // Windows paths are handled on all platforms, as is the ".exe" extension
+    let file_name = program.rsplit(['/', '\\']).next().unwrap_or(&program);
+    let name = Path::new(file_name)
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or(file_name);
+    if let Some((_, flag, known_flags)) = GUI_EDITOR_WAIT_FLAGS
+        .iter()
+        .find(|(editor, _, _)| editor.eq_ignore_ascii_case(name))
+    {
+        if !args.iter().any(|arg| known_flags.contains(&arg.as_str())) {
+            tracing::debug!(
+                "Passing {} to {} to wait for the file to be closed",
+                flag,
+                program
+            );
+            args.push(flag.to_string());
+        }
+    }
+
+    Some((program, args))
+}
+
+/// Split a command line into its arguments at whitespace, keeping quoted parts together
+///
+/// Both single and double quotes are supported. Backslashes are not treated as escape characters,
+/// so Windows paths like `"C:\Program Files\Notepad++\notepad++.exe"` can be used as they are.
+fn split_command_line(command_line: &str) -> Vec<String> {
+    let mut args = Vec::new();
+    let mut current = None::<String>;
+    let mut quote = None;
+
+    for c in command_line.chars() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), c) => current.get_or_insert_with(String::new).push(c),
+            (None, '"' | '\'') => {
+                quote = Some(c);
+                current.get_or_insert_with(String::new);
+            }
+            (None, c) if c.is_whitespace() => args.extend(current.take()),
+            (None, c) => current.get_or_insert_with(String::new).push(c),
+        }
+    }
+    args.extend(current);
+    args
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn command_line(program: &str, args: &[&str]) -> Option<(String, Vec<String>)> {
+        Some((
+            program.to_string(),
+            args.iter().map(ToString::to_string).collect(),
+        ))
+    }
+
+    #[test]
+    fn test_editor_command_line() {
+        assert_eq!(editor_command_line("vim"), command_line("vim", &[]));
+        assert_eq!(
+            editor_command_line("nvim -u NONE"),
+            command_line("nvim", &["-u", "NONE"])
+        );
+        assert_eq!(
+            editor_command_line("code"),
+            command_line("code", &["--wait"])
+        );
+        assert_eq!(
+            editor_command_line("/usr/local/bin/subl"),
+            command_line("/usr/local/bin/subl", &["-w"])
+        );
+        assert_eq!(
+            editor_command_line("code -w --new-window"),
+            command_line("code", &["-w", "--new-window"])
+        );
+        assert_eq!(
+            editor_command_line("subl --wait"),
+            command_line("subl", &["--wait"])
+        );
+        assert_eq!(editor_command_line("  "), None);
+    }
+
+    #[test]
+    fn test_editor_command_line_windows() {
+        assert_eq!(
+            editor_command_line(r#""C:\Program Files\Notepad++\notepad++.exe" -multiInst"#),
+            command_line(r"C:\Program Files\Notepad++\notepad++.exe", &["-multiInst"])
+        );
+        assert_eq!(
+            editor_command_line(r"C:\Users\me\AppData\Local\Programs\Code.exe"),
+            command_line(r"C:\Users\me\AppData\Local\Programs\Code.exe", &["--wait"])
+        );
+    }
+
+    #[test]
+    fn test_split_command_line() {
+        assert_eq!(split_command_line("a  b\tc"), vec!["a", "b", "c"]);
+        assert_eq!(
+            split_command_line(r#"'my editor' "--opt=a b" """#),
+            vec!["my editor", "--opt=a b", ""]
+        );
+        assert_eq!(
+            split_command_line(r"C:\vim\vim.exe"),
+            vec![r"C:\vim\vim.exe"]
+        );
+        assert!(split_command_line(" ").is_empty());
+    }
+}
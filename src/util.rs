@@ -22,3 +22,71 @@ pub fn repo_is_dirty(repo: &git2::Repository) -> bool {
         true
     }
 }
+
+/// Get the paths of all changes that are staged in the index
+pub fn staged_paths(repo: &git2::Repository) -> Result<Vec<String>, git2::Error> {
+    let staged = git2::Status::INDEX_NEW
+        | git2::Status::INDEX_MODIFIED
+        | git2::Status::INDEX_DELETED
+        | git2::Status::INDEX_RENAMED
+        | git2::Status::INDEX_TYPECHANGE;
+
+    let statuses = repo.statuses(Some(git2::StatusOptions::new().include_ignored(false)))?;
+    Ok(statuses
+        .iter()
+        .filter(|s| s.status().intersects(staged))
+        .filter_map(|s| s.path().map(|s| s.to_owned()))
+        .collect())
+}
+
+/// Find the commit that added each file in `dir`, by their path relative to the repository root
+///
+/// The history is walked once, from the oldest commit on. Renamed files (e.g. moved by
+/// `create-release`) keep the commit that added them under their old path.
+pub fn commits_adding_files(
+    repo: &git2::Repository,
+    dir: &std::path::Path,
+) -> Result<std::collections::HashMap<std::path::PathBuf, git2::Oid>, git2::Error> {
+    let mut commits = std::collections::HashMap::new();
+    if repo.head().is_err() {
+        // Nothing is committed yet
+        return Ok(commits);
+    }
+
+    let mut revwalk = repo.revwalk()?;
+    revwalk.push_head()?;
+    revwalk.set_sorting(git2::Sort::TOPOLOGICAL | git2::Sort::REVERSE)?;
+
+    for oid in revwalk {
+        let commit = repo.find_commit(oid?)?;
+        let parent_tree = match commit.parents().next() {
+            Some(parent) => Some(parent.tree()?),
+            None => None,
+        };
+
+        let mut diff = repo.diff_tree_to_tree(
+            parent_tree.as_ref(),
+            Some(&commit.tree()?),
+            Some(git2::DiffOptions::new().pathspec(dir)),
+        )?;
+        diff.find_similar(Some(git2::DiffFindOptions::new().renames(true)))?;
+
+        for delta in diff.deltas() {
+            let Some(path) = delta.new_file().path() else {
+                continue;
+            };
+            let added_by = match delta.status() {
+                git2::Delta::Added | git2::Delta::Copied => commit.id(),
+                git2::Delta::Renamed => delta
+                    .old_file()
+                    .path()
+                    .and_then(|old| commits.get(old).copied())
+                    .unwrap_or(commit.id()),
+                _ => continue,
+            };
+            commits.entry(path.to_path_buf()).or_insert(added_by);
+        }
+    }
+
+    Ok(commits)
+}
@@ -318,3 +318,641 @@ fn add_command_cannot_create_nonexistent_oneof() {
         .assert()
         .failure();
 }
+
+#[test]
+fn add_command_with_bump_creates_fragment_in_next_version_dir() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("cargo-changelog")
+        .tempdir()
+        .unwrap();
+    self::common::init_git(temp_dir.path());
+    self::common::init_cargo(temp_dir.path(), "add_command_with_bump");
+    self::common::init_cargo_changelog(temp_dir.path());
+
+    self::common::cargo_changelog_add(temp_dir.path())
+        .args([
+            "--format=toml",
+            "--set",
+            "issue=123",
+            "--set",
+            "subject='This is some text'",
+            "--set",
+            "type=Bugfix",
+            "--bump",
+            "minor",
+        ])
+        .assert()
+        .success();
+
+    let version_dir = temp_dir.path().join(".changelogs").join("0.2.0");
+    if !version_dir.exists() {
+        panic!("Version directory '0.2.0' does not exist");
+    }
+
+    let files = std::fs::read_dir(&version_dir).unwrap().collect::<Vec<_>>();
+    assert_eq!(
+        files.len(),
+        1,
+        "Expected 1 entry in version directory, found {}: {:?}",
+        files.len(),
+        files
+    );
+}
+
+#[test]
+fn add_command_without_create_dirs_fails_for_missing_version_dir() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("cargo-changelog")
+        .tempdir()
+        .unwrap();
+    self::common::init_git(temp_dir.path());
+    self::common::init_cargo(temp_dir.path(), "add_command_without_create_dirs");
+    self::common::init_cargo_changelog(temp_dir.path());
+
+    self::common::cargo_changelog_add(temp_dir.path())
+        .args([
+            "--format=toml",
+            "--set",
+            "issue=123",
+            "--set",
+            "subject='This is some text'",
+            "--set",
+            "type=Bugfix",
+            "--bump",
+            "minor",
+            "--create-dirs=false",
+        ])
+        .assert()
+        .failure();
+
+    let version_dir = temp_dir.path().join(".changelogs").join("0.2.0");
+    assert!(!version_dir.exists(), "Version directory was created");
+}
+
+#[test]
+fn add_command_with_text_creates_toml_with_literal_text() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("cargo-changelog")
+        .tempdir()
+        .unwrap();
+    self::common::init_git(temp_dir.path());
+    self::common::init_cargo_changelog(temp_dir.path());
+
+    self::common::cargo_changelog_add(temp_dir.path())
+        .args([
+            "--format=toml",
+            "--set",
+            "issue=123",
+            "--set",
+            "subject='This is some text'",
+            "--set",
+            "type=Bugfix",
+            "--text",
+            "This is the entry text",
+        ])
+        .assert()
+        .success();
+
+    let fragment_file = std::fs::read_dir(temp_dir.path().join(".changelogs").join("unreleased"))
+        .unwrap()
+        .find(|rde| match rde {
+            Ok(de) => !de.path().ends_with(".gitkeep"),
+            Err(_) => true,
+        })
+        .unwrap()
+        .unwrap();
+
+    let new_fragment_file_contents = std::fs::read_to_string(fragment_file.path()).unwrap();
+    assert!(
+        new_fragment_file_contents.ends_with("+++\nThis is the entry text\n"),
+        "Unexpected contents: {new_fragment_file_contents}"
+    );
+}
+
+#[test]
+fn add_command_concurrent_invocations_do_not_clobber() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("cargo-changelog")
+        .tempdir()
+        .unwrap();
+    self::common::init_git(temp_dir.path());
+    self::common::init_cargo_changelog(temp_dir.path());
+
+    let binary = assert_cmd::cargo::cargo_bin("cargo-changelog");
+    let children = (0..8)
+        .map(|i| {
+            std::process::Command::new(&binary)
+                .args([
+                    "add",
+                    "--interactive=false",
+                    "--edit=false",
+                    "--set",
+                    "issue=123",
+                    "--set",
+                    &format!("subject='Subject {i}'"),
+                    "--set",
+                    "type=Misc",
+                ])
+                .current_dir(&temp_dir)
+                .spawn()
+                .unwrap()
+        })
+        .collect::<Vec<_>>();
+
+    for mut child in children {
+        assert!(child.wait().unwrap().success());
+    }
+
+    let unreleased_dir = temp_dir.path().join(".changelogs").join("unreleased");
+    let subjects = std::fs::read_dir(unreleased_dir)
+        .unwrap()
+        .map(|de| de.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "md"))
+        .map(|path| {
+            let content = std::fs::read_to_string(path).unwrap();
+            content
+                .lines()
+                .find(|line| line.starts_with("subject"))
+                .unwrap()
+                .to_string()
+        })
+        .collect::<std::collections::HashSet<_>>();
+    assert_eq!(subjects.len(), 8, "Fragments were lost: {subjects:?}");
+}
+
+#[test]
+fn add_command_prints_summary_unless_quiet() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("cargo-changelog")
+        .tempdir()
+        .unwrap();
+    self::common::init_git(temp_dir.path());
+    self::common::init_cargo_changelog(temp_dir.path());
+
+    let args = [
+        "--format=toml",
+        "--set",
+        "issue=123",
+        "--set",
+        "subject=Summarized",
+        "--text",
+        "The entry text",
+    ];
+
+    self::common::cargo_changelog_add(temp_dir.path())
+        .args(args)
+        .assert()
+        .success()
+        .stdout(predicates::str::starts_with(
+            "Created .changelogs/unreleased/",
+        ))
+        .stdout(predicates::str::contains("  subject: Summarized\n"))
+        .stdout(predicates::str::contains("  The entry text\n"));
+
+    self::common::cargo_changelog_add(temp_dir.path())
+        .arg("--quiet")
+        .args(args)
+        .assert()
+        .success()
+        .stdout(predicates::str::is_empty());
+}
+
+#[test]
+fn add_command_with_hash_name_rejects_duplicates() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("cargo-changelog")
+        .tempdir()
+        .unwrap();
+    self::common::init_git(temp_dir.path());
+    self::common::init_cargo_changelog(temp_dir.path());
+
+    let args = [
+        "--format=toml",
+        "--hash-name",
+        "--set",
+        "issue=123",
+        "--set",
+        "subject=Duplicate",
+        "--text",
+        "The entry text",
+    ];
+
+    self::common::cargo_changelog_add(temp_dir.path())
+        .args(args)
+        .assert()
+        .success();
+
+    self::common::cargo_changelog_add(temp_dir.path())
+        .args(args)
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("Fragment already exists"));
+
+    let fragments = std::fs::read_dir(temp_dir.path().join(".changelogs").join("unreleased"))
+        .unwrap()
+        .map(|rde| rde.unwrap().file_name().into_string().unwrap())
+        .filter(|name| name != ".gitkeep")
+        .collect::<Vec<_>>();
+    assert_eq!(fragments.len(), 1, "Unexpected fragments: {fragments:?}");
+    assert_eq!(fragments[0].len(), "0123456789ab.md".len());
+}
+
+#[test]
+fn add_command_dry_run_does_not_create_anything() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("cargo-changelog")
+        .tempdir()
+        .unwrap();
+    self::common::init_git(temp_dir.path());
+    self::common::init_cargo(temp_dir.path(), "add_command_dry_run");
+    self::common::init_cargo_changelog(temp_dir.path());
+
+    self::common::cargo_changelog_add(temp_dir.path())
+        .args([
+            "--format=toml",
+            "--set",
+            "issue=123",
+            "--set",
+            "subject='This is some text'",
+            "--set",
+            "type=Bugfix",
+            "--bump",
+            "minor",
+            "--dry-run",
+        ])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Would create .changelogs/0.2.0/"))
+        .stdout(predicates::str::contains(
+            "Would create directory .changelogs/0.2.0\n",
+        ))
+        .stdout(predicates::str::contains("+++\n"))
+        .stdout(predicates::str::contains("issue = 123\n"))
+        .stdout(predicates::str::contains("<text of the changelog entry>"));
+
+    let version_dir = temp_dir.path().join(".changelogs").join("0.2.0");
+    assert!(!version_dir.exists(), "Version directory was created");
+
+    let unreleased_dir = temp_dir.path().join(".changelogs").join("unreleased");
+    let files = std::fs::read_dir(&unreleased_dir)
+        .unwrap()
+        .filter_map(Result::ok)
+        .filter(|de| de.path().extension().is_some_and(|ext| ext == "md"))
+        .count();
+    assert_eq!(files, 0, "Fragment was created");
+}
+
+/// The contents of the fragment files in the unreleased directory
+fn unreleased_fragment_contents(dir: &std::path::Path) -> Vec<String> {
+    let unreleased_dir = dir.join(".changelogs").join("unreleased");
+    std::fs::read_dir(unreleased_dir)
+        .unwrap()
+        .map(|rde| rde.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "md"))
+        .map(|path| std::fs::read_to_string(path).unwrap())
+        .collect()
+}
+
+#[test]
+fn add_command_writes_created_field() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("cargo-changelog")
+        .tempdir()
+        .unwrap();
+    self::common::init_git(temp_dir.path());
+    self::common::init_cargo_changelog(temp_dir.path());
+
+    let add = |args: &[&str]| {
+        self::common::cargo_changelog_add(temp_dir.path())
+            .args([
+                "--format=toml",
+                "--set",
+                "issue=123",
+                "--set",
+                "subject='This is some text'",
+                "--set",
+                "type=Bugfix",
+            ])
+            .args(args)
+            .assert()
+            .success();
+    };
+
+    add(&[]);
+    let contents = unreleased_fragment_contents(temp_dir.path());
+    let created = contents[0]
+        .lines()
+        .find_map(|line| line.strip_prefix("created = \""))
+        .expect("No created field in fragment");
+    assert!(created.ends_with("Z\""), "Not an UTC timestamp: {created}");
+
+    let unreleased_dir = temp_dir.path().join(".changelogs").join("unreleased");
+    std::fs::remove_dir_all(&unreleased_dir).unwrap();
+    std::fs::create_dir(&unreleased_dir).unwrap();
+    add(&["--set", "created=2024-01-31T12:00:00Z"]);
+    let contents = unreleased_fragment_contents(temp_dir.path());
+    assert!(
+        contents[0].contains("created = \"2024-01-31T12:00:00Z\""),
+        "Creation time not overridden: {}",
+        contents[0]
+    );
+
+    std::fs::remove_dir_all(&unreleased_dir).unwrap();
+    std::fs::create_dir(&unreleased_dir).unwrap();
+    let config_file_path = temp_dir.path().join("changelog.toml");
+    let config = std::fs::read_to_string(&config_file_path).unwrap();
+    std::fs::write(
+        config_file_path,
+        format!("record_created = false\n{config}"),
+    )
+    .unwrap();
+    add(&[]);
+    let contents = unreleased_fragment_contents(temp_dir.path());
+    assert!(
+        !contents[0].contains("created ="),
+        "Creation time written: {}",
+        contents[0]
+    );
+}
+
+#[test]
+fn add_command_writes_header_fields_in_declared_order() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("cargo-changelog")
+        .tempdir()
+        .unwrap();
+    self::common::init_git(temp_dir.path());
+    self::common::init_cargo_changelog(temp_dir.path());
+
+    self::common::cargo_changelog_add(temp_dir.path())
+        .args([
+            "--format=toml",
+            "--set",
+            "type=Bugfix",
+            "--set",
+            "issue=123",
+            "--set",
+            "subject='This is some text'",
+            "--set",
+            "created=2024-01-31T12:00:00Z",
+        ])
+        .assert()
+        .success();
+
+    let contents = unreleased_fragment_contents(temp_dir.path());
+    let header = contents[0].split("+++\n").nth(1).unwrap();
+    assert_eq!(
+        header,
+        concat!(
+            "subject = \"'This is some text'\"\n",
+            "issue = 123\n",
+            "type = \"Bugfix\"\n",
+            "created = \"2024-01-31T12:00:00Z\"\n",
+        )
+    );
+}
+
+/// Write a virtual workspace with `[workspace.package]` version "0.3.0" and the given members
+fn init_workspace(dir: &std::path::Path, members: &[(&str, &str)]) {
+    let names = members
+        .iter()
+        .map(|(name, _)| format!("{name:?}"))
+        .collect::<Vec<_>>()
+        .join(", ");
+    std::fs::write(
+        dir.join("Cargo.toml"),
+        format!(
+            "[workspace]\nmembers = [{names}]\nresolver = \"2\"\n\n[workspace.package]\nversion = \"0.3.0\"\n"
+        ),
+    )
+    .unwrap();
+    for (name, version) in members {
+        let crate_dir = dir.join(name);
+        std::fs::create_dir_all(crate_dir.join("src")).unwrap();
+        std::fs::write(crate_dir.join("src").join("lib.rs"), "").unwrap();
+        std::fs::write(
+            crate_dir.join("Cargo.toml"),
+            format!("[package]\nname = \"{name}\"\n{version}\nedition = \"2021\"\n"),
+        )
+        .unwrap();
+    }
+}
+
+fn add_with_minor_bump(dir: &std::path::Path) {
+    self::common::cargo_changelog_add(dir)
+        .args([
+            "--format=toml",
+            "--set",
+            "issue=123",
+            "--set",
+            "subject='This is some text'",
+            "--set",
+            "type=Bugfix",
+            "--bump",
+            "minor",
+        ])
+        .assert()
+        .success();
+}
+
+#[test]
+fn add_command_with_bump_uses_inherited_workspace_version() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("cargo-changelog")
+        .tempdir()
+        .unwrap();
+    self::common::init_git(temp_dir.path());
+
+    // A member inheriting the version and an internal crate that does not
+    init_workspace(
+        temp_dir.path(),
+        &[
+            ("inheriting", "version.workspace = true"),
+            ("internal", "version = \"0.1.0\"\npublish = false"),
+        ],
+    );
+    self::common::init_cargo_changelog(temp_dir.path());
+
+    add_with_minor_bump(temp_dir.path());
+
+    let version_dir = temp_dir.path().join(".changelogs").join("0.4.0");
+    assert!(
+        version_dir.exists(),
+        "Version directory '0.4.0' does not exist"
+    );
+}
+
+#[test]
+fn add_command_with_bump_ignores_workspace_version_not_inherited() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("cargo-changelog")
+        .tempdir()
+        .unwrap();
+    self::common::init_git(temp_dir.path());
+
+    init_workspace(
+        temp_dir.path(),
+        &[
+            ("first", "version = \"1.0.0\""),
+            ("second", "version = \"1.0.0\""),
+        ],
+    );
+    self::common::init_cargo_changelog(temp_dir.path());
+
+    add_with_minor_bump(temp_dir.path());
+
+    let version_dir = temp_dir.path().join(".changelogs").join("1.1.0");
+    assert!(
+        version_dir.exists(),
+        "Version directory '1.1.0' does not exist"
+    );
+}
+
+#[test]
+fn add_command_handles_missing_required_value_as_configured() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("cargo-changelog")
+        .tempdir()
+        .unwrap();
+    self::common::init_git(temp_dir.path());
+    self::common::init_cargo_changelog(temp_dir.path());
+
+    // "subject" is required, but not given
+    let add = || {
+        self::common::cargo_changelog_add(temp_dir.path())
+            .args(["--format=toml", "--set", "issue=123", "--set", "type=Misc"])
+            .assert()
+    };
+    let config_file_path = temp_dir.path().join("changelog.toml");
+    let config = std::fs::read_to_string(&config_file_path).unwrap();
+    let set_missing_required = |value: &str| {
+        std::fs::write(
+            &config_file_path,
+            format!("missing_required = \"{value}\"\n{config}"),
+        )
+        .unwrap();
+    };
+
+    let unreleased_dir = temp_dir.path().join(".changelogs").join("unreleased");
+    let clear_unreleased = || {
+        std::fs::remove_dir_all(&unreleased_dir).unwrap();
+        std::fs::create_dir(&unreleased_dir).unwrap();
+    };
+
+    add()
+        .failure()
+        .stderr(predicates::str::contains("Required value 'subject'"));
+
+    clear_unreleased();
+    set_missing_required("warn");
+    add().success();
+    let contents = unreleased_fragment_contents(temp_dir.path());
+    assert!(!contents[0].contains("subject"), "{}", contents[0]);
+
+    clear_unreleased();
+    set_missing_required("placeholder");
+    add().success();
+    let contents = unreleased_fragment_contents(temp_dir.path());
+    assert!(
+        contents[0].contains("subject = \"TODO\""),
+        "{}",
+        contents[0]
+    );
+}
+
+#[test]
+fn add_command_writes_keys_in_configured_case() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("cargo-changelog")
+        .tempdir()
+        .unwrap();
+    self::common::init_git(temp_dir.path());
+    self::common::init_cargo_changelog(temp_dir.path());
+
+    let config_file_path = temp_dir.path().join("changelog.toml");
+    let config = std::fs::read_to_string(&config_file_path).unwrap();
+    std::fs::write(
+        &config_file_path,
+        format!(
+            "key_case = \"kebab\"\n{config}\n[header_fields.pr-number]\ntype = \"int\"\nrequired = false\n"
+        ),
+    )
+    .unwrap();
+
+    // Keys on the commandline are converted before they are matched with the header fields
+    self::common::cargo_changelog_add(temp_dir.path())
+        .args([
+            "--format=toml",
+            "--set",
+            "subject='Test subject'",
+            "--set",
+            "pr_number=42",
+        ])
+        .assert()
+        .success();
+
+    let contents = unreleased_fragment_contents(temp_dir.path());
+    assert!(
+        contents[0].contains("\npr-number = 42\n"),
+        "{}",
+        contents[0]
+    );
+
+    // Header fields in another case would be renamed away from their declaration
+    let config = std::fs::read_to_string(&config_file_path).unwrap();
+    std::fs::write(
+        &config_file_path,
+        format!("{config}\n[header_fields.breaking_change]\ntype = \"bool\"\nrequired = false\n"),
+    )
+    .unwrap();
+
+    self::common::cargo_changelog_add(temp_dir.path())
+        .args(["--format=toml", "--set", "subject='Test subject'"])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains(
+            "Header field 'breaking_change' is not kebab-case",
+        ));
+}
+
+#[test]
+fn add_command_keeps_numeric_looking_string_values() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("cargo-changelog")
+        .tempdir()
+        .unwrap();
+    self::common::init_git(temp_dir.path());
+    self::common::init_cargo_changelog(temp_dir.path());
+
+    self::common::cargo_changelog_add(temp_dir.path())
+        .args(["--format=toml", "--set", "subject=1.5", "--set", "issue=-3"])
+        .assert()
+        .success();
+
+    let contents = unreleased_fragment_contents(temp_dir.path());
+    assert!(
+        contents[0].contains("subject = \"1.5\"\n"),
+        "{}",
+        contents[0]
+    );
+    assert!(contents[0].contains("issue = -3\n"), "{}", contents[0]);
+}
+
+#[cfg(feature = "github")]
+#[test]
+fn add_command_from_pr_without_curl_fails() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("cargo-changelog")
+        .tempdir()
+        .unwrap();
+    self::common::init_git(temp_dir.path());
+    self::common::init_cargo_changelog(temp_dir.path());
+    let empty_path = tempfile::tempdir().unwrap();
+
+    self::common::cargo_changelog_add(temp_dir.path())
+        .env("PATH", empty_path.path())
+        .args(["--format=toml", "--from-pr", "1"])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("curl was not found"));
+}
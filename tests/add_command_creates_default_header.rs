@@ -83,3 +83,99 @@ fn new_command_creates_default_header() {
     assert!(number.is_integer());
     assert_eq!(number.as_integer().unwrap(), 123);
 }
+
+#[test]
+fn new_command_creates_default_header_from_template() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("cargo-changelog")
+        .tempdir()
+        .unwrap();
+    self::common::init_git(temp_dir.path());
+    self::common::init_cargo_changelog(temp_dir.path());
+
+    {
+        let config_file_path = temp_dir.path().join("changelog.toml");
+        let mut file = std::fs::OpenOptions::new()
+            .append(true)
+            .open(config_file_path)
+            .unwrap();
+
+        writeln!(file, "[header_fields.slug]").unwrap();
+        writeln!(file, r#"type = "string""#).unwrap();
+        writeln!(file, r#"default_template = "{{{{issue}}}}-{{{{type}}}}""#).unwrap();
+        writeln!(file, "required = true").unwrap();
+        file.sync_all().unwrap()
+    }
+
+    self::common::cargo_changelog_add(temp_dir.path())
+        .args([
+            "--format=toml",
+            "--set",
+            "issue=123",
+            "--set",
+            "subject=This is some text",
+            "--set",
+            "type=Misc",
+        ])
+        .assert()
+        .success();
+
+    let unreleased_dir = temp_dir.path().join(".changelogs").join("unreleased");
+    let fragment = std::fs::read_dir(unreleased_dir)
+        .unwrap()
+        .find(|rde| match rde {
+            Ok(de) => !de.path().ends_with(".gitkeep"),
+            Err(_) => true,
+        })
+        .unwrap()
+        .unwrap();
+
+    let new_fragment_file_contents = std::fs::read_to_string(fragment.path()).unwrap();
+    let toml_header = new_fragment_file_contents
+        .lines()
+        .skip(1)
+        .take_while(|line| *line != "+++")
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let toml = toml::from_str::<toml::Value>(&toml_header).unwrap();
+    let slug = toml.get("slug").unwrap();
+    assert_eq!(slug.as_str().unwrap(), "123-Misc");
+}
+
+#[test]
+fn new_command_default_header_template_with_missing_field_errors() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("cargo-changelog")
+        .tempdir()
+        .unwrap();
+    self::common::init_git(temp_dir.path());
+    self::common::init_cargo_changelog(temp_dir.path());
+
+    {
+        let config_file_path = temp_dir.path().join("changelog.toml");
+        let mut file = std::fs::OpenOptions::new()
+            .append(true)
+            .open(config_file_path)
+            .unwrap();
+
+        writeln!(file, "[header_fields.slug]").unwrap();
+        writeln!(file, r#"type = "string""#).unwrap();
+        writeln!(file, r#"default_template = "{{{{nonexistent}}}}""#).unwrap();
+        writeln!(file, "required = true").unwrap();
+        file.sync_all().unwrap()
+    }
+
+    self::common::cargo_changelog_add(temp_dir.path())
+        .args([
+            "--format=toml",
+            "--set",
+            "issue=123",
+            "--set",
+            "subject=This is some text",
+            "--set",
+            "type=Misc",
+        ])
+        .assert()
+        .failure();
+}
@@ -0,0 +1,61 @@
+mod common;
+
+#[test]
+fn add_type_command_adds_type_to_configuration() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("cargo-changelog")
+        .tempdir()
+        .unwrap();
+    self::common::init_git(temp_dir.path());
+    self::common::init_cargo_changelog(temp_dir.path());
+
+    self::common::cargo_changelog_cmd(temp_dir.path())
+        .args(["add-type", "Security"])
+        .assert()
+        .success()
+        .stdout("Added type 'Security' to changelog.toml\n");
+
+    let config = std::fs::read_to_string(temp_dir.path().join("changelog.toml")).unwrap();
+    assert!(
+        config.contains(r#"type = [ "Bugfix", "Feature", "Misc", "Security" ]"#),
+        "{config}"
+    );
+    // Comments of the default configuration are kept
+    assert!(
+        config.contains("# A header field named \"type\""),
+        "{config}"
+    );
+
+    self::common::cargo_changelog_add(temp_dir.path())
+        .args([
+            "--format=toml",
+            "--set",
+            "issue=123",
+            "--set",
+            "subject='Test subject'",
+            "--set",
+            "type=Security",
+        ])
+        .assert()
+        .success();
+}
+
+#[test]
+fn add_type_command_refuses_existing_type() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("cargo-changelog")
+        .tempdir()
+        .unwrap();
+    self::common::init_git(temp_dir.path());
+    self::common::init_cargo_changelog(temp_dir.path());
+    let config_before = std::fs::read_to_string(temp_dir.path().join("changelog.toml")).unwrap();
+
+    self::common::cargo_changelog_cmd(temp_dir.path())
+        .args(["add-type", "feature"])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("Type 'feature' already exists"));
+
+    let config_after = std::fs::read_to_string(temp_dir.path().join("changelog.toml")).unwrap();
+    assert_eq!(config_before, config_after);
+}
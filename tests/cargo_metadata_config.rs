@@ -0,0 +1,74 @@
+mod common;
+
+const METADATA_CONFIG: &str = r#"
+[package.metadata.changelog]
+add_version_date = false
+fragment_dir = "notes"
+edit_data = false
+edit_format = "toml"
+git_commit_signoff = false
+
+[package.metadata.changelog.header_fields]
+subject = { type = "string", required = true }
+"#;
+
+fn setup_metadata_config(name: &str) -> tempfile::TempDir {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("cargo-changelog")
+        .tempdir()
+        .unwrap();
+    self::common::init_git(temp_dir.path());
+    self::common::init_cargo(temp_dir.path(), name);
+    self::common::init_cargo_changelog(temp_dir.path());
+
+    let manifest_path = temp_dir.path().join("Cargo.toml");
+    let manifest = std::fs::read_to_string(&manifest_path).unwrap();
+    std::fs::write(manifest_path, format!("{manifest}{METADATA_CONFIG}")).unwrap();
+    temp_dir
+}
+
+fn count_fragments(dir: &std::path::Path) -> usize {
+    std::fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .filter(|de| !de.as_ref().unwrap().path().ends_with(".gitkeep"))
+                .count()
+        })
+        .unwrap_or(0)
+}
+
+#[test]
+fn configuration_is_loaded_from_cargo_metadata() {
+    let temp_dir = setup_metadata_config("configuration_is_loaded_from_cargo_metadata");
+    std::fs::remove_file(temp_dir.path().join("changelog.toml")).unwrap();
+    std::fs::create_dir(temp_dir.path().join("notes")).unwrap();
+
+    self::common::cargo_changelog_add(temp_dir.path())
+        .args(["--set", "subject=Test"])
+        .assert()
+        .success();
+
+    let unreleased_dir = temp_dir.path().join("notes").join("unreleased");
+    assert_eq!(count_fragments(&unreleased_dir), 1);
+}
+
+#[test]
+fn configuration_file_takes_precedence_over_cargo_metadata() {
+    let temp_dir = setup_metadata_config("configuration_file_takes_precedence_over_cargo_metadata");
+
+    self::common::cargo_changelog_add(temp_dir.path())
+        .args([
+            "--set",
+            "issue=123",
+            "--set",
+            "subject=Test",
+            "--set",
+            "type=Misc",
+        ])
+        .assert()
+        .success();
+
+    let unreleased_dir = temp_dir.path().join(".changelogs").join("unreleased");
+    assert_eq!(count_fragments(&unreleased_dir), 1);
+    assert!(!temp_dir.path().join("notes").exists());
+}
@@ -0,0 +1,252 @@
+mod common;
+
+fn add_fragment(dir: &std::path::Path, extra_args: &[&str]) {
+    self::common::cargo_changelog_add(dir)
+        .args([
+            "--format=toml",
+            "--set",
+            "issue=123",
+            "--set",
+            "subject='Test subject'",
+            "--set",
+            "type=Misc",
+        ])
+        .args(extra_args)
+        .assert()
+        .success();
+}
+
+#[test]
+fn config_flag_loads_configuration_from_path() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("cargo-changelog")
+        .tempdir()
+        .unwrap();
+    self::common::init_git(temp_dir.path());
+    self::common::init_cargo_changelog(temp_dir.path());
+
+    let config = std::fs::read_to_string(temp_dir.path().join("changelog.toml")).unwrap();
+    std::fs::remove_file(temp_dir.path().join("changelog.toml")).unwrap();
+    std::fs::write(
+        temp_dir.path().join("custom.toml"),
+        config.replace("fragment_dir = \".changelogs\"", "fragment_dir = \"notes\""),
+    )
+    .unwrap();
+
+    add_fragment(temp_dir.path(), &["--config", "custom.toml"]);
+
+    let unreleased_dir = temp_dir.path().join("notes").join("unreleased");
+    assert_eq!(std::fs::read_dir(unreleased_dir).unwrap().count(), 1);
+}
+
+#[test]
+fn fragment_dir_flag_overrides_configuration() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("cargo-changelog")
+        .tempdir()
+        .unwrap();
+    self::common::init_git(temp_dir.path());
+    self::common::init_cargo_changelog(temp_dir.path());
+
+    add_fragment(temp_dir.path(), &["--fragment-dir", "other"]);
+
+    let unreleased_dir = temp_dir.path().join("other").join("unreleased");
+    assert_eq!(std::fs::read_dir(unreleased_dir).unwrap().count(), 1);
+
+    let default_unreleased_dir = temp_dir.path().join(".changelogs").join("unreleased");
+    let default_count = std::fs::read_dir(default_unreleased_dir)
+        .unwrap()
+        .filter(|de| !de.as_ref().unwrap().path().ends_with(".gitkeep"))
+        .count();
+    assert_eq!(default_count, 0);
+}
+
+fn write_json_config(dir: &std::path::Path, name: &str) {
+    let config_path = dir.join("changelog.toml");
+    let config: toml::Table =
+        toml::from_str(&std::fs::read_to_string(&config_path).unwrap()).unwrap();
+    std::fs::remove_file(config_path).unwrap();
+    std::fs::write(
+        dir.join(name),
+        serde_json::to_string_pretty(&config).unwrap(),
+    )
+    .unwrap();
+}
+
+#[test]
+fn json_configuration_is_discovered() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("cargo-changelog")
+        .tempdir()
+        .unwrap();
+    self::common::init_git(temp_dir.path());
+    self::common::init_cargo_changelog(temp_dir.path());
+    write_json_config(temp_dir.path(), "changelog.json");
+
+    add_fragment(temp_dir.path(), &[]);
+
+    let unreleased_dir = temp_dir.path().join(".changelogs").join("unreleased");
+    assert_eq!(std::fs::read_dir(unreleased_dir).unwrap().count(), 2);
+}
+
+#[test]
+fn config_format_flag_overrides_extension() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("cargo-changelog")
+        .tempdir()
+        .unwrap();
+    self::common::init_git(temp_dir.path());
+    self::common::init_cargo_changelog(temp_dir.path());
+    write_json_config(temp_dir.path(), "custom.conf");
+
+    self::common::cargo_changelog_add(temp_dir.path())
+        .args(["--config", "custom.conf"])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("TOML deserialization error"));
+
+    add_fragment(
+        temp_dir.path(),
+        &["--config", "custom.conf", "--config-format", "json"],
+    );
+}
+
+#[test]
+fn threads_flag_does_not_change_loaded_fragments() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("cargo-changelog")
+        .tempdir()
+        .unwrap();
+    self::common::init_git(temp_dir.path());
+    self::common::init_cargo_changelog(temp_dir.path());
+
+    for _ in 0..5 {
+        add_fragment(temp_dir.path(), &[]);
+    }
+
+    let stats = |threads: &str| {
+        let output = self::common::cargo_changelog_cmd(temp_dir.path())
+            .args(["stats", "--format", "json", "--threads", threads])
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        String::from_utf8(output.stdout).unwrap()
+    };
+
+    assert!(stats("1").starts_with(r#"{"total":5,"#));
+    assert_eq!(stats("1"), stats("3"));
+
+    self::common::cargo_changelog_cmd(temp_dir.path())
+        .args(["stats", "--threads", "0"])
+        .assert()
+        .failure();
+}
+
+#[test]
+fn changelog_flag_and_env_override_configuration() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("cargo-changelog")
+        .tempdir()
+        .unwrap();
+    self::common::init_git(temp_dir.path());
+    self::common::init_cargo_changelog(temp_dir.path());
+    add_fragment(temp_dir.path(), &[]);
+    std::fs::create_dir(temp_dir.path().join("ci")).unwrap();
+
+    self::common::cargo_changelog_cmd(temp_dir.path())
+        .args(["generate-changelog", "--all", "--allow-dirty"])
+        .env("CARGO_CHANGELOG_OUTPUT", "ci/CHANGELOG.md")
+        .assert()
+        .success();
+    assert!(temp_dir.path().join("ci").join("CHANGELOG.md").exists());
+    assert!(!temp_dir.path().join("CHANGELOG.md").exists());
+
+    self::common::cargo_changelog_cmd(temp_dir.path())
+        .args([
+            "generate-changelog",
+            "--all",
+            "--allow-dirty",
+            "--changelog",
+            "FLAG.md",
+        ])
+        .env("CARGO_CHANGELOG_OUTPUT", "ENV.md")
+        .assert()
+        .success();
+    assert!(temp_dir.path().join("FLAG.md").exists());
+    assert!(!temp_dir.path().join("ENV.md").exists());
+}
+
+#[test]
+fn no_interactive_flag_adds_without_prompting() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("cargo-changelog")
+        .tempdir()
+        .unwrap();
+    self::common::init_git(temp_dir.path());
+    self::common::init_cargo_changelog(temp_dir.path());
+
+    // The editor would create the marker file if it was run
+    self::common::cargo_changelog_cmd(temp_dir.path())
+        .args([
+            "add",
+            "--no-interactive",
+            "--set",
+            "issue=123",
+            "--set",
+            "subject='Test subject'",
+            "--set",
+            "type=Misc",
+        ])
+        .env("EDITOR", "touch opened")
+        .assert()
+        .success();
+    assert!(!temp_dir.path().join("opened").exists());
+
+    let unreleased_dir = temp_dir.path().join(".changelogs").join("unreleased");
+    let fragment = std::fs::read_dir(&unreleased_dir)
+        .unwrap()
+        .map(|rde| rde.unwrap().path())
+        .find(|path| path.extension().is_some_and(|ext| ext == "md"))
+        .unwrap();
+    let contents = std::fs::read_to_string(&fragment).unwrap();
+    assert!(
+        contents.contains("subject = \"'Test subject'\""),
+        "{contents}"
+    );
+
+    // "subject" is required
+    self::common::cargo_changelog_cmd(temp_dir.path())
+        .args(["add", "--no-interactive", "--set", "type=Misc"])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("Required value 'subject'"));
+
+    // Explicitly requested prompts fail before any fragment is created
+    let fragment_count = || std::fs::read_dir(&unreleased_dir).unwrap().count();
+    let count = fragment_count();
+    for flag in ["-i", "-e"] {
+        self::common::cargo_changelog_cmd(temp_dir.path())
+            .args([
+                "add",
+                "--no-interactive",
+                flag,
+                "true",
+                "--set",
+                "type=Misc",
+            ])
+            .env("EDITOR", "touch opened")
+            .assert()
+            .failure()
+            .stderr(predicates::str::contains("disabled with --no-interactive"));
+    }
+    assert_eq!(fragment_count(), count);
+
+    self::common::cargo_changelog_cmd(temp_dir.path())
+        .args(["--no-interactive", "edit"])
+        .arg(&fragment)
+        .env("EDITOR", "touch opened")
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("disabled with --no-interactive"));
+    assert!(!temp_dir.path().join("opened").exists());
+}
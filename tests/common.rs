@@ -12,6 +12,23 @@ pub fn init_git(temp_dir: &std::path::Path) {
     }
 }
 
+pub fn init_git_user(temp_dir: &std::path::Path) {
+    for (key, value) in [
+        ("user.name", "cargo-changelog"),
+        ("user.email", "test@example.com"),
+    ] {
+        if !std::process::Command::new("git")
+            .args(["config", key, value])
+            .current_dir(temp_dir)
+            .status()
+            .unwrap()
+            .success()
+        {
+            panic!("Failed to git-config {key}");
+        }
+    }
+}
+
 pub fn init_cargo(temp_dir: &std::path::Path, name: &str) {
     if !std::process::Command::new("cargo")
         .args(["init", "--bin", "--name", name])
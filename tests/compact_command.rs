@@ -0,0 +1,100 @@
+mod common;
+
+fn add_fragment(dir: &std::path::Path, subject: &str) {
+    self::common::cargo_changelog_add(dir)
+        .args([
+            "--format=toml",
+            "--set",
+            "issue=123",
+            "--set",
+            &format!("subject='{subject}'"),
+            "--set",
+            "type=Misc",
+        ])
+        .assert()
+        .success();
+}
+
+fn generate_changelog(dir: &std::path::Path) -> String {
+    self::common::cargo_changelog_cmd(dir)
+        .args(["generate-changelog", "--all"])
+        .assert()
+        .success();
+    std::fs::read_to_string(dir.join("CHANGELOG.md")).unwrap()
+}
+
+#[test]
+fn compact_command_moves_released_fragments_into_database() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("cargo-changelog")
+        .tempdir()
+        .unwrap();
+    self::common::init_git(temp_dir.path());
+    self::common::init_cargo(temp_dir.path(), "compact_command");
+    self::common::init_cargo_changelog(temp_dir.path());
+
+    let config_file_path = temp_dir.path().join("changelog.toml");
+    let config = std::fs::read_to_string(&config_file_path).unwrap();
+    std::fs::write(
+        &config_file_path,
+        format!("database = \"released.jsonl\"\n{config}"),
+    )
+    .unwrap();
+
+    add_fragment(temp_dir.path(), "First released change");
+    add_fragment(temp_dir.path(), "Second released change");
+    self::common::cargo_changelog_cmd(temp_dir.path())
+        .args(["create-release", "minor"])
+        .assert()
+        .success();
+    add_fragment(temp_dir.path(), "Unreleased change");
+
+    let changelog = generate_changelog(temp_dir.path());
+    assert!(changelog.contains("First released change"), "{changelog}");
+
+    self::common::cargo_changelog_cmd(temp_dir.path())
+        .args(["compact"])
+        .assert()
+        .success()
+        .stdout("Compacted 2 fragments into .changelogs/released.jsonl\n");
+
+    let fragment_dir = temp_dir.path().join(".changelogs");
+    let version_dir_files = std::fs::read_dir(fragment_dir.join("0.1.0"))
+        .unwrap()
+        .filter(|rde| {
+            rde.as_ref()
+                .unwrap()
+                .path()
+                .extension()
+                .is_some_and(|ext| ext == "md")
+        })
+        .count();
+    assert_eq!(version_dir_files, 0);
+    let database = std::fs::read_to_string(fragment_dir.join("released.jsonl")).unwrap();
+    assert_eq!(database.lines().count(), 2);
+
+    assert_eq!(generate_changelog(temp_dir.path()), changelog);
+
+    // Compacting again does not duplicate anything
+    self::common::cargo_changelog_cmd(temp_dir.path())
+        .args(["compact"])
+        .assert()
+        .success()
+        .stdout("Compacted 0 fragments into .changelogs/released.jsonl\n");
+    assert_eq!(generate_changelog(temp_dir.path()), changelog);
+}
+
+#[test]
+fn compact_command_fails_without_database() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("cargo-changelog")
+        .tempdir()
+        .unwrap();
+    self::common::init_git(temp_dir.path());
+    self::common::init_cargo_changelog(temp_dir.path());
+
+    self::common::cargo_changelog_cmd(temp_dir.path())
+        .args(["compact"])
+        .assert()
+        .failure();
+}
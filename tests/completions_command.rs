@@ -0,0 +1,19 @@
+use assert_cmd::Command;
+
+#[test]
+fn completions_command_works_outside_of_repository() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("cargo-changelog")
+        .tempdir()
+        .unwrap();
+
+    for shell in ["bash", "zsh", "fish"] {
+        Command::cargo_bin("cargo-changelog")
+            .unwrap()
+            .args(["completions", shell])
+            .current_dir(&temp_dir)
+            .assert()
+            .success()
+            .stdout(predicates::str::contains("generate-changelog"));
+    }
+}
@@ -0,0 +1,88 @@
+mod common;
+
+fn write_editor_script(dir: &std::path::Path, content: &str) -> std::path::PathBuf {
+    use std::os::unix::fs::PermissionsExt;
+
+    let script_path = dir.join("editor");
+    std::fs::write(&script_path, format!("#!/bin/sh\n{content}\n")).unwrap();
+    std::fs::set_permissions(&script_path, std::fs::Permissions::from_mode(0o744)).unwrap();
+    script_path
+}
+
+fn setup() -> (tempfile::TempDir, std::path::PathBuf) {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("cargo-changelog")
+        .tempdir()
+        .unwrap();
+    self::common::init_git(temp_dir.path());
+    self::common::init_cargo_changelog(temp_dir.path());
+
+    let fragment_path = temp_dir
+        .path()
+        .join(".changelogs")
+        .join("unreleased")
+        .join("fix.md");
+    std::fs::write(
+        &fragment_path,
+        "+++\nsubject = \"Original\"\nissue = 1\n+++\nOriginal text\n",
+    )
+    .unwrap();
+    (temp_dir, fragment_path)
+}
+
+#[test]
+fn edit_command_saves_valid_fragment() {
+    let (temp_dir, fragment_path) = setup();
+    let script_dir = tempfile::tempdir().unwrap();
+    let editor = write_editor_script(
+        script_dir.path(),
+        r#"printf '+++\nsubject = "Edited"\nissue = 1\n+++\nEdited text\n' > "$1""#,
+    );
+
+    self::common::cargo_changelog_cmd(temp_dir.path())
+        .env("EDITOR", &editor)
+        .args(["edit", ".changelogs/unreleased/fix.md"])
+        .assert()
+        .success();
+
+    assert_eq!(
+        std::fs::read_to_string(fragment_path).unwrap(),
+        "+++\nsubject = \"Edited\"\nissue = 1\n+++\nEdited text\n"
+    );
+}
+
+#[test]
+fn edit_command_keeps_fragment_if_edit_is_invalid() {
+    let (temp_dir, fragment_path) = setup();
+    let original = std::fs::read_to_string(&fragment_path).unwrap();
+    let script_dir = tempfile::tempdir().unwrap();
+
+    for script in [
+        // Not a fragment at all
+        r#"echo "garbage" > "$1""#,
+        // The issue must be an integer
+        r#"printf '+++\nsubject = "Edited"\nissue = "one"\n+++\n' > "$1""#,
+    ] {
+        let editor = write_editor_script(script_dir.path(), script);
+        self::common::cargo_changelog_cmd(temp_dir.path())
+            .env("EDITOR", &editor)
+            .args(["edit", ".changelogs/unreleased/fix.md"])
+            .assert()
+            .failure();
+
+        assert_eq!(std::fs::read_to_string(&fragment_path).unwrap(), original);
+    }
+}
+
+#[test]
+fn edit_command_refuses_files_outside_of_fragment_dir() {
+    let (temp_dir, _) = setup();
+    std::fs::write(temp_dir.path().join("README.md"), "Readme\n").unwrap();
+
+    self::common::cargo_changelog_cmd(temp_dir.path())
+        .env("EDITOR", "true")
+        .args(["edit", "README.md"])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("Not in the fragment directory"));
+}
@@ -0,0 +1,138 @@
+use assert_cmd::Command;
+
+mod common;
+
+fn set_flat_layout(dir: &std::path::Path) {
+    let config_file_path = dir.join("changelog.toml");
+    let config = std::fs::read_to_string(&config_file_path).unwrap();
+    std::fs::write(config_file_path, format!("layout = \"flat\"\n{config}")).unwrap();
+}
+
+fn add_fragment(dir: &std::path::Path, subject: &str, extra_args: &[&str]) {
+    self::common::cargo_changelog_add(dir)
+        .args([
+            "--format=toml",
+            "--set",
+            "issue=123",
+            "--set",
+            &format!("subject={subject}"),
+            "--set",
+            "type=Misc",
+        ])
+        .args(extra_args)
+        .assert()
+        .success();
+}
+
+#[test]
+fn flat_layout_stores_version_in_header() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("cargo-changelog")
+        .tempdir()
+        .unwrap();
+    self::common::init_git(temp_dir.path());
+    self::common::init_cargo(temp_dir.path(), "flat_layout_stores_version_in_header");
+    self::common::init_cargo_changelog(temp_dir.path());
+    set_flat_layout(temp_dir.path());
+
+    add_fragment(temp_dir.path(), "bumped", &["--bump", "minor"]);
+    add_fragment(temp_dir.path(), "released", &[]);
+
+    let flat_dir = temp_dir.path().join(".changelogs").join("fragments");
+    assert_eq!(std::fs::read_dir(&flat_dir).unwrap().count(), 2);
+
+    Command::cargo_bin("cargo-changelog")
+        .unwrap()
+        .args(["create-release", "custom", "0.3.0"])
+        .current_dir(&temp_dir)
+        .assert()
+        .success();
+
+    // create-release must not move the fragments
+    assert_eq!(std::fs::read_dir(&flat_dir).unwrap().count(), 2);
+
+    Command::cargo_bin("cargo-changelog")
+        .unwrap()
+        .args(["generate-changelog"])
+        .current_dir(&temp_dir)
+        .assert()
+        .success();
+
+    let changelog = std::fs::read_to_string(temp_dir.path().join("CHANGELOG.md")).unwrap();
+    assert!(changelog.contains("## v0.2.0"), "{changelog}");
+    assert!(changelog.contains("## v0.3.0"), "{changelog}");
+    assert!(changelog.contains("bumped"), "{changelog}");
+    assert!(changelog.contains("released"), "{changelog}");
+}
+
+#[test]
+fn flat_layout_with_version_directory_errors() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("cargo-changelog")
+        .tempdir()
+        .unwrap();
+    self::common::init_git(temp_dir.path());
+    self::common::init_cargo(temp_dir.path(), "flat_layout_with_version_directory_errors");
+    self::common::init_cargo_changelog(temp_dir.path());
+
+    add_fragment(temp_dir.path(), "versioned", &[]);
+
+    Command::cargo_bin("cargo-changelog")
+        .unwrap()
+        .args(["create-release", "minor"])
+        .current_dir(&temp_dir)
+        .assert()
+        .success();
+
+    set_flat_layout(temp_dir.path());
+
+    Command::cargo_bin("cargo-changelog")
+        .unwrap()
+        .args(["generate-changelog"])
+        .current_dir(&temp_dir)
+        .assert()
+        .failure();
+}
+
+#[test]
+fn flat_layout_show_uses_version_header() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("cargo-changelog")
+        .tempdir()
+        .unwrap();
+    self::common::init_git(temp_dir.path());
+    self::common::init_cargo(temp_dir.path(), "flat_layout_show_uses_version_header");
+    self::common::init_cargo_changelog(temp_dir.path());
+    set_flat_layout(temp_dir.path());
+
+    add_fragment(temp_dir.path(), "released", &[]);
+    self::common::cargo_changelog_cmd(temp_dir.path())
+        .args(["create-release", "custom", "0.3.0"])
+        .assert()
+        .success();
+
+    add_fragment(temp_dir.path(), "pending", &[]);
+
+    let show = |args: &[&str]| {
+        let output = self::common::cargo_changelog_cmd(temp_dir.path())
+            .args(["show", "--no-color"])
+            .args(args)
+            .assert()
+            .success()
+            .get_output()
+            .stdout
+            .clone();
+        String::from_utf8(output).unwrap()
+    };
+
+    let released = show(&["exact", "0.3.0"]);
+    assert!(released.starts_with("0.3.0\n"), "{released}");
+    assert!(released.contains("released"), "{released}");
+    assert!(!released.contains("unreleased"), "{released}");
+    assert!(!released.contains("pending"), "{released}");
+
+    let unreleased = show(&[]);
+    assert!(unreleased.starts_with("unreleased\n"), "{unreleased}");
+    assert!(unreleased.contains("pending"), "{unreleased}");
+    assert!(!unreleased.contains("0.3.0"), "{unreleased}");
+}
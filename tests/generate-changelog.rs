@@ -356,3 +356,1780 @@ fn generate_changelog_works_with_default_headers() {
         panic!("Changelog is not a file");
     }
 }
+
+#[test]
+fn generate_changelog_command_commits_release() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("cargo-changelog")
+        .tempdir()
+        .unwrap();
+    self::common::init_git(temp_dir.path());
+    self::common::init_git_user(temp_dir.path());
+    self::common::init_cargo(
+        temp_dir.path(),
+        "generate_changelog_command_commits_release",
+    );
+    self::common::init_cargo_changelog(temp_dir.path());
+
+    self::common::cargo_changelog_add(temp_dir.path())
+        .args([
+            "--format=toml",
+            "--set",
+            "issue=123",
+            "--set",
+            "subject='Test subject'",
+            "--set",
+            "type=Misc",
+        ])
+        .assert()
+        .success();
+
+    Command::cargo_bin("cargo-changelog")
+        .unwrap()
+        .args(["create-release", "minor"])
+        .current_dir(&temp_dir)
+        .assert()
+        .success();
+
+    Command::cargo_bin("cargo-changelog")
+        .unwrap()
+        .args(["generate-changelog", "--commit"])
+        .current_dir(&temp_dir)
+        .assert()
+        .success();
+
+    let log = std::process::Command::new("git")
+        .args(["log", "--format=%s", "-1"])
+        .current_dir(&temp_dir)
+        .output()
+        .unwrap();
+    assert_eq!(String::from_utf8(log.stdout).unwrap(), "Release v0.1.0\n");
+
+    let status = std::process::Command::new("git")
+        .args(["status", "--porcelain", "CHANGELOG.md", ".changelogs"])
+        .current_dir(&temp_dir)
+        .output()
+        .unwrap();
+    assert!(
+        status.stdout.is_empty(),
+        "Changes not committed: {}",
+        String::from_utf8_lossy(&status.stdout)
+    );
+}
+
+#[test]
+fn generate_changelog_command_commit_refuses_unrelated_staged_changes() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("cargo-changelog")
+        .tempdir()
+        .unwrap();
+    self::common::init_git(temp_dir.path());
+    self::common::init_git_user(temp_dir.path());
+    self::common::init_cargo(
+        temp_dir.path(),
+        "generate_changelog_command_commit_refuses_unrelated_staged_changes",
+    );
+    self::common::init_cargo_changelog(temp_dir.path());
+
+    assert!(std::process::Command::new("git")
+        .args(["add", "Cargo.toml"])
+        .current_dir(&temp_dir)
+        .status()
+        .unwrap()
+        .success());
+
+    Command::cargo_bin("cargo-changelog")
+        .unwrap()
+        .args(["generate-changelog", "--commit"])
+        .current_dir(&temp_dir)
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("unrelated to the changelog"));
+}
+
+#[test]
+fn generate_changelog_command_tags_release() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("cargo-changelog")
+        .tempdir()
+        .unwrap();
+    self::common::init_git(temp_dir.path());
+    self::common::init_git_user(temp_dir.path());
+    self::common::init_cargo(temp_dir.path(), "generate_changelog_command_tags_release");
+    self::common::init_cargo_changelog(temp_dir.path());
+
+    self::common::cargo_changelog_add(temp_dir.path())
+        .args([
+            "--format=toml",
+            "--set",
+            "issue=123",
+            "--set",
+            "subject='Test subject'",
+            "--set",
+            "type=Misc",
+        ])
+        .assert()
+        .success();
+
+    Command::cargo_bin("cargo-changelog")
+        .unwrap()
+        .args(["create-release", "minor"])
+        .current_dir(&temp_dir)
+        .assert()
+        .success();
+
+    Command::cargo_bin("cargo-changelog")
+        .unwrap()
+        .args(["generate-changelog", "--commit", "--tag"])
+        .current_dir(&temp_dir)
+        .assert()
+        .success();
+
+    let tags = std::process::Command::new("git")
+        .args(["tag", "--points-at", "HEAD"])
+        .current_dir(&temp_dir)
+        .output()
+        .unwrap();
+    assert_eq!(String::from_utf8(tags.stdout).unwrap(), "v0.1.0\n");
+
+    // The tag exists now, so tagging again must fail
+    Command::cargo_bin("cargo-changelog")
+        .unwrap()
+        .args(["generate-changelog", "--commit", "--tag"])
+        .current_dir(&temp_dir)
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("already exists"));
+}
+
+fn generate_changelog_with_line_ending(name: &str, line_ending: &str) -> String {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("cargo-changelog")
+        .tempdir()
+        .unwrap();
+    self::common::init_git(temp_dir.path());
+    self::common::init_cargo(temp_dir.path(), name);
+    self::common::init_cargo_changelog(temp_dir.path());
+
+    let config_file_path = temp_dir.path().join("changelog.toml");
+    let config = std::fs::read_to_string(&config_file_path).unwrap();
+    std::fs::write(
+        config_file_path,
+        format!("line_ending = \"{line_ending}\"\n{config}"),
+    )
+    .unwrap();
+
+    self::common::cargo_changelog_add(temp_dir.path())
+        .args([
+            "--format=toml",
+            "--set",
+            "issue=123",
+            "--set",
+            "subject='Test subject'",
+            "--set",
+            "type=Misc",
+        ])
+        .assert()
+        .success();
+
+    Command::cargo_bin("cargo-changelog")
+        .unwrap()
+        .args(["generate-changelog", "--all"])
+        .current_dir(&temp_dir)
+        .assert()
+        .success();
+
+    std::fs::read_to_string(temp_dir.path().join("CHANGELOG.md")).unwrap()
+}
+
+#[test]
+fn generate_changelog_command_uses_lf_line_endings() {
+    let changelog = generate_changelog_with_line_ending(
+        "generate_changelog_command_uses_lf_line_endings",
+        "lf",
+    );
+
+    assert!(!changelog.contains('\r'), "Found CR: {changelog:?}");
+    assert!(
+        changelog.ends_with("\n"),
+        "No trailing newline: {changelog:?}"
+    );
+    assert!(
+        !changelog.ends_with("\n\n"),
+        "More than one trailing newline: {changelog:?}"
+    );
+}
+
+#[test]
+fn generate_changelog_command_uses_crlf_line_endings() {
+    let changelog = generate_changelog_with_line_ending(
+        "generate_changelog_command_uses_crlf_line_endings",
+        "crlf",
+    );
+
+    assert!(
+        changelog.lines().count() > 1,
+        "Expected multiple lines: {changelog:?}"
+    );
+    assert_eq!(
+        changelog.matches('\n').count(),
+        changelog.matches("\r\n").count(),
+        "Found LF without CR: {changelog:?}"
+    );
+    assert!(
+        changelog.ends_with("\r\n"),
+        "No trailing newline: {changelog:?}"
+    );
+    assert!(
+        !changelog.ends_with("\r\n\r\n"),
+        "More than one trailing newline: {changelog:?}"
+    );
+}
+
+#[test]
+fn generate_changelog_command_exposes_source_path() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("cargo-changelog")
+        .tempdir()
+        .unwrap();
+    self::common::init_git(temp_dir.path());
+    self::common::init_cargo(
+        temp_dir.path(),
+        "generate_changelog_command_exposes_source_path",
+    );
+    self::common::init_cargo_changelog(temp_dir.path());
+
+    std::fs::write(
+        temp_dir.path().join(".changelogs").join("template.md"),
+        "{{#each versions}}{{#each this.entries}}{{this.source_path}}\n{{/each}}{{/each}}",
+    )
+    .unwrap();
+
+    self::common::cargo_changelog_add(temp_dir.path())
+        .args([
+            "--format=toml",
+            "--set",
+            "issue=123",
+            "--set",
+            "subject='Test subject'",
+            "--set",
+            "type=Misc",
+        ])
+        .assert()
+        .success();
+
+    Command::cargo_bin("cargo-changelog")
+        .unwrap()
+        .args(["generate-changelog", "--all"])
+        .current_dir(&temp_dir)
+        .assert()
+        .success();
+
+    let changelog = std::fs::read_to_string(temp_dir.path().join("CHANGELOG.md")).unwrap();
+    let expected_dir = std::path::Path::new(".changelogs")
+        .join("unreleased")
+        .display()
+        .to_string();
+    assert!(
+        changelog.starts_with(&expected_dir),
+        "Expected relative source path: {changelog:?}"
+    );
+    assert!(changelog.trim_end().ends_with(".md"), "{changelog:?}");
+}
+
+#[test]
+fn generate_changelog_command_marks_yanked_version() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("cargo-changelog")
+        .tempdir()
+        .unwrap();
+    self::common::init_git(temp_dir.path());
+    self::common::init_cargo(
+        temp_dir.path(),
+        "generate_changelog_command_marks_yanked_version",
+    );
+    self::common::init_cargo_changelog(temp_dir.path());
+
+    self::common::cargo_changelog_add(temp_dir.path())
+        .args([
+            "--format=toml",
+            "--set",
+            "issue=123",
+            "--set",
+            "subject='Test subject'",
+            "--set",
+            "type=Misc",
+        ])
+        .assert()
+        .success();
+
+    // `create-release` uses the version from the Cargo.toml, which is 0.1.0
+    Command::cargo_bin("cargo-changelog")
+        .unwrap()
+        .args(["create-release", "minor"])
+        .current_dir(&temp_dir)
+        .assert()
+        .success();
+
+    std::fs::write(
+        temp_dir
+            .path()
+            .join(".changelogs")
+            .join("0.1.0")
+            .join(".yanked"),
+        "",
+    )
+    .unwrap();
+
+    Command::cargo_bin("cargo-changelog")
+        .unwrap()
+        .args(["generate-changelog", "--allow-dirty"])
+        .current_dir(&temp_dir)
+        .assert()
+        .success();
+
+    let changelog = std::fs::read_to_string(temp_dir.path().join("CHANGELOG.md")).unwrap();
+    assert!(
+        changelog.contains("## v0.1.0 [YANKED]"),
+        "Version not marked as yanked: {changelog}"
+    );
+}
+
+#[test]
+fn generate_changelog_command_check_detects_stale_changelog() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("cargo-changelog")
+        .tempdir()
+        .unwrap();
+    self::common::init_git(temp_dir.path());
+    self::common::init_cargo(
+        temp_dir.path(),
+        "generate_changelog_command_check_detects_stale_changelog",
+    );
+    self::common::init_cargo_changelog(temp_dir.path());
+
+    let add = |subject: &str| {
+        self::common::cargo_changelog_add(temp_dir.path())
+            .args([
+                "--format=toml",
+                "--set",
+                "issue=123",
+                "--set",
+                &format!("subject='{subject}'"),
+                "--set",
+                "type=Misc",
+            ])
+            .assert()
+            .success();
+    };
+
+    add("First subject");
+    Command::cargo_bin("cargo-changelog")
+        .unwrap()
+        .args(["generate-changelog", "--all", "--allow-dirty"])
+        .current_dir(&temp_dir)
+        .assert()
+        .success();
+
+    let changelog_file_path = temp_dir.path().join("CHANGELOG.md");
+    let changelog = std::fs::read_to_string(&changelog_file_path).unwrap();
+
+    Command::cargo_bin("cargo-changelog")
+        .unwrap()
+        .args(["generate-changelog", "--all", "--check"])
+        .current_dir(&temp_dir)
+        .assert()
+        .success();
+
+    add("Second subject");
+    let output = Command::cargo_bin("cargo-changelog")
+        .unwrap()
+        .args(["generate-changelog", "--all", "--check"])
+        .current_dir(&temp_dir)
+        .assert()
+        .failure();
+
+    let stdout = String::from_utf8(output.get_output().stdout.clone()).unwrap();
+    assert!(
+        stdout
+            .lines()
+            .any(|line| line.starts_with('+') && line.contains("Second subject")),
+        "Missing diff: {stdout}"
+    );
+
+    // --check must never write the changelog
+    assert_eq!(
+        changelog,
+        std::fs::read_to_string(&changelog_file_path).unwrap()
+    );
+}
+
+#[test]
+fn generate_changelog_command_renders_toc() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("cargo-changelog")
+        .tempdir()
+        .unwrap();
+    self::common::init_git(temp_dir.path());
+    self::common::init_cargo(temp_dir.path(), "generate_changelog_command_renders_toc");
+    Command::cargo_bin("cargo-changelog")
+        .unwrap()
+        .args(["init", "--toc"])
+        .current_dir(&temp_dir)
+        .assert()
+        .success();
+
+    self::common::cargo_changelog_add(temp_dir.path())
+        .args([
+            "--format=toml",
+            "--set",
+            "issue=123",
+            "--set",
+            "subject='Test subject'",
+            "--set",
+            "type=Misc",
+        ])
+        .assert()
+        .success();
+
+    Command::cargo_bin("cargo-changelog")
+        .unwrap()
+        .args(["create-release", "minor"])
+        .current_dir(&temp_dir)
+        .assert()
+        .success();
+
+    Command::cargo_bin("cargo-changelog")
+        .unwrap()
+        .args(["generate-changelog", "--allow-dirty"])
+        .current_dir(&temp_dir)
+        .assert()
+        .success();
+
+    let changelog = std::fs::read_to_string(temp_dir.path().join("CHANGELOG.md")).unwrap();
+    assert!(
+        changelog.contains("- [v0.1.0](#v0-1-0)\n"),
+        "Missing TOC entry: {changelog}"
+    );
+    assert!(
+        changelog.contains("<a id=\"v0-1-0\"></a>\n## v0.1.0\n"),
+        "Missing anchor: {changelog}"
+    );
+}
+
+fn generate_changelog_with_hook(name: &str, hook: &str) -> (tempfile::TempDir, bool) {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("cargo-changelog")
+        .tempdir()
+        .unwrap();
+    self::common::init_git(temp_dir.path());
+    self::common::init_cargo(temp_dir.path(), name);
+    self::common::init_cargo_changelog(temp_dir.path());
+
+    let config_file_path = temp_dir.path().join("changelog.toml");
+    let config = std::fs::read_to_string(&config_file_path).unwrap();
+    std::fs::write(
+        config_file_path,
+        format!("post_release_hook = {hook:?}\n{config}"),
+    )
+    .unwrap();
+
+    self::common::cargo_changelog_add(temp_dir.path())
+        .args([
+            "--format=toml",
+            "--set",
+            "issue=123",
+            "--set",
+            "subject='Test subject'",
+            "--set",
+            "type=Misc",
+        ])
+        .assert()
+        .success();
+
+    Command::cargo_bin("cargo-changelog")
+        .unwrap()
+        .args(["create-release", "minor"])
+        .current_dir(&temp_dir)
+        .assert()
+        .success();
+
+    let success = Command::cargo_bin("cargo-changelog")
+        .unwrap()
+        .args(["generate-changelog", "--allow-dirty"])
+        .current_dir(&temp_dir)
+        .output()
+        .unwrap()
+        .status
+        .success();
+    (temp_dir, success)
+}
+
+#[cfg(unix)]
+#[test]
+fn generate_changelog_command_runs_post_release_hook() {
+    let (temp_dir, success) = generate_changelog_with_hook(
+        "generate_changelog_command_runs_post_release_hook",
+        "sh -c 'echo $CHANGELOG_VERSION > hook.txt'",
+    );
+    assert!(success, "generate-changelog failed");
+
+    let hook_output = std::fs::read_to_string(temp_dir.path().join("hook.txt")).unwrap();
+    assert_eq!(hook_output.trim(), "0.1.0");
+}
+
+#[cfg(unix)]
+#[test]
+fn generate_changelog_command_fails_with_failing_post_release_hook() {
+    let (_temp_dir, success) = generate_changelog_with_hook(
+        "generate_changelog_command_fails_with_failing_post_release_hook",
+        "false",
+    );
+    assert!(!success, "generate-changelog did not fail");
+}
+
+#[test]
+fn generate_changelog_command_uses_template_override() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("cargo-changelog")
+        .tempdir()
+        .unwrap();
+    self::common::init_git(temp_dir.path());
+    self::common::init_cargo(
+        temp_dir.path(),
+        "generate_changelog_command_uses_template_override",
+    );
+    self::common::init_cargo_changelog(temp_dir.path());
+
+    std::fs::write(
+        temp_dir.path().join("website.md"),
+        "Website changelog\n{{#each versions}}{{this.version}}\n{{/each}}",
+    )
+    .unwrap();
+
+    self::common::cargo_changelog_add(temp_dir.path())
+        .args([
+            "--format=toml",
+            "--set",
+            "issue=123",
+            "--set",
+            "subject='Test subject'",
+            "--set",
+            "type=Misc",
+        ])
+        .assert()
+        .success();
+
+    Command::cargo_bin("cargo-changelog")
+        .unwrap()
+        .args([
+            "generate-changelog",
+            "--all",
+            "--allow-dirty",
+            "--template",
+            "website.md",
+        ])
+        .current_dir(&temp_dir)
+        .assert()
+        .success();
+
+    let changelog = std::fs::read_to_string(temp_dir.path().join("CHANGELOG.md")).unwrap();
+    assert_eq!(changelog, "Website changelog\nunreleased\n");
+}
+
+#[test]
+fn generate_changelog_command_passes_template_vars() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("cargo-changelog")
+        .tempdir()
+        .unwrap();
+    self::common::init_git(temp_dir.path());
+    self::common::init_cargo(
+        temp_dir.path(),
+        "generate_changelog_command_passes_template_vars",
+    );
+    self::common::init_cargo_changelog(temp_dir.path());
+
+    let config_file_path = temp_dir.path().join("changelog.toml");
+    let config = std::fs::read_to_string(&config_file_path).unwrap();
+    std::fs::write(
+        config_file_path,
+        format!("{config}\n[template_vars]\nproject_name = \"my-project\"\n"),
+    )
+    .unwrap();
+    std::fs::write(
+        temp_dir.path().join("website.md"),
+        "Changelog of {{vars.project_name}}\n",
+    )
+    .unwrap();
+
+    Command::cargo_bin("cargo-changelog")
+        .unwrap()
+        .args([
+            "generate-changelog",
+            "--allow-dirty",
+            "--template",
+            "website.md",
+        ])
+        .current_dir(&temp_dir)
+        .assert()
+        .success();
+
+    let changelog = std::fs::read_to_string(temp_dir.path().join("CHANGELOG.md")).unwrap();
+    assert_eq!(changelog, "Changelog of my-project\n");
+}
+
+#[test]
+fn generate_changelog_command_writes_frontmatter() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("cargo-changelog")
+        .tempdir()
+        .unwrap();
+    self::common::init_git(temp_dir.path());
+    self::common::init_cargo(
+        temp_dir.path(),
+        "generate_changelog_command_writes_frontmatter",
+    );
+    self::common::init_cargo_changelog(temp_dir.path());
+
+    let config_file_path = temp_dir.path().join("changelog.toml");
+    let config = std::fs::read_to_string(&config_file_path).unwrap();
+    std::fs::write(
+        config_file_path,
+        format!("{config}\n[changelog_frontmatter]\nlayout = \"page\"\ntitle = \"Changelog\"\n"),
+    )
+    .unwrap();
+
+    Command::cargo_bin("cargo-changelog")
+        .unwrap()
+        .args(["generate-changelog", "--allow-dirty"])
+        .current_dir(&temp_dir)
+        .assert()
+        .success();
+
+    let changelog = std::fs::read_to_string(temp_dir.path().join("CHANGELOG.md")).unwrap();
+    assert!(
+        changelog.starts_with("---\nlayout: \"page\"\ntitle: \"Changelog\"\n---\n# CHANGELOG\n"),
+        "Missing front-matter: {changelog}"
+    );
+}
+
+fn generate_changelog_with_empty_version(name: &str, skip_empty_versions: bool) -> String {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("cargo-changelog")
+        .tempdir()
+        .unwrap();
+    self::common::init_git(temp_dir.path());
+    self::common::init_cargo(temp_dir.path(), name);
+    self::common::init_cargo_changelog(temp_dir.path());
+
+    let config_file_path = temp_dir.path().join("changelog.toml");
+    let config = std::fs::read_to_string(&config_file_path).unwrap();
+    std::fs::write(
+        config_file_path,
+        format!("skip_empty_versions = {skip_empty_versions}\n{config}"),
+    )
+    .unwrap();
+
+    std::fs::create_dir(temp_dir.path().join(".changelogs").join("0.2.0")).unwrap();
+
+    Command::cargo_bin("cargo-changelog")
+        .unwrap()
+        .args(["generate-changelog", "--allow-dirty"])
+        .current_dir(&temp_dir)
+        .assert()
+        .success();
+
+    std::fs::read_to_string(temp_dir.path().join("CHANGELOG.md")).unwrap()
+}
+
+#[test]
+fn generate_changelog_command_skips_empty_versions() {
+    let changelog = generate_changelog_with_empty_version(
+        "generate_changelog_command_skips_empty_versions",
+        true,
+    );
+    assert!(
+        !changelog.contains("## v0.2.0"),
+        "Empty version rendered: {changelog}"
+    );
+}
+
+#[test]
+fn generate_changelog_command_renders_empty_versions() {
+    let changelog = generate_changelog_with_empty_version(
+        "generate_changelog_command_renders_empty_versions",
+        false,
+    );
+    assert!(
+        changelog.contains("## v0.2.0\n\nNo changes\n"),
+        "Empty version not rendered: {changelog}"
+    );
+}
+
+#[test]
+fn generate_changelog_command_includes_empty_unreleased() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("cargo-changelog")
+        .tempdir()
+        .unwrap();
+    self::common::init_git(temp_dir.path());
+    self::common::init_cargo(
+        temp_dir.path(),
+        "generate_changelog_command_includes_empty_unreleased",
+    );
+    self::common::init_cargo_changelog(temp_dir.path());
+
+    Command::cargo_bin("cargo-changelog")
+        .unwrap()
+        .args(["generate-changelog", "--allow-dirty"])
+        .current_dir(&temp_dir)
+        .assert()
+        .success();
+    let changelog = std::fs::read_to_string(temp_dir.path().join("CHANGELOG.md")).unwrap();
+    assert!(
+        !changelog.contains("## vunreleased"),
+        "Unreleased rendered: {changelog}"
+    );
+
+    Command::cargo_bin("cargo-changelog")
+        .unwrap()
+        .args([
+            "generate-changelog",
+            "--allow-dirty",
+            "--include-unreleased",
+        ])
+        .current_dir(&temp_dir)
+        .assert()
+        .success();
+    let changelog = std::fs::read_to_string(temp_dir.path().join("CHANGELOG.md")).unwrap();
+    assert!(
+        changelog.contains("## vunreleased\n\nNo changes\n"),
+        "Unreleased not rendered: {changelog}"
+    );
+}
+
+#[test]
+fn generate_changelog_command_filters_by_author() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("cargo-changelog")
+        .tempdir()
+        .unwrap();
+    self::common::init_git(temp_dir.path());
+    self::common::init_cargo(
+        temp_dir.path(),
+        "generate_changelog_command_filters_by_author",
+    );
+    self::common::init_cargo_changelog(temp_dir.path());
+
+    let config_file_path = temp_dir.path().join("changelog.toml");
+    let config = std::fs::read_to_string(&config_file_path).unwrap();
+    std::fs::write(
+        config_file_path,
+        format!("{config}\n[header_fields.author]\ntype = \"string\"\nrequired = false\n"),
+    )
+    .unwrap();
+
+    for (subject, author) in [("Mine", "alice"), ("Theirs", "bob")] {
+        self::common::cargo_changelog_add(temp_dir.path())
+            .args([
+                "--format=toml",
+                "--set",
+                "issue=123",
+                "--set",
+                &format!("subject={subject}"),
+                "--set",
+                "type=Misc",
+                "--set",
+                &format!("author={author}"),
+            ])
+            .assert()
+            .success();
+    }
+
+    Command::cargo_bin("cargo-changelog")
+        .unwrap()
+        .args([
+            "generate-changelog",
+            "--allow-dirty",
+            "--all",
+            "--author",
+            "alice",
+            "--changelog",
+            "ALICE.md",
+        ])
+        .current_dir(&temp_dir)
+        .assert()
+        .success();
+
+    let changelog = std::fs::read_to_string(temp_dir.path().join("ALICE.md")).unwrap();
+    assert!(changelog.contains("Mine"), "Missing fragment: {changelog}");
+    assert!(!changelog.contains("Theirs"), "Not filtered: {changelog}");
+
+    // The changelog of the project is never overwritten with the fragments of one author
+    Command::cargo_bin("cargo-changelog")
+        .unwrap()
+        .args([
+            "generate-changelog",
+            "--allow-dirty",
+            "--all",
+            "--author",
+            "alice",
+        ])
+        .current_dir(&temp_dir)
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains(
+            "--author only renders part of the changelog",
+        ));
+    assert!(!temp_dir.path().join("CHANGELOG.md").exists());
+
+    Command::cargo_bin("cargo-changelog")
+        .unwrap()
+        .args(["generate-changelog", "--commit", "--author", "alice"])
+        .current_dir(&temp_dir)
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("cannot be used with"));
+}
+
+#[test]
+fn generate_changelog_command_renders_compare_links() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("cargo-changelog")
+        .tempdir()
+        .unwrap();
+    self::common::init_git(temp_dir.path());
+    self::common::init_cargo(
+        temp_dir.path(),
+        "generate_changelog_command_renders_compare_links",
+    );
+    self::common::init_cargo_changelog(temp_dir.path());
+
+    let config_file_path = temp_dir.path().join("changelog.toml");
+    let config = std::fs::read_to_string(&config_file_path).unwrap();
+    std::fs::write(
+        config_file_path,
+        format!("repository_url = \"https://example.com/repo\"\n{config}"),
+    )
+    .unwrap();
+
+    for version in ["0.1.0", "0.2.0"] {
+        let version_dir = temp_dir.path().join(".changelogs").join(version);
+        std::fs::create_dir_all(&version_dir).unwrap();
+        std::fs::write(
+            version_dir.join("fragment.md"),
+            "+++\nsubject = \"test\"\n+++\ntext\n",
+        )
+        .unwrap();
+    }
+
+    Command::cargo_bin("cargo-changelog")
+        .unwrap()
+        .args(["generate-changelog", "--allow-dirty"])
+        .current_dir(&temp_dir)
+        .assert()
+        .success();
+
+    let changelog = std::fs::read_to_string(temp_dir.path().join("CHANGELOG.md")).unwrap();
+    assert!(
+        changelog
+            .contains("**Full Changelog**: https://example.com/repo/compare/v0.1.0...v0.2.0\n"),
+        "Missing compare link: {changelog}"
+    );
+    assert_eq!(
+        changelog.matches("**Full Changelog**").count(),
+        1,
+        "First version must not have a compare link: {changelog}"
+    );
+
+    // The compare link uses the configured tag names
+    let config_file_path = temp_dir.path().join("changelog.toml");
+    let config = std::fs::read_to_string(&config_file_path).unwrap();
+    std::fs::write(
+        config_file_path,
+        format!("tag_format = \"release-{{{{version}}}}\"\n{config}"),
+    )
+    .unwrap();
+
+    Command::cargo_bin("cargo-changelog")
+        .unwrap()
+        .args(["generate-changelog", "--allow-dirty"])
+        .current_dir(&temp_dir)
+        .assert()
+        .success();
+
+    let changelog = std::fs::read_to_string(temp_dir.path().join("CHANGELOG.md")).unwrap();
+    assert!(
+        changelog.contains(
+            "**Full Changelog**: https://example.com/repo/compare/release-0.1.0...release-0.2.0\n"
+        ),
+        "Missing compare link: {changelog}"
+    );
+}
+
+#[cfg(unix)]
+fn generate_changelog_with_symlinked_fragment(name: &str, follow_symlinks: bool) -> String {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("cargo-changelog")
+        .tempdir()
+        .unwrap();
+    self::common::init_git(temp_dir.path());
+    self::common::init_cargo(temp_dir.path(), name);
+    self::common::init_cargo_changelog(temp_dir.path());
+
+    let config_file_path = temp_dir.path().join("changelog.toml");
+    let config = std::fs::read_to_string(&config_file_path).unwrap();
+    std::fs::write(
+        config_file_path,
+        format!("follow_symlinks = {follow_symlinks}\n{config}"),
+    )
+    .unwrap();
+
+    let target = temp_dir.path().join("elsewhere.md");
+    std::fs::write(&target, "+++\nsubject = \"Symlinked\"\n+++\ntext\n").unwrap();
+
+    let version_dir = temp_dir.path().join(".changelogs").join("0.1.0");
+    std::fs::create_dir_all(&version_dir).unwrap();
+    std::os::unix::fs::symlink(&target, version_dir.join("fragment.md")).unwrap();
+    // A symlink loop must not break the generation
+    std::os::unix::fs::symlink(&version_dir, version_dir.join("loop")).unwrap();
+
+    Command::cargo_bin("cargo-changelog")
+        .unwrap()
+        .args(["generate-changelog", "--allow-dirty"])
+        .current_dir(&temp_dir)
+        .assert()
+        .success();
+
+    std::fs::read_to_string(temp_dir.path().join("CHANGELOG.md")).unwrap()
+}
+
+#[cfg(unix)]
+#[test]
+fn generate_changelog_command_ignores_symlinks_by_default() {
+    let changelog = generate_changelog_with_symlinked_fragment(
+        "generate_changelog_command_ignores_symlinks_by_default",
+        false,
+    );
+    assert!(
+        !changelog.contains("Symlinked"),
+        "Symlink followed: {changelog}"
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn generate_changelog_command_follows_symlinks() {
+    let changelog = generate_changelog_with_symlinked_fragment(
+        "generate_changelog_command_follows_symlinks",
+        true,
+    );
+    assert!(
+        changelog.contains("Symlinked"),
+        "Symlink not followed: {changelog}"
+    );
+}
+
+#[test]
+fn generate_changelog_command_emits_single_version() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("cargo-changelog")
+        .tempdir()
+        .unwrap();
+    self::common::init_git(temp_dir.path());
+    self::common::init_cargo(
+        temp_dir.path(),
+        "generate_changelog_command_emits_single_version",
+    );
+    self::common::init_cargo_changelog(temp_dir.path());
+
+    for version in ["0.1.0", "0.2.0"] {
+        let version_dir = temp_dir.path().join(".changelogs").join(version);
+        std::fs::create_dir_all(&version_dir).unwrap();
+        std::fs::write(
+            version_dir.join("fragment.md"),
+            format!("+++\nsubject = \"Change in {version}\"\n+++\ntext\n"),
+        )
+        .unwrap();
+    }
+
+    let output = Command::cargo_bin("cargo-changelog")
+        .unwrap()
+        .args(["generate-changelog", "--emit-version-only", "0.1.0"])
+        .current_dir(&temp_dir)
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let output = String::from_utf8(output).unwrap();
+
+    assert!(
+        output.starts_with("## v0.1.0\n"),
+        "Unexpected output: {output}"
+    );
+    assert!(
+        output.contains("Change in 0.1.0"),
+        "Unexpected output: {output}"
+    );
+    assert!(!output.contains("0.2.0"), "Unexpected output: {output}");
+    assert!(!temp_dir.path().join("CHANGELOG.md").exists());
+
+    Command::cargo_bin("cargo-changelog")
+        .unwrap()
+        .args(["generate-changelog", "--emit-version-only", "0.3.0"])
+        .current_dir(&temp_dir)
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains(
+            "No changelog fragments found for version 0.3.0",
+        ));
+}
+
+#[test]
+fn generate_changelog_command_renders_multiple_outputs() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("cargo-changelog")
+        .tempdir()
+        .unwrap();
+    self::common::init_git(temp_dir.path());
+    self::common::init_cargo(
+        temp_dir.path(),
+        "generate_changelog_command_renders_multiple_outputs",
+    );
+    self::common::init_cargo_changelog(temp_dir.path());
+
+    let fragment_dir = temp_dir.path().join(".changelogs");
+    std::fs::write(
+        fragment_dir.join("changes.txt"),
+        "CHANGES\n{{#each versions}}{{this.version}}\n{{/each}}",
+    )
+    .unwrap();
+    std::fs::write(
+        fragment_dir.join("versions.txt"),
+        "{{#each version_list}}{{this}}\n{{/each}}",
+    )
+    .unwrap();
+
+    let config_file_path = temp_dir.path().join("changelog.toml");
+    let config = std::fs::read_to_string(&config_file_path).unwrap();
+    std::fs::write(
+        config_file_path,
+        format!("outputs = [{{ path = \"CHANGES.txt\", template = \"changes.txt\" }}]\n{config}"),
+    )
+    .unwrap();
+
+    self::common::cargo_changelog_add(temp_dir.path())
+        .args([
+            "--format=toml",
+            "--set",
+            "issue=123",
+            "--set",
+            "subject='Test subject'",
+            "--set",
+            "type=Misc",
+        ])
+        .assert()
+        .success();
+
+    // A failing output does not keep the other outputs from being written
+    self::common::cargo_changelog_cmd(temp_dir.path())
+        .args([
+            "generate-changelog",
+            "--all",
+            "--allow-dirty",
+            "--output",
+            "VERSIONS.txt=versions.txt",
+            "--output",
+            "BROKEN.txt=does-not-exist.txt",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains(
+            "Failed to write 1 of the changelog files",
+        ))
+        .stderr(predicates::str::contains("BROKEN.txt"));
+
+    let changelog = std::fs::read_to_string(temp_dir.path().join("CHANGELOG.md")).unwrap();
+    assert!(changelog.contains("unreleased"), "{changelog}");
+    let changes = std::fs::read_to_string(temp_dir.path().join("CHANGES.txt")).unwrap();
+    assert_eq!(changes, "CHANGES\nunreleased\n");
+    let versions = std::fs::read_to_string(temp_dir.path().join("VERSIONS.txt")).unwrap();
+    assert_eq!(versions, "unreleased\n");
+    assert!(!temp_dir.path().join("BROKEN.txt").exists());
+    assert!(!temp_dir.path().join(".BROKEN.txt.tmp").exists());
+}
+
+#[test]
+fn generate_changelog_command_uses_template_preset() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("cargo-changelog")
+        .tempdir()
+        .unwrap();
+    self::common::init_git(temp_dir.path());
+    self::common::init_cargo(
+        temp_dir.path(),
+        "generate_changelog_command_uses_template_preset",
+    );
+    self::common::init_cargo_changelog(temp_dir.path());
+
+    self::common::cargo_changelog_add(temp_dir.path())
+        .args([
+            "--format=toml",
+            "--set",
+            "issue=123",
+            "--set",
+            "subject=Test subject",
+            "--set",
+            "type=Misc",
+        ])
+        .assert()
+        .success();
+
+    // The configured template is not used, so it does not need to exist
+    std::fs::remove_file(temp_dir.path().join(".changelogs").join("template.md")).unwrap();
+
+    self::common::cargo_changelog_cmd(temp_dir.path())
+        .args([
+            "generate-changelog",
+            "--all",
+            "--allow-dirty",
+            "--format",
+            "simple",
+        ])
+        .assert()
+        .success();
+    let changelog = std::fs::read_to_string(temp_dir.path().join("CHANGELOG.md")).unwrap();
+    assert_eq!(
+        changelog,
+        "# Changelog\n\n## unreleased\n\n- Test subject\n"
+    );
+
+    self::common::cargo_changelog_cmd(temp_dir.path())
+        .args([
+            "generate-changelog",
+            "--all",
+            "--allow-dirty",
+            "--format",
+            "keepachangelog",
+        ])
+        .assert()
+        .success();
+    let changelog = std::fs::read_to_string(temp_dir.path().join("CHANGELOG.md")).unwrap();
+    assert!(
+        changelog.contains("## [Unreleased]\n\n### Misc\n\n- Test subject (#123)\n"),
+        "{changelog}"
+    );
+
+    // A template file takes precedence
+    std::fs::write(temp_dir.path().join("custom.md"), "Custom").unwrap();
+    self::common::cargo_changelog_cmd(temp_dir.path())
+        .args([
+            "generate-changelog",
+            "--all",
+            "--allow-dirty",
+            "--format",
+            "github",
+            "--template",
+            "custom.md",
+        ])
+        .assert()
+        .success();
+    let changelog = std::fs::read_to_string(temp_dir.path().join("CHANGELOG.md")).unwrap();
+    assert_eq!(changelog, "Custom\n");
+}
+
+#[test]
+fn generate_changelog_command_leaves_out_skipped_fragments() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("cargo-changelog")
+        .tempdir()
+        .unwrap();
+    self::common::init_git(temp_dir.path());
+    self::common::init_cargo(
+        temp_dir.path(),
+        "generate_changelog_command_leaves_out_skipped_fragments",
+    );
+    self::common::init_cargo_changelog(temp_dir.path());
+
+    let unreleased_dir = temp_dir.path().join(".changelogs").join("unreleased");
+    std::fs::write(
+        unreleased_dir.join("public.md"),
+        "+++\nissue = 1\nsubject = \"Public change\"\ntype = \"Misc\"\n+++\n",
+    )
+    .unwrap();
+    std::fs::write(
+        unreleased_dir.join("internal.md"),
+        "+++\nissue = \"2\"\nsubject = \"Internal change\"\ntype = \"Misc\"\nskip = true\n+++\n",
+    )
+    .unwrap();
+
+    self::common::cargo_changelog_cmd(temp_dir.path())
+        .args(["generate-changelog", "--all", "--allow-dirty"])
+        .assert()
+        .success();
+
+    let changelog = std::fs::read_to_string(temp_dir.path().join("CHANGELOG.md")).unwrap();
+    assert!(changelog.contains("Public change"), "{changelog}");
+    assert!(!changelog.contains("Internal change"), "{changelog}");
+
+    // Skipped fragments are still verified, the issue of the internal change is not an integer
+    self::common::cargo_changelog_cmd(temp_dir.path())
+        .args(["verify-metadata"])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("internal.md"));
+}
+
+#[test]
+fn generate_changelog_command_resolves_relative_links() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("cargo-changelog")
+        .tempdir()
+        .unwrap();
+    self::common::init_git(temp_dir.path());
+    self::common::init_cargo(
+        temp_dir.path(),
+        "generate_changelog_command_resolves_relative_links",
+    );
+    self::common::init_cargo_changelog(temp_dir.path());
+
+    self::common::cargo_changelog_add(temp_dir.path())
+        .args([
+            "--format=toml",
+            "--set",
+            "issue=123",
+            "--set",
+            "subject=Test subject",
+            "--set",
+            "type=Misc",
+            "--text",
+            "See [the docs](docs/usage.md) or [the website](https://example.com).",
+        ])
+        .assert()
+        .success();
+
+    self::common::cargo_changelog_cmd(temp_dir.path())
+        .args([
+            "generate-changelog",
+            "--all",
+            "--allow-dirty",
+            "--base-url",
+            "https://github.com/owner/repo/blob/main",
+        ])
+        .assert()
+        .success();
+
+    let changelog = std::fs::read_to_string(temp_dir.path().join("CHANGELOG.md")).unwrap();
+    assert!(
+        changelog.contains(
+            "See [the docs](https://github.com/owner/repo/blob/main/docs/usage.md) or [the website](https://example.com)."
+        ),
+        "{changelog}"
+    );
+}
+
+#[test]
+fn generate_changelog_command_uses_group_headings() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("cargo-changelog")
+        .tempdir()
+        .unwrap();
+    self::common::init_git(temp_dir.path());
+    self::common::init_cargo(
+        temp_dir.path(),
+        "generate_changelog_command_uses_group_headings",
+    );
+    self::common::init_cargo_changelog(temp_dir.path());
+
+    let config_file_path = temp_dir.path().join("changelog.toml");
+    let mut config = std::fs::read_to_string(&config_file_path).unwrap();
+    config.push_str("\n[group_headings]\nFeature = \"🚀 Features\"\n");
+    std::fs::write(&config_file_path, config).unwrap();
+
+    for ty in ["Feature", "Bugfix"] {
+        self::common::cargo_changelog_add(temp_dir.path())
+            .args([
+                "--format=toml",
+                "--set",
+                "issue=123",
+                "--set",
+                "subject=Test subject",
+                "--set",
+                &format!("type={ty}"),
+            ])
+            .assert()
+            .success();
+    }
+
+    self::common::cargo_changelog_cmd(temp_dir.path())
+        .args(["generate-changelog", "--all", "--allow-dirty"])
+        .assert()
+        .success();
+    let changelog = std::fs::read_to_string(temp_dir.path().join("CHANGELOG.md")).unwrap();
+    assert!(changelog.contains("### 🚀 Features\n"), "{changelog}");
+    assert!(changelog.contains("### Bugfix\n"), "{changelog}");
+}
+
+#[test]
+fn generate_changelog_command_with_strict_templates() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("cargo-changelog")
+        .tempdir()
+        .unwrap();
+    self::common::init_git(temp_dir.path());
+    self::common::init_cargo(
+        temp_dir.path(),
+        "generate_changelog_command_with_strict_templates",
+    );
+    self::common::init_cargo_changelog(temp_dir.path());
+
+    let config_file_path = temp_dir.path().join("changelog.toml");
+    let config = std::fs::read_to_string(&config_file_path).unwrap();
+    std::fs::write(
+        &config_file_path,
+        format!("strict_templates = true\n{config}"),
+    )
+    .unwrap();
+
+    self::common::cargo_changelog_add(temp_dir.path())
+        .args([
+            "--format=toml",
+            "--set",
+            "subject=Test subject",
+            "--set",
+            "type=Misc",
+        ])
+        .assert()
+        .success();
+
+    // The default template works without optional fields
+    self::common::cargo_changelog_cmd(temp_dir.path())
+        .args(["generate-changelog", "--all", "--allow-dirty"])
+        .assert()
+        .success();
+
+    let template_path = temp_dir.path().join(".changelogs").join("template.md");
+    std::fs::write(
+        &template_path,
+        "{{#each versions}}{{this.verison}}{{/each}}",
+    )
+    .unwrap();
+    self::common::cargo_changelog_cmd(temp_dir.path())
+        .args(["generate-changelog", "--all", "--allow-dirty"])
+        .assert()
+        .failure();
+}
+
+#[test]
+fn generate_changelog_command_shows_backported_fragments_under_each_version() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("cargo-changelog")
+        .tempdir()
+        .unwrap();
+    self::common::init_git(temp_dir.path());
+    self::common::init_cargo(
+        temp_dir.path(),
+        "generate_changelog_command_shows_backported_fragments_under_each_version",
+    );
+    self::common::init_cargo_changelog(temp_dir.path());
+
+    self::common::cargo_changelog_add(temp_dir.path())
+        .args([
+            "--format=toml",
+            "--set",
+            "issue=123",
+            "--set",
+            "subject=Backported fix",
+            "--set",
+            "type=Bugfix",
+        ])
+        .assert()
+        .success();
+
+    let unreleased_dir = temp_dir.path().join(".changelogs").join("unreleased");
+    let fragment_path = std::fs::read_dir(&unreleased_dir)
+        .unwrap()
+        .map(|rde| rde.unwrap().path())
+        .find(|path| path.extension().is_some_and(|ext| ext == "md"))
+        .unwrap();
+    let fragment = std::fs::read_to_string(&fragment_path).unwrap();
+    let fragment = fragment.replacen("+++\n", "+++\nversions = [\"0.1.1\", \"0.2.1\"]\n", 1);
+    std::fs::write(&fragment_path, fragment).unwrap();
+
+    self::common::cargo_changelog_cmd(temp_dir.path())
+        .args(["generate-changelog", "--allow-dirty"])
+        .assert()
+        .success();
+    let changelog = std::fs::read_to_string(temp_dir.path().join("CHANGELOG.md")).unwrap();
+    assert!(changelog.contains("## v0.1.1"), "{changelog}");
+    assert!(changelog.contains("## v0.2.1"), "{changelog}");
+    assert_eq!(
+        changelog.matches("Backported fix").count(),
+        2,
+        "{changelog}"
+    );
+
+    self::common::cargo_changelog_cmd(temp_dir.path())
+        .args(["list-versions"])
+        .assert()
+        .success()
+        .stdout("0.1.1 (1)\n0.2.1 (1)\n");
+}
+
+#[test]
+fn generate_changelog_command_writes_manifest() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("cargo-changelog")
+        .tempdir()
+        .unwrap();
+    self::common::init_git(temp_dir.path());
+    self::common::init_cargo(
+        temp_dir.path(),
+        "generate_changelog_command_writes_manifest",
+    );
+    self::common::init_cargo_changelog(temp_dir.path());
+
+    self::common::cargo_changelog_add(temp_dir.path())
+        .args([
+            "--format=toml",
+            "--set",
+            "issue=123",
+            "--set",
+            "subject=Released",
+            "--set",
+            "type=Misc",
+        ])
+        .assert()
+        .success();
+    self::common::cargo_changelog_cmd(temp_dir.path())
+        .args(["create-release", "minor"])
+        .assert()
+        .success();
+
+    self::common::cargo_changelog_cmd(temp_dir.path())
+        .args([
+            "generate-changelog",
+            "--allow-dirty",
+            "--manifest",
+            "manifest.json",
+        ])
+        .assert()
+        .success();
+
+    let manifest = std::fs::read_to_string(temp_dir.path().join("manifest.json")).unwrap();
+    let manifest: serde_json::Value = serde_json::from_str(&manifest).unwrap();
+    let versions = manifest["versions"].as_array().unwrap();
+    assert_eq!(versions.len(), 1, "{manifest}");
+    assert_eq!(versions[0]["version"], "0.1.0");
+    let fragments = versions[0]["fragments"].as_array().unwrap();
+    assert_eq!(fragments.len(), 1, "{manifest}");
+    assert!(fragments[0]
+        .as_str()
+        .unwrap()
+        .starts_with(".changelogs/0.1.0/"));
+}
+
+#[test]
+fn generate_changelog_command_renders_version_range() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("cargo-changelog")
+        .tempdir()
+        .unwrap();
+    self::common::init_git(temp_dir.path());
+    self::common::init_cargo(
+        temp_dir.path(),
+        "generate_changelog_command_renders_version_range",
+    );
+    self::common::init_cargo_changelog(temp_dir.path());
+
+    for version in ["0.1.0", "0.2.0", "0.3.0", "0.4.0", "0.5.0"] {
+        let version_dir = temp_dir.path().join(".changelogs").join(version);
+        std::fs::create_dir_all(&version_dir).unwrap();
+        std::fs::write(
+            version_dir.join("change.md"),
+            format!("+++\nsubject = \"Change in {version}\"\ntype = \"Misc\"\n+++\n"),
+        )
+        .unwrap();
+    }
+
+    self::common::cargo_changelog_cmd(temp_dir.path())
+        .args([
+            "generate-changelog",
+            "--allow-dirty",
+            "--from",
+            "0.2.0",
+            "--to",
+            "0.4.0",
+        ])
+        .assert()
+        .success();
+    let changelog = std::fs::read_to_string(temp_dir.path().join("CHANGELOG.md")).unwrap();
+    assert!(!changelog.contains("## v0.1.0"), "{changelog}");
+    assert!(changelog.contains("## v0.2.0"), "{changelog}");
+    assert!(changelog.contains("## v0.3.0"), "{changelog}");
+    assert!(changelog.contains("## v0.4.0"), "{changelog}");
+    assert!(!changelog.contains("## v0.5.0"), "{changelog}");
+}
+
+#[test]
+fn generate_changelog_command_resolves_commits() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("cargo-changelog")
+        .tempdir()
+        .unwrap();
+    self::common::init_git(temp_dir.path());
+    self::common::init_git_user(temp_dir.path());
+    self::common::init_cargo(
+        temp_dir.path(),
+        "generate_changelog_command_resolves_commits",
+    );
+    self::common::init_cargo_changelog(temp_dir.path());
+
+    let config_file_path = temp_dir.path().join("changelog.toml");
+    let config = std::fs::read_to_string(&config_file_path).unwrap();
+    std::fs::write(
+        &config_file_path,
+        format!("resolve_commits = true\n{config}"),
+    )
+    .unwrap();
+    std::fs::write(
+        temp_dir.path().join(".changelogs").join("template.md"),
+        "{{#each versions}}{{this.version}}:{{#each this.entries}} {{this.commit}}{{/each}}\n{{/each}}",
+    )
+    .unwrap();
+
+    let add = || {
+        self::common::cargo_changelog_add(temp_dir.path())
+            .args([
+                "--format=toml",
+                "--set",
+                "issue=123",
+                "--set",
+                "subject='Test subject'",
+                "--set",
+                "type=Misc",
+            ])
+            .assert()
+            .success();
+    };
+    let git = |args: &[&str]| {
+        let output = std::process::Command::new("git")
+            .args(args)
+            .current_dir(&temp_dir)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "git {args:?} failed");
+        String::from_utf8(output.stdout).unwrap().trim().to_string()
+    };
+
+    // The fragment is committed, then moved by the release
+    add();
+    git(&["add", "."]);
+    git(&["commit", "-m", "Add fragment"]);
+    let added_in = git(&["rev-parse", "--short=7", "HEAD"]);
+    self::common::cargo_changelog_cmd(temp_dir.path())
+        .args(["create-release", "minor"])
+        .assert()
+        .success();
+    git(&["add", "."]);
+    git(&["commit", "-m", "Release"]);
+
+    add();
+    self::common::cargo_changelog_cmd(temp_dir.path())
+        .args(["generate-changelog", "--all", "--allow-dirty"])
+        .assert()
+        .success();
+    let changelog = std::fs::read_to_string(temp_dir.path().join("CHANGELOG.md")).unwrap();
+    assert!(
+        changelog.contains(&format!("0.1.0: {added_in}\n")),
+        "{changelog}"
+    );
+    assert!(
+        changelog.contains("unreleased: uncommitted\n"),
+        "{changelog}"
+    );
+}
+
+#[test]
+fn generate_changelog_command_filters_by_audience() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("cargo-changelog")
+        .tempdir()
+        .unwrap();
+    self::common::init_git(temp_dir.path());
+    self::common::init_cargo(
+        temp_dir.path(),
+        "generate_changelog_command_filters_by_audience",
+    );
+    self::common::init_cargo_changelog(temp_dir.path());
+
+    let config_file_path = temp_dir.path().join("changelog.toml");
+    let mut config = std::fs::read_to_string(&config_file_path).unwrap();
+    config.push_str(
+        "\n[header_fields.audience]\ntype = [\"internal\", \"external\"]\nrequired = false\n",
+    );
+    std::fs::write(&config_file_path, config).unwrap();
+
+    for (subject, extra_args) in [
+        ("For everyone", &[][..]),
+        ("For maintainers", &["--set", "audience=internal"][..]),
+    ] {
+        self::common::cargo_changelog_add(temp_dir.path())
+            .args([
+                "--format=toml",
+                "--set",
+                "issue=123",
+                "--set",
+                &format!("subject={subject}"),
+                "--set",
+                "type=Misc",
+            ])
+            .args(extra_args)
+            .assert()
+            .success();
+    }
+
+    let generate = |audience: &str| {
+        self::common::cargo_changelog_cmd(temp_dir.path())
+            .args([
+                "generate-changelog",
+                "--all",
+                "--allow-dirty",
+                "--audience",
+                audience,
+            ])
+            .assert()
+            .success();
+        std::fs::read_to_string(temp_dir.path().join("CHANGELOG.md")).unwrap()
+    };
+
+    let changelog = generate("external");
+    assert!(changelog.contains("For everyone"), "{changelog}");
+    assert!(!changelog.contains("For maintainers"), "{changelog}");
+
+    let changelog = generate("internal");
+    assert!(!changelog.contains("For everyone"), "{changelog}");
+    assert!(changelog.contains("For maintainers"), "{changelog}");
+}
+
+#[test]
+fn generate_changelog_command_uses_version_display_map() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("cargo-changelog")
+        .tempdir()
+        .unwrap();
+    self::common::init_git(temp_dir.path());
+    self::common::init_cargo(
+        temp_dir.path(),
+        "generate_changelog_command_uses_version_display_map",
+    );
+    self::common::init_cargo_changelog(temp_dir.path());
+
+    let config_file_path = temp_dir.path().join("changelog.toml");
+    let mut config = std::fs::read_to_string(&config_file_path).unwrap();
+    config.push_str("\n[version_display_map]\n\"0.1.0\" = \"First Light\"\n");
+    std::fs::write(&config_file_path, config).unwrap();
+    std::fs::write(
+        temp_dir.path().join(".changelogs/template.md"),
+        "{{#each (reverse (sort_versions this.versions))}}## {{display_version this.version}}\n{{/each}}",
+    )
+    .unwrap();
+
+    for version in ["0.1.0", "0.2.0"] {
+        self::common::cargo_changelog_add(temp_dir.path())
+            .args([
+                "--format=toml",
+                "--set",
+                "issue=123",
+                "--set",
+                "subject=Test subject",
+            ])
+            .assert()
+            .success();
+        self::common::cargo_changelog_cmd(temp_dir.path())
+            .args(["create-release", "custom", version])
+            .assert()
+            .success();
+    }
+
+    self::common::cargo_changelog_cmd(temp_dir.path())
+        .args(["generate-changelog", "--allow-dirty"])
+        .assert()
+        .success();
+    let changelog = std::fs::read_to_string(temp_dir.path().join("CHANGELOG.md")).unwrap();
+    assert_eq!(changelog, "## 0.2.0\n## First Light\n");
+}
+
+#[test]
+fn generate_changelog_command_does_not_open_outside_of_terminal() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("cargo-changelog")
+        .tempdir()
+        .unwrap();
+    self::common::init_git(temp_dir.path());
+    self::common::init_cargo(
+        temp_dir.path(),
+        "generate_changelog_command_does_not_open_outside_of_terminal",
+    );
+    self::common::init_cargo_changelog(temp_dir.path());
+
+    // The editor would create the marker file if it was run
+    self::common::cargo_changelog_cmd(temp_dir.path())
+        .args(["generate-changelog", "--allow-dirty", "--open"])
+        .env("EDITOR", "touch opened")
+        .assert()
+        .success();
+    assert!(temp_dir.path().join("CHANGELOG.md").exists());
+    assert!(!temp_dir.path().join("opened").exists());
+}
+
+#[test]
+fn generate_changelog_command_ignores_versions_outside_fragment_dir() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("cargo-changelog")
+        .tempdir()
+        .unwrap();
+    // The repository itself is in a directory that looks like a version
+    let repo_dir = temp_dir.path().join("1.0.0");
+    std::fs::create_dir(&repo_dir).unwrap();
+    self::common::init_git(&repo_dir);
+    self::common::init_cargo_changelog(&repo_dir);
+
+    self::common::cargo_changelog_add(&repo_dir)
+        .args([
+            "--format=toml",
+            "--set",
+            "issue=123",
+            "--set",
+            "subject='Unreleased subject'",
+            "--set",
+            "type=Misc",
+        ])
+        .assert()
+        .success();
+
+    // A version header that does not match the version directory is ignored
+    let version_dir = repo_dir.join(".changelogs").join("0.1.0");
+    std::fs::create_dir(&version_dir).unwrap();
+    std::fs::write(
+        version_dir.join("released.md"),
+        "+++\nissue = 345\nsubject = \"Released subject\"\ntype = \"Misc\"\nversion = \"0.2.0\"\n+++\n",
+    )
+    .unwrap();
+
+    self::common::cargo_changelog_cmd(&repo_dir)
+        .args(["-v", "generate-changelog", "--allow-dirty"])
+        .assert()
+        .success()
+        .stderr(predicates::str::contains(
+            "Ignoring the version header field",
+        ));
+
+    let changelog = std::fs::read_to_string(repo_dir.join("CHANGELOG.md")).unwrap();
+    assert!(changelog.contains("Released subject"), "{changelog}");
+    assert!(!changelog.contains("Unreleased subject"), "{changelog}");
+    assert!(!changelog.contains("0.2.0"), "{changelog}");
+    assert!(!changelog.contains("1.0.0"), "{changelog}");
+}
@@ -64,6 +64,6 @@ fn generate_changelog_with_body_indented() {
     }
 
     let file_content = std::fs::read_to_string(changelog_file_path).unwrap();
-    let expected_content = "\n test123\n test456\n     test123\n     test456\n\n";
+    let expected_content = "\n test123\n test456\n     test123\n     test456\n";
     assert_eq!(file_content, expected_content);
 }
@@ -0,0 +1,156 @@
+use assert_cmd::Command;
+
+mod common;
+
+#[test]
+fn list_versions_command_lists_released_versions() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("cargo-changelog")
+        .tempdir()
+        .unwrap();
+    self::common::init_git(temp_dir.path());
+    self::common::init_cargo(temp_dir.path(), "list_versions_command");
+    self::common::init_cargo_changelog(temp_dir.path());
+
+    for _ in 0..2 {
+        self::common::cargo_changelog_add(temp_dir.path())
+            .args([
+                "--format=toml",
+                "--set",
+                "issue=123",
+                "--set",
+                "subject='Test subject'",
+                "--set",
+                "type=Misc",
+            ])
+            .assert()
+            .success();
+    }
+
+    Command::cargo_bin("cargo-changelog")
+        .unwrap()
+        .args(["create-release", "minor"])
+        .current_dir(&temp_dir)
+        .assert()
+        .success();
+
+    self::common::cargo_changelog_add(temp_dir.path())
+        .args([
+            "--format=toml",
+            "--set",
+            "issue=345",
+            "--set",
+            "subject='Unreleased subject'",
+            "--set",
+            "type=Misc",
+        ])
+        .assert()
+        .success();
+
+    self::common::cargo_changelog_cmd(temp_dir.path())
+        .args(["list-versions"])
+        .assert()
+        .success()
+        .stdout("0.1.0 (2)\n");
+
+    self::common::cargo_changelog_cmd(temp_dir.path())
+        .args(["list-versions", "--format", "json"])
+        .assert()
+        .success()
+        .stdout("[{\"version\":\"0.1.0\",\"fragments\":2}]\n");
+}
+
+#[test]
+fn list_versions_command_respects_max_walk_depth() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("cargo-changelog")
+        .tempdir()
+        .unwrap();
+    self::common::init_git(temp_dir.path());
+    self::common::init_cargo_changelog(temp_dir.path());
+
+    let version_dir = temp_dir.path().join(".changelogs").join("0.1.0");
+    let deep_dir = version_dir.join("a").join("b");
+    std::fs::create_dir_all(&deep_dir).unwrap();
+    let fragment = "+++\nsubject = \"test\"\n+++\n";
+    std::fs::write(version_dir.join("shallow.md"), fragment).unwrap();
+    std::fs::write(deep_dir.join("deep.md"), fragment).unwrap();
+
+    self::common::cargo_changelog_cmd(temp_dir.path())
+        .args(["-v", "list-versions"])
+        .assert()
+        .success()
+        .stdout("0.1.0 (1)\n")
+        .stderr(predicates::str::contains(
+            "Skipping directory deeper than max_walk_depth (3)",
+        ));
+
+    let config_file_path = temp_dir.path().join("changelog.toml");
+    let config = std::fs::read_to_string(&config_file_path).unwrap();
+    std::fs::write(config_file_path, format!("max_walk_depth = 4\n{config}")).unwrap();
+
+    self::common::cargo_changelog_cmd(temp_dir.path())
+        .args(["list-versions"])
+        .assert()
+        .success()
+        .stdout("0.1.0 (2)\n");
+}
+
+#[test]
+fn list_versions_command_matches_generated_changelog() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("cargo-changelog")
+        .tempdir()
+        .unwrap();
+    self::common::init_git(temp_dir.path());
+    self::common::init_cargo(temp_dir.path(), "list_versions_command");
+    self::common::init_cargo_changelog(temp_dir.path());
+
+    let config_file_path = temp_dir.path().join("changelog.toml");
+    let config = std::fs::read_to_string(&config_file_path).unwrap();
+    std::fs::write(
+        &config_file_path,
+        format!("database = \"released.jsonl\"\n{config}"),
+    )
+    .unwrap();
+
+    for _ in 0..2 {
+        self::common::cargo_changelog_add(temp_dir.path())
+            .args([
+                "--format=toml",
+                "--set",
+                "issue=123",
+                "--set",
+                "subject='Test subject'",
+                "--set",
+                "type=Misc",
+            ])
+            .assert()
+            .success();
+    }
+    self::common::cargo_changelog_cmd(temp_dir.path())
+        .args(["create-release", "minor"])
+        .assert()
+        .success();
+
+    // Compacted fragments are still listed
+    self::common::cargo_changelog_cmd(temp_dir.path())
+        .args(["compact"])
+        .assert()
+        .success();
+
+    // Skipped fragments are not
+    let version_dir = temp_dir.path().join(".changelogs").join("0.2.0");
+    std::fs::create_dir_all(&version_dir).unwrap();
+    std::fs::write(
+        version_dir.join("skipped.md"),
+        "+++\nsubject = \"test\"\nskip = true\n+++\n",
+    )
+    .unwrap();
+
+    self::common::cargo_changelog_cmd(temp_dir.path())
+        .args(["list-versions"])
+        .assert()
+        .success()
+        .stdout("0.1.0 (2)\n");
+}
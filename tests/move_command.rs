@@ -0,0 +1,105 @@
+use assert_cmd::Command;
+
+mod common;
+
+fn setup(name: &str) -> tempfile::TempDir {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("cargo-changelog")
+        .tempdir()
+        .unwrap();
+    self::common::init_git(temp_dir.path());
+    self::common::init_cargo(temp_dir.path(), name);
+    self::common::init_cargo_changelog(temp_dir.path());
+    temp_dir
+}
+
+fn write_fragment(dir: &std::path::Path, version: &str, name: &str) -> std::path::PathBuf {
+    let version_dir = dir.join(".changelogs").join(version);
+    std::fs::create_dir_all(&version_dir).unwrap();
+    let path = version_dir.join(name);
+    std::fs::write(&path, "+++\nsubject = \"test\"\n+++\n").unwrap();
+    path
+}
+
+#[test]
+fn move_command_moves_fragment_to_version_dir() {
+    let temp_dir = setup("move_command_moves_fragment_to_version_dir");
+    write_fragment(temp_dir.path(), "0.1.0", "fragment.md");
+
+    Command::cargo_bin("cargo-changelog")
+        .unwrap()
+        .args(["move", ".changelogs/0.1.0/fragment.md", "0.2.0"])
+        .current_dir(&temp_dir)
+        .assert()
+        .success();
+
+    let changelogs = temp_dir.path().join(".changelogs");
+    assert!(!changelogs.join("0.1.0").join("fragment.md").exists());
+    assert!(changelogs.join("0.2.0").join("fragment.md").is_file());
+}
+
+#[test]
+fn move_command_refuses_to_overwrite() {
+    let temp_dir = setup("move_command_refuses_to_overwrite");
+    write_fragment(temp_dir.path(), "0.1.0", "fragment.md");
+    write_fragment(temp_dir.path(), "0.2.0", "fragment.md");
+
+    Command::cargo_bin("cargo-changelog")
+        .unwrap()
+        .args(["move", ".changelogs/0.1.0/fragment.md", "0.2.0"])
+        .current_dir(&temp_dir)
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("Fragment already exists"));
+
+    assert!(temp_dir
+        .path()
+        .join(".changelogs")
+        .join("0.1.0")
+        .join("fragment.md")
+        .is_file());
+}
+
+#[test]
+fn move_command_rejects_invalid_version() {
+    let temp_dir = setup("move_command_rejects_invalid_version");
+    write_fragment(temp_dir.path(), "0.1.0", "fragment.md");
+
+    Command::cargo_bin("cargo-changelog")
+        .unwrap()
+        .args(["move", ".changelogs/0.1.0/fragment.md", "next"])
+        .current_dir(&temp_dir)
+        .assert()
+        .failure();
+
+    assert!(!temp_dir.path().join(".changelogs").join("next").exists());
+}
+
+#[test]
+fn move_command_updates_version_header_with_flat_layout() {
+    let temp_dir = setup("move_command_updates_version_header_with_flat_layout");
+    let config_file_path = temp_dir.path().join("changelog.toml");
+    let config = std::fs::read_to_string(&config_file_path).unwrap();
+    std::fs::write(config_file_path, format!("layout = \"flat\"\n{config}")).unwrap();
+
+    let path = temp_dir.path().join(".changelogs").join("fragments");
+    std::fs::create_dir_all(&path).unwrap();
+    std::fs::write(
+        path.join("fragment.md"),
+        "+++\nsubject = \"test\"\nversion = \"0.1.0\"\n+++\n",
+    )
+    .unwrap();
+
+    Command::cargo_bin("cargo-changelog")
+        .unwrap()
+        .args(["move", ".changelogs/fragments/fragment.md", "0.2.0"])
+        .current_dir(&temp_dir)
+        .assert()
+        .success();
+
+    let fragment = std::fs::read_to_string(path.join("fragment.md")).unwrap();
+    assert!(
+        fragment.contains("version = \"0.2.0\""),
+        "Version not updated: {fragment}"
+    );
+}
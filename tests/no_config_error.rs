@@ -35,3 +35,24 @@ fn no_configuration_file_errors_with_error_message() {
             "Configuration file does not exist",
         ));
 }
+
+#[test]
+fn explicit_missing_configuration_file_errors() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("cargo-changelog")
+        .tempdir()
+        .unwrap();
+    self::common::init_git(temp_dir.path());
+    self::common::init_cargo_changelog(temp_dir.path());
+
+    // The discovered configuration must not be used as fallback
+    Command::cargo_bin("cargo-changelog")
+        .unwrap()
+        .args(["list-versions", "--config", "does-not-exist.toml"])
+        .current_dir(&temp_dir)
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains(
+            "Configuration file does not exist",
+        ));
+}
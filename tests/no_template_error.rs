@@ -0,0 +1,42 @@
+use assert_cmd::Command;
+
+mod common;
+
+#[test]
+fn no_template_file_errors_with_error_message() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("cargo-changelog")
+        .tempdir()
+        .unwrap();
+    self::common::init_git(temp_dir.path());
+    self::common::init_cargo_changelog(temp_dir.path());
+
+    let template_path = temp_dir.path().join(".changelogs").join("template.md");
+    std::fs::remove_file(&template_path).unwrap();
+
+    Command::cargo_bin("cargo-changelog")
+        .unwrap()
+        .args(["generate-changelog"])
+        .current_dir(&temp_dir)
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("Template file does not exist"));
+}
+
+#[test]
+fn missing_template_override_errors_with_error_message() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("cargo-changelog")
+        .tempdir()
+        .unwrap();
+    self::common::init_git(temp_dir.path());
+    self::common::init_cargo_changelog(temp_dir.path());
+
+    Command::cargo_bin("cargo-changelog")
+        .unwrap()
+        .args(["generate-changelog", "--template", "website.md"])
+        .current_dir(&temp_dir)
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("Template file does not exist"));
+}
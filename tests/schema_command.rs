@@ -0,0 +1,29 @@
+mod common;
+
+#[test]
+fn schema_command_describes_header_fields() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("cargo-changelog")
+        .tempdir()
+        .unwrap();
+    self::common::init_git(temp_dir.path());
+    self::common::init_cargo_changelog(temp_dir.path());
+
+    let output = self::common::cargo_changelog_cmd(temp_dir.path())
+        .args(["schema"])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+
+    let schema: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(schema["type"], "object");
+    assert_eq!(schema["properties"]["subject"]["type"], "string");
+    assert_eq!(schema["properties"]["issue"]["type"], "integer");
+    assert_eq!(
+        schema["properties"]["type"]["enum"],
+        serde_json::json!(["Bugfix", "Feature", "Misc"])
+    );
+    assert_eq!(schema["required"], serde_json::json!(["subject"]));
+}
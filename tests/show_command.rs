@@ -0,0 +1,101 @@
+mod common;
+
+#[test]
+fn show_command_without_color_indents_text() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("cargo-changelog")
+        .tempdir()
+        .unwrap();
+    self::common::init_git(temp_dir.path());
+    self::common::init_cargo_changelog(temp_dir.path());
+
+    self::common::cargo_changelog_add(temp_dir.path())
+        .args([
+            "--format=toml",
+            "--set",
+            "issue=123",
+            "--set",
+            "subject='This is some text'",
+            "--set",
+            "type=Bugfix",
+            "--read",
+            "-",
+        ])
+        .write_stdin("test123\ntest456")
+        .assert()
+        .success();
+
+    let output = self::common::cargo_changelog_cmd(temp_dir.path())
+        .args(["show", "--no-color"])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let output = String::from_utf8(output).unwrap();
+
+    assert!(!output.contains('\x1b'), "Output is colored: {output}");
+    assert!(
+        output.starts_with("unreleased\n"),
+        "Output does not start with version header: {output}"
+    );
+    assert!(
+        output.contains("\n    test123\n    test456\n"),
+        "Text is not indented: {output}"
+    );
+}
+
+fn add_authored_fragment(dir: &std::path::Path, subject: &str, author: Option<&str>) {
+    let mut cmd = self::common::cargo_changelog_add(dir);
+    cmd.args([
+        "--format=toml",
+        "--set",
+        "issue=123",
+        "--set",
+        &format!("subject={subject}"),
+        "--set",
+        "type=Misc",
+    ]);
+    if let Some(author) = author {
+        cmd.args(["--set", &format!("author={author}")]);
+    }
+    cmd.assert().success();
+}
+
+#[test]
+fn show_command_filters_by_author() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("cargo-changelog")
+        .tempdir()
+        .unwrap();
+    self::common::init_git(temp_dir.path());
+    self::common::init_cargo_changelog(temp_dir.path());
+
+    let config_file_path = temp_dir.path().join("changelog.toml");
+    let config = std::fs::read_to_string(&config_file_path).unwrap();
+    std::fs::write(
+        config_file_path,
+        format!("{config}\n[header_fields.author]\ntype = \"string\"\nrequired = false\n"),
+    )
+    .unwrap();
+
+    add_authored_fragment(temp_dir.path(), "Mine", Some("alice"));
+    add_authored_fragment(temp_dir.path(), "Theirs", Some("bob"));
+    add_authored_fragment(temp_dir.path(), "Nobodys", None);
+
+    let output = self::common::cargo_changelog_cmd(temp_dir.path())
+        .args(["show", "--no-color", "--author", "alice"])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let output = String::from_utf8(output).unwrap();
+
+    assert!(
+        output.contains("subject: Mine"),
+        "Missing fragment: {output}"
+    );
+    assert!(!output.contains("Theirs"), "Not filtered: {output}");
+    assert!(!output.contains("Nobodys"), "Not filtered: {output}");
+}
@@ -0,0 +1,88 @@
+mod common;
+
+#[test]
+fn stats_command_counts_fragments() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("cargo-changelog")
+        .tempdir()
+        .unwrap();
+    self::common::init_git(temp_dir.path());
+    self::common::init_cargo_changelog(temp_dir.path());
+
+    for ty in ["Bugfix", "Feature", "Bugfix"] {
+        self::common::cargo_changelog_add(temp_dir.path())
+            .args([
+                "--format=toml",
+                "--set",
+                "issue=123",
+                "--set",
+                "subject='Test subject'",
+                "--set",
+                &format!("type={ty}"),
+            ])
+            .assert()
+            .success();
+    }
+
+    self::common::cargo_changelog_cmd(temp_dir.path())
+        .args(["stats"])
+        .assert()
+        .success()
+        .stdout(predicates::str::starts_with("Total: 3\n"))
+        .stdout(predicates::str::contains("By version:\n  unreleased: 3\n"))
+        .stdout(predicates::str::contains(
+            "By type:\n  Bugfix: 2\n  Feature: 1\n",
+        ));
+
+    self::common::cargo_changelog_cmd(temp_dir.path())
+        .args(["stats", "--format", "json"])
+        .assert()
+        .success()
+        .stdout(concat!(
+            r#"{"total":3,"versions":{"unreleased":3},"#,
+            r#""types":{"Bugfix":2,"Feature":1},"authors":{"(none)":3}}"#,
+            "\n"
+        ));
+}
+
+#[test]
+fn stats_command_skips_ignored_fragments() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("cargo-changelog")
+        .tempdir()
+        .unwrap();
+    self::common::init_git(temp_dir.path());
+    self::common::init_cargo_changelog(temp_dir.path());
+
+    for ty in ["Bugfix", "Feature"] {
+        self::common::cargo_changelog_add(temp_dir.path())
+            .args([
+                "--format=toml",
+                "--set",
+                "issue=123",
+                "--set",
+                "subject='Test subject'",
+                "--set",
+                &format!("type={ty}"),
+            ])
+            .assert()
+            .success();
+    }
+
+    let fragment_dir = temp_dir.path().join(".changelogs");
+    let unreleased_dir = fragment_dir.join("unreleased");
+    let fragment_path = std::fs::read_dir(&unreleased_dir)
+        .unwrap()
+        .next()
+        .unwrap()
+        .unwrap()
+        .path();
+    std::fs::rename(fragment_path, unreleased_dir.join("wip-fragment.md")).unwrap();
+    std::fs::write(fragment_dir.join(".changelogignore"), "# WIP\nwip-*.md\n").unwrap();
+
+    self::common::cargo_changelog_cmd(temp_dir.path())
+        .args(["stats"])
+        .assert()
+        .success()
+        .stdout(predicates::str::starts_with("Total: 1\n"));
+}
@@ -0,0 +1,61 @@
+use assert_cmd::Command;
+
+mod common;
+
+#[test]
+fn quiet_still_reports_errors() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("cargo-changelog")
+        .tempdir()
+        .unwrap();
+    self::common::init_git(temp_dir.path());
+
+    Command::cargo_bin("cargo-changelog")
+        .unwrap()
+        .args(["-q", "generate-changelog"])
+        .current_dir(&temp_dir)
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains(
+            "Configuration file does not exist",
+        ));
+}
+
+#[test]
+fn verbose_logging_does_not_write_to_stdout() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("cargo-changelog")
+        .tempdir()
+        .unwrap();
+    self::common::init_git(temp_dir.path());
+    self::common::init_cargo_changelog(temp_dir.path());
+
+    self::common::cargo_changelog_add(temp_dir.path())
+        .args([
+            "--format=toml",
+            "--set",
+            "issue=123",
+            "--set",
+            "subject='Test subject'",
+            "--set",
+            "type=Misc",
+        ])
+        .assert()
+        .success();
+
+    let output = Command::cargo_bin("cargo-changelog")
+        .unwrap()
+        .args(["-vvvv", "list-versions", "--format", "json"])
+        .current_dir(&temp_dir)
+        .assert()
+        .success()
+        .get_output()
+        .clone();
+
+    assert!(!output.stderr.is_empty(), "Expected log output on stderr");
+    assert!(
+        serde_json::from_slice::<serde_json::Value>(&output.stdout).is_ok(),
+        "stdout is not only JSON: {}",
+        String::from_utf8_lossy(&output.stdout)
+    );
+}
@@ -48,3 +48,665 @@ fn verify_metadata_command_succeeds_with_empty_changelog() {
         .assert()
         .success();
 }
+
+fn setup_version_check(name: &str) -> tempfile::TempDir {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("cargo-changelog")
+        .tempdir()
+        .unwrap();
+    self::common::init_git(temp_dir.path());
+    self::common::init_cargo(temp_dir.path(), name);
+    self::common::init_cargo_changelog(temp_dir.path());
+
+    let config_file_path = temp_dir.path().join("changelog.toml");
+    let config = std::fs::read_to_string(&config_file_path).unwrap();
+    std::fs::write(
+        config_file_path,
+        format!("max_version_bump = \"minor\"\n{config}"),
+    )
+    .unwrap();
+    temp_dir
+}
+
+fn write_fragment(dir: &std::path::Path, version: &str) {
+    let version_dir = dir.join(".changelogs").join(version);
+    std::fs::create_dir_all(&version_dir).unwrap();
+    std::fs::write(
+        version_dir.join("fragment.md"),
+        "+++\nsubject = \"test subject\"\n+++\n",
+    )
+    .unwrap();
+}
+
+#[test]
+fn verify_metadata_command_warns_about_version_too_far_ahead() {
+    let temp_dir = setup_version_check("verify_metadata_warns_about_version_too_far_ahead");
+    // `cargo init` creates a crate with version 0.1.0
+    write_fragment(temp_dir.path(), "0.3.0");
+
+    let output = Command::cargo_bin("cargo-changelog")
+        .unwrap()
+        .args(["-v", "verify-metadata"])
+        .current_dir(&temp_dir)
+        .assert()
+        .success();
+
+    let stderr = String::from_utf8(output.get_output().stderr.clone()).unwrap();
+    assert!(
+        stderr.contains("Version 0.3.0 is ahead of the next expected version 0.2.0"),
+        "Missing warning: {stderr}"
+    );
+}
+
+#[test]
+fn verify_metadata_command_accepts_next_version() {
+    let temp_dir = setup_version_check("verify_metadata_accepts_next_version");
+    write_fragment(temp_dir.path(), "0.2.0");
+
+    let output = Command::cargo_bin("cargo-changelog")
+        .unwrap()
+        .args(["-v", "verify-metadata"])
+        .current_dir(&temp_dir)
+        .assert()
+        .success();
+
+    let stderr = String::from_utf8(output.get_output().stderr.clone()).unwrap();
+    assert!(
+        !stderr.contains("is ahead of"),
+        "Unexpected warning: {stderr}"
+    );
+}
+
+#[test]
+fn verify_metadata_command_fails_for_fragment_without_version_dir() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("cargo-changelog")
+        .tempdir()
+        .unwrap();
+    self::common::init_git(temp_dir.path());
+    self::common::init_cargo_changelog(temp_dir.path());
+
+    // "v0.1.0" is not a valid semver version
+    write_fragment(temp_dir.path(), "v0.1.0");
+
+    let output = Command::cargo_bin("cargo-changelog")
+        .unwrap()
+        .args(["verify-metadata"])
+        .current_dir(&temp_dir)
+        .assert()
+        .failure();
+
+    let stderr = String::from_utf8(output.get_output().stderr.clone()).unwrap();
+    assert!(stderr.contains("v0.1.0"), "Missing path in error: {stderr}");
+}
+
+fn write_quoted_issue_fragment(dir: &std::path::Path) {
+    let unreleased_dir = dir.join(".changelogs").join("unreleased");
+    std::fs::create_dir_all(&unreleased_dir).unwrap();
+    std::fs::write(
+        unreleased_dir.join("fragment.md"),
+        "+++\nissue = \"123\"\nsubject = \"test subject\"\n+++\n",
+    )
+    .unwrap();
+}
+
+#[test]
+fn verify_metadata_command_fails_for_wrong_type() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("cargo-changelog")
+        .tempdir()
+        .unwrap();
+    self::common::init_git(temp_dir.path());
+    self::common::init_cargo_changelog(temp_dir.path());
+    write_quoted_issue_fragment(temp_dir.path());
+
+    Command::cargo_bin("cargo-changelog")
+        .unwrap()
+        .args(["verify-metadata"])
+        .current_dir(&temp_dir)
+        .assert()
+        .failure();
+}
+
+#[test]
+fn verify_metadata_command_coerces_scalars() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("cargo-changelog")
+        .tempdir()
+        .unwrap();
+    self::common::init_git(temp_dir.path());
+    self::common::init_cargo_changelog(temp_dir.path());
+    write_quoted_issue_fragment(temp_dir.path());
+
+    let config_file_path = temp_dir.path().join("changelog.toml");
+    let config = std::fs::read_to_string(&config_file_path).unwrap();
+    std::fs::write(config_file_path, format!("coerce_scalars = true\n{config}")).unwrap();
+
+    Command::cargo_bin("cargo-changelog")
+        .unwrap()
+        .args(["verify-metadata"])
+        .current_dir(&temp_dir)
+        .assert()
+        .success();
+}
+
+#[test]
+fn verify_metadata_command_normalizes_enum_case() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("cargo-changelog")
+        .tempdir()
+        .unwrap();
+    self::common::init_git(temp_dir.path());
+    self::common::init_cargo_changelog(temp_dir.path());
+
+    let unreleased_dir = temp_dir.path().join(".changelogs").join("unreleased");
+    std::fs::write(
+        unreleased_dir.join("fragment.md"),
+        "+++\nissue = 123\nsubject = \"test subject\"\ntype = \"BUGFIX\"\n+++\n",
+    )
+    .unwrap();
+
+    self::common::cargo_changelog_cmd(temp_dir.path())
+        .args(["verify-metadata"])
+        .assert()
+        .failure();
+
+    let config_file_path = temp_dir.path().join("changelog.toml");
+    let config = std::fs::read_to_string(&config_file_path).unwrap();
+    std::fs::write(
+        config_file_path,
+        format!("normalize_enum_case = true\n{config}"),
+    )
+    .unwrap();
+
+    self::common::cargo_changelog_cmd(temp_dir.path())
+        .args(["verify-metadata"])
+        .assert()
+        .success();
+
+    std::fs::write(
+        unreleased_dir.join("fragment.md"),
+        "+++\nissue = 123\nsubject = \"test subject\"\ntype = \"bug\"\n+++\n",
+    )
+    .unwrap();
+
+    self::common::cargo_changelog_cmd(temp_dir.path())
+        .args(["verify-metadata"])
+        .assert()
+        .failure();
+}
+
+#[test]
+fn verify_metadata_command_prints_problems_as_json() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("cargo-changelog")
+        .tempdir()
+        .unwrap();
+    self::common::init_git(temp_dir.path());
+    self::common::init_cargo_changelog(temp_dir.path());
+    write_quoted_issue_fragment(temp_dir.path());
+
+    let output = Command::cargo_bin("cargo-changelog")
+        .unwrap()
+        .args(["verify-metadata", "--format", "json"])
+        .current_dir(&temp_dir)
+        .assert()
+        .failure();
+
+    let problems: serde_json::Value = serde_json::from_slice(&output.get_output().stdout).unwrap();
+    let problems = problems.as_array().unwrap();
+    assert_eq!(problems.len(), 1, "Expected one problem: {problems:?}");
+    assert_eq!(problems[0]["kind"], "fragment-parsing");
+    assert!(problems[0]["path"]
+        .as_str()
+        .unwrap()
+        .ends_with("fragment.md"));
+    assert!(
+        problems[0]["message"].as_str().unwrap().contains("issue"),
+        "Message does not mention field: {problems:?}"
+    );
+}
+
+#[test]
+fn verify_metadata_command_prints_empty_json_without_problems() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("cargo-changelog")
+        .tempdir()
+        .unwrap();
+    self::common::init_git(temp_dir.path());
+    self::common::init_cargo_changelog(temp_dir.path());
+
+    let output = Command::cargo_bin("cargo-changelog")
+        .unwrap()
+        .args(["verify-metadata", "--format", "json"])
+        .current_dir(&temp_dir)
+        .assert()
+        .success();
+
+    let problems: serde_json::Value = serde_json::from_slice(&output.get_output().stdout).unwrap();
+    assert_eq!(problems, serde_json::json!([]));
+}
+
+#[test]
+fn verify_metadata_command_warns_about_duplicate_version_dirs() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("cargo-changelog")
+        .tempdir()
+        .unwrap();
+    self::common::init_git(temp_dir.path());
+    self::common::init_cargo_changelog(temp_dir.path());
+
+    write_fragment(temp_dir.path(), "1.0.0-rc.1");
+    write_fragment(temp_dir.path(), "1.0.0-RC.1");
+    write_fragment(temp_dir.path(), "1.0.0");
+
+    let output = Command::cargo_bin("cargo-changelog")
+        .unwrap()
+        .args(["-v", "verify-metadata"])
+        .current_dir(&temp_dir)
+        .assert()
+        .success();
+
+    let stderr = String::from_utf8(output.get_output().stderr.clone()).unwrap();
+    assert!(
+        stderr.contains("Multiple directories for version 1.0.0-rc.1: 1.0.0-RC.1, 1.0.0-rc.1"),
+        "Missing warning: {stderr}"
+    );
+    assert!(
+        !stderr.contains("Multiple directories for version 1.0.0:"),
+        "Unexpected warning: {stderr}"
+    );
+}
+
+#[test]
+fn verify_metadata_command_warns_about_build_metadata_dirs_only_when_merged() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("cargo-changelog")
+        .tempdir()
+        .unwrap();
+    self::common::init_git(temp_dir.path());
+    self::common::init_cargo_changelog(temp_dir.path());
+
+    write_fragment(temp_dir.path(), "1.0.0");
+    write_fragment(temp_dir.path(), "1.0.0+build.1");
+
+    let verify = || {
+        let output = Command::cargo_bin("cargo-changelog")
+            .unwrap()
+            .args(["-v", "verify-metadata"])
+            .current_dir(&temp_dir)
+            .assert()
+            .success();
+        String::from_utf8(output.get_output().stderr.clone()).unwrap()
+    };
+
+    // With the default "separate" policy, the versions are distinct
+    let stderr = verify();
+    assert!(
+        !stderr.contains("Multiple directories"),
+        "Unexpected warning: {stderr}"
+    );
+
+    prepend_config(temp_dir.path(), r#"build_metadata = "merge""#);
+    let stderr = verify();
+    assert!(
+        stderr.contains("Multiple directories for version 1.0.0: 1.0.0, 1.0.0+build.1"),
+        "Missing warning: {stderr}"
+    );
+}
+
+fn prepend_config(dir: &std::path::Path, setting: &str) {
+    let config_file_path = dir.join("changelog.toml");
+    let config = std::fs::read_to_string(&config_file_path).unwrap();
+    std::fs::write(config_file_path, format!("{setting}\n{config}")).unwrap();
+}
+
+#[test]
+fn verify_metadata_command_fails_for_too_many_fragments() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("cargo-changelog")
+        .tempdir()
+        .unwrap();
+    self::common::init_git(temp_dir.path());
+    self::common::init_cargo_changelog(temp_dir.path());
+    prepend_config(temp_dir.path(), "max_fragments_per_version = 1");
+
+    let version_dir = temp_dir.path().join(".changelogs").join("0.1.0");
+    std::fs::create_dir_all(&version_dir).unwrap();
+    for name in ["a.md", "b.md"] {
+        std::fs::write(version_dir.join(name), "+++\nsubject = \"test\"\n+++\n").unwrap();
+    }
+
+    Command::cargo_bin("cargo-changelog")
+        .unwrap()
+        .args(["verify-metadata"])
+        .current_dir(&temp_dir)
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains(
+            "Version 0.1.0 has 2 fragments, more than the maximum of 1",
+        ));
+}
+
+#[test]
+fn verify_metadata_command_fails_for_breaking_change_in_patch_release() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("cargo-changelog")
+        .tempdir()
+        .unwrap();
+    self::common::init_git(temp_dir.path());
+    self::common::init_cargo_changelog(temp_dir.path());
+    prepend_config(temp_dir.path(), "breaking_change_field = \"breaking\"");
+
+    for version in ["0.2.0", "0.2.1"] {
+        let version_dir = temp_dir.path().join(".changelogs").join(version);
+        std::fs::create_dir_all(&version_dir).unwrap();
+        std::fs::write(
+            version_dir.join("fragment.md"),
+            "+++\nsubject = \"test\"\nbreaking = true\n+++\n",
+        )
+        .unwrap();
+    }
+
+    let output = Command::cargo_bin("cargo-changelog")
+        .unwrap()
+        .args(["verify-metadata", "--format", "json"])
+        .current_dir(&temp_dir)
+        .assert()
+        .failure();
+
+    let problems: serde_json::Value = serde_json::from_slice(&output.get_output().stdout).unwrap();
+    let problems = problems.as_array().unwrap();
+    assert_eq!(problems.len(), 1, "Expected one problem: {problems:?}");
+    assert_eq!(problems[0]["kind"], "breaking-change-in-patch-release");
+    assert!(problems[0]["message"].as_str().unwrap().contains("0.2.1"));
+}
+
+#[test]
+fn verify_metadata_command_fails_for_too_long_body() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("cargo-changelog")
+        .tempdir()
+        .unwrap();
+    self::common::init_git(temp_dir.path());
+    self::common::init_cargo_changelog(temp_dir.path());
+    prepend_config(temp_dir.path(), "max_body_length = 10");
+
+    let version_dir = temp_dir.path().join(".changelogs").join("0.1.0");
+    std::fs::create_dir_all(&version_dir).unwrap();
+    std::fs::write(
+        version_dir.join("short.md"),
+        "+++\nsubject = \"test\"\n+++\nShort\n",
+    )
+    .unwrap();
+    std::fs::write(
+        version_dir.join("long.md"),
+        "+++\nsubject = \"test\"\n+++\nThis text is way too long\n",
+    )
+    .unwrap();
+
+    let output = Command::cargo_bin("cargo-changelog")
+        .unwrap()
+        .args(["verify-metadata", "--format", "json"])
+        .current_dir(&temp_dir)
+        .assert()
+        .failure();
+
+    let problems: serde_json::Value = serde_json::from_slice(&output.get_output().stdout).unwrap();
+    let problems = problems.as_array().unwrap();
+    assert_eq!(problems.len(), 1, "Expected one problem: {problems:?}");
+    assert_eq!(problems[0]["kind"], "body-too-long");
+    assert!(problems[0]["path"].as_str().unwrap().ends_with("long.md"));
+}
+
+#[test]
+fn verify_metadata_command_fails_for_missing_required_body() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("cargo-changelog")
+        .tempdir()
+        .unwrap();
+    self::common::init_git(temp_dir.path());
+    self::common::init_cargo_changelog(temp_dir.path());
+    prepend_config(temp_dir.path(), r#"require_body_for = ["feature"]"#);
+
+    let version_dir = temp_dir.path().join(".changelogs").join("0.1.0");
+    std::fs::create_dir_all(&version_dir).unwrap();
+    for (name, ty, text) in [
+        ("feature_with_text.md", "Feature", "Explanation\n"),
+        ("feature_without_text.md", "Feature", "\n"),
+        ("misc_without_text.md", "Misc", ""),
+    ] {
+        std::fs::write(
+            version_dir.join(name),
+            format!("+++\nsubject = \"test\"\ntype = \"{ty}\"\n+++\n{text}"),
+        )
+        .unwrap();
+    }
+
+    let output = Command::cargo_bin("cargo-changelog")
+        .unwrap()
+        .args(["verify-metadata", "--format", "json"])
+        .current_dir(&temp_dir)
+        .assert()
+        .failure();
+
+    let problems: serde_json::Value = serde_json::from_slice(&output.get_output().stdout).unwrap();
+    let problems = problems.as_array().unwrap();
+    assert_eq!(problems.len(), 1, "Expected one problem: {problems:?}");
+    assert_eq!(problems[0]["kind"], "body-required");
+    assert!(problems[0]["path"]
+        .as_str()
+        .unwrap()
+        .ends_with("feature_without_text.md"));
+}
+
+#[test]
+fn verify_metadata_command_accepts_byte_order_mark() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("cargo-changelog")
+        .tempdir()
+        .unwrap();
+    self::common::init_git(temp_dir.path());
+    self::common::init_cargo_changelog(temp_dir.path());
+
+    let unreleased_dir = temp_dir.path().join(".changelogs").join("unreleased");
+    std::fs::write(
+        unreleased_dir.join("bom.md"),
+        b"\xEF\xBB\xBF+++\nsubject = \"test subject\"\n+++\ntext\n",
+    )
+    .unwrap();
+
+    Command::cargo_bin("cargo-changelog")
+        .unwrap()
+        .args(["verify-metadata"])
+        .current_dir(&temp_dir)
+        .assert()
+        .success();
+
+    std::fs::write(
+        unreleased_dir.join("latin1.md"),
+        b"+++\nsubject = \"caf\xE9\"\n+++\n",
+    )
+    .unwrap();
+
+    Command::cargo_bin("cargo-changelog")
+        .unwrap()
+        .args(["verify-metadata", "--format", "json"])
+        .current_dir(&temp_dir)
+        .assert()
+        .failure()
+        .stdout(predicates::str::contains("latin1.md"))
+        .stdout(predicates::str::contains("not valid UTF-8"));
+}
+
+#[test]
+fn verify_metadata_command_fails_for_conflict_markers() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("cargo-changelog")
+        .tempdir()
+        .unwrap();
+    self::common::init_git(temp_dir.path());
+    self::common::init_cargo_changelog(temp_dir.path());
+
+    let version_dir = temp_dir.path().join(".changelogs").join("0.1.0");
+    std::fs::create_dir_all(&version_dir).unwrap();
+    std::fs::write(
+        version_dir.join("heading.md"),
+        "+++\nsubject = \"test\"\n+++\nHeading\n=======\n",
+    )
+    .unwrap();
+    std::fs::write(
+        version_dir.join("conflict.md"),
+        "+++\n<<<<<<< HEAD\nsubject = \"ours\"\n=======\nsubject = \"theirs\"\n>>>>>>> branch\n+++\n",
+    )
+    .unwrap();
+
+    let output = Command::cargo_bin("cargo-changelog")
+        .unwrap()
+        .args(["verify-metadata", "--format", "json"])
+        .current_dir(&temp_dir)
+        .assert()
+        .failure();
+
+    let problems: serde_json::Value = serde_json::from_slice(&output.get_output().stdout).unwrap();
+    let problems = problems.as_array().unwrap();
+    assert_eq!(problems.len(), 1, "Expected one problem: {problems:?}");
+    assert_eq!(problems[0]["kind"], "conflict-marker");
+    assert!(problems[0]["path"]
+        .as_str()
+        .unwrap()
+        .ends_with("conflict.md"));
+    assert!(problems[0]["message"].as_str().unwrap().contains("line 2"));
+}
+
+#[test]
+fn verify_metadata_command_verifies_single_file() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("cargo-changelog")
+        .tempdir()
+        .unwrap();
+    self::common::init_git(temp_dir.path());
+    self::common::init_cargo_changelog(temp_dir.path());
+
+    let version_dir = temp_dir.path().join(".changelogs").join("0.1.0");
+    std::fs::create_dir_all(&version_dir).unwrap();
+    std::fs::write(
+        version_dir.join("good.md"),
+        "+++\nsubject = \"test\"\n+++\ntext\n",
+    )
+    .unwrap();
+    std::fs::write(version_dir.join("broken.md"), "no header\n").unwrap();
+
+    Command::cargo_bin("cargo-changelog")
+        .unwrap()
+        .args(["verify", ".changelogs/0.1.0/good.md"])
+        .current_dir(&temp_dir)
+        .assert()
+        .success();
+
+    let output = Command::cargo_bin("cargo-changelog")
+        .unwrap()
+        .args([
+            "verify-metadata",
+            "--format",
+            "json",
+            ".changelogs/0.1.0/broken.md",
+            ".changelogs/0.1.0/missing.md",
+        ])
+        .current_dir(&temp_dir)
+        .assert()
+        .failure();
+
+    let problems: serde_json::Value = serde_json::from_slice(&output.get_output().stdout).unwrap();
+    let problems = problems.as_array().unwrap();
+    assert_eq!(problems.len(), 2, "Expected two problems: {problems:?}");
+    assert!(problems[0]["path"].as_str().unwrap().ends_with("broken.md"));
+    assert!(problems[1]["path"]
+        .as_str()
+        .unwrap()
+        .ends_with("missing.md"));
+}
+
+#[test]
+fn verify_metadata_command_fails_for_unrenderable_template() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("cargo-changelog")
+        .tempdir()
+        .unwrap();
+    self::common::init_git(temp_dir.path());
+    self::common::init_cargo_changelog(temp_dir.path());
+
+    let version_dir = temp_dir.path().join(".changelogs").join("0.1.0");
+    std::fs::create_dir_all(&version_dir).unwrap();
+    std::fs::write(
+        version_dir.join("good.md"),
+        "+++\nsubject = \"test\"\n+++\ntext\n",
+    )
+    .unwrap();
+
+    Command::cargo_bin("cargo-changelog")
+        .unwrap()
+        .args(["verify-metadata"])
+        .current_dir(&temp_dir)
+        .assert()
+        .success();
+
+    std::fs::write(
+        temp_dir.path().join(".changelogs").join("template.md"),
+        "{{#each versions}}{{unknown_helper this}}{{/each}}",
+    )
+    .unwrap();
+
+    let output = Command::cargo_bin("cargo-changelog")
+        .unwrap()
+        .args(["verify-metadata", "--format", "json"])
+        .current_dir(&temp_dir)
+        .assert()
+        .failure();
+
+    let problems: serde_json::Value = serde_json::from_slice(&output.get_output().stdout).unwrap();
+    let problems = problems.as_array().unwrap();
+    assert_eq!(problems.len(), 1, "Expected one problem: {problems:?}");
+    assert_eq!(problems[0]["kind"], "template-render");
+    assert!(problems[0]["path"]
+        .as_str()
+        .unwrap()
+        .ends_with("template.md"));
+}
+
+#[test]
+fn verify_metadata_command_fails_for_keys_in_other_case() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("cargo-changelog")
+        .tempdir()
+        .unwrap();
+    self::common::init_git(temp_dir.path());
+    self::common::init_cargo_changelog(temp_dir.path());
+    prepend_config(temp_dir.path(), r#"key_case = "kebab""#);
+
+    let version_dir = temp_dir.path().join(".changelogs").join("0.1.0");
+    std::fs::create_dir_all(&version_dir).unwrap();
+    for (name, key) in [("kebab.md", "pr-number"), ("snake.md", "pr_number")] {
+        std::fs::write(
+            version_dir.join(name),
+            format!("+++\nsubject = \"test\"\n{key} = 1\n+++\n"),
+        )
+        .unwrap();
+    }
+
+    let output = Command::cargo_bin("cargo-changelog")
+        .unwrap()
+        .args(["verify-metadata", "--format", "json"])
+        .current_dir(&temp_dir)
+        .assert()
+        .failure();
+
+    let problems: serde_json::Value = serde_json::from_slice(&output.get_output().stdout).unwrap();
+    let problems = problems.as_array().unwrap();
+    assert_eq!(problems.len(), 1, "Expected one problem: {problems:?}");
+    assert_eq!(problems[0]["kind"], "key-case");
+    assert!(problems[0]["message"]
+        .as_str()
+        .unwrap()
+        .contains("'pr_number', which is not kebab-case"));
+}